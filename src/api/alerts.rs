@@ -0,0 +1,76 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+const MAX_ALERTS: usize = 500;
+
+#[derive(Serialize, Clone)]
+pub struct Alert {
+    pub id: u64,
+    pub timestamp: String,
+    pub source: String,
+    pub subject: String,
+    pub severity: String,
+    pub message: String,
+}
+
+#[derive(Default)]
+pub struct AlertLog {
+    alerts: Mutex<VecDeque<Alert>>,
+    next_id: Mutex<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct AlertQuery {
+    pub source: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl AlertLog {
+    pub fn push(&self, source: &str, subject: &str, severity: &str, message: String) {
+        log::warn!("[{}] {} ({}): {}", source, subject, severity, message);
+
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+
+        let mut alerts = self.alerts.lock().unwrap();
+        if alerts.len() >= MAX_ALERTS {
+            alerts.pop_front();
+        }
+        alerts.push_back(Alert {
+            id,
+            timestamp: Utc::now().to_rfc3339(),
+            source: source.to_string(),
+            subject: subject.to_string(),
+            severity: severity.to_string(),
+            message,
+        });
+    }
+}
+
+pub async fn list_alerts(
+    query: web::Query<AlertQuery>,
+    data: web::Data<AlertLog>,
+) -> impl Responder {
+    let alerts = data.alerts.lock().unwrap();
+    let limit = query.limit.unwrap_or(100);
+
+    let recent: Vec<&Alert> = alerts
+        .iter()
+        .rev()
+        .filter(|a| query.source.as_ref().is_none_or(|s| &a.source == s))
+        .take(limit)
+        .collect();
+
+    HttpResponse::Ok().json(recent)
+}
+
+pub async fn clear_alerts(data: web::Data<AlertLog>) -> impl Responder {
+    data.alerts.lock().unwrap().clear();
+    HttpResponse::Ok().json("Alerts cleared")
+}
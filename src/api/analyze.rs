@@ -0,0 +1,429 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::process::Command;
+
+use crate::api::systemd;
+
+const MAX_DEPTH: u32 = 4;
+const MAX_NODES: usize = 250;
+
+const DEPENDENCY_PROPERTIES: &[&str] = &[
+    "Id",
+    "Description",
+    "LoadState",
+    "ActiveState",
+    "SubState",
+    "Requires",
+    "Wants",
+    "RequiredBy",
+    "WantedBy",
+    "After",
+    "Before",
+];
+
+#[derive(Deserialize)]
+pub struct DependencyQuery {
+    pub depth: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub description: String,
+    pub load_state: String,
+    pub active_state: String,
+    pub sub_state: String,
+    pub depth: u32,
+}
+
+// Edges always point from the dependent unit to its dependency. Before= is
+// folded into "after" with the endpoints swapped.
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: String,
+}
+
+#[derive(Serialize)]
+pub struct DependencyGraph {
+    pub root: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub truncated: bool,
+}
+
+#[derive(Serialize)]
+pub struct BootPhase {
+    pub name: String,
+    pub duration_ms: f64,
+}
+
+#[derive(Serialize)]
+pub struct BlameEntry {
+    pub unit: String,
+    pub duration_ms: f64,
+}
+
+#[derive(Serialize)]
+pub struct ChainEntry {
+    pub unit: String,
+    pub depth: usize,
+    pub activated_at_ms: Option<f64>,
+    pub duration_ms: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct BootAnalysis {
+    pub total_ms: Option<f64>,
+    pub phases: Vec<BootPhase>,
+    pub blame: Vec<BlameEntry>,
+    pub critical_chain: Vec<ChainEntry>,
+}
+
+#[derive(Deserialize)]
+pub struct BootQuery {
+    pub unit: Option<String>,
+}
+
+fn split_units(props: &HashMap<String, String>, key: &str) -> Vec<String> {
+    props
+        .get(key)
+        .map(|v| v.split_whitespace().map(|u| u.to_string()).collect())
+        .unwrap_or_default()
+}
+
+pub fn build_graph(root: &str, max_depth: u32) -> Result<DependencyGraph, String> {
+    let mut nodes: Vec<GraphNode> = Vec::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut edges: BTreeSet<GraphEdge> = BTreeSet::new();
+    let mut ordering: Vec<GraphEdge> = Vec::new();
+    let mut truncated = false;
+
+    let mut frontier = vec![root.to_string()];
+    seen.insert(root.to_string());
+
+    for depth in 0..=max_depth {
+        if frontier.is_empty() {
+            break;
+        }
+
+        let units = systemd::show_units(&frontier, DEPENDENCY_PROPERTIES)?;
+        let mut next = Vec::new();
+
+        for props in units {
+            let id = props.get("Id").cloned().unwrap_or_default();
+            if id.is_empty() {
+                continue;
+            }
+
+            let mut link = |from: &str, to: &str, kind: &str| {
+                edges.insert(GraphEdge {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    kind: kind.to_string(),
+                });
+            };
+            let mut neighbours = Vec::new();
+            for (key, kind, outgoing) in [
+                ("Requires", "requires", true),
+                ("Wants", "wants", true),
+                ("RequiredBy", "requires", false),
+                ("WantedBy", "wants", false),
+            ] {
+                for other in split_units(&props, key) {
+                    if outgoing {
+                        link(&id, &other, kind);
+                    } else {
+                        link(&other, &id, kind);
+                    }
+                    neighbours.push(other);
+                }
+            }
+            for other in split_units(&props, "After") {
+                ordering.push(GraphEdge {
+                    from: id.clone(),
+                    to: other,
+                    kind: "after".to_string(),
+                });
+            }
+            for other in split_units(&props, "Before") {
+                ordering.push(GraphEdge {
+                    from: other,
+                    to: id.clone(),
+                    kind: "after".to_string(),
+                });
+            }
+
+            if depth < max_depth {
+                for other in neighbours {
+                    if seen.len() >= MAX_NODES {
+                        truncated = true;
+                        break;
+                    }
+                    if seen.insert(other.clone()) {
+                        next.push(other);
+                    }
+                }
+            }
+
+            nodes.push(GraphNode {
+                description: props.get("Description").cloned().unwrap_or_default(),
+                load_state: props.get("LoadState").cloned().unwrap_or_default(),
+                active_state: props.get("ActiveState").cloned().unwrap_or_default(),
+                sub_state: props.get("SubState").cloned().unwrap_or_default(),
+                depth,
+                id,
+            });
+        }
+
+        frontier = next;
+    }
+
+    let included: BTreeSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let edges = edges
+        .into_iter()
+        .chain(ordering)
+        .filter(|e| included.contains(e.from.as_str()) && included.contains(e.to.as_str()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    Ok(DependencyGraph {
+        root: root.to_string(),
+        nodes,
+        edges,
+        truncated,
+    })
+}
+
+// Parses systemd time spans such as "1min 2.345s", "345ms" or "@5.1s" into milliseconds.
+pub fn parse_timespan_ms(value: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut matched = false;
+
+    for token in value.split_whitespace() {
+        let token = token.trim_start_matches(['@', '+']);
+        let split = token
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(token.len());
+        let (number, unit) = token.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let factor = match unit {
+            "us" | "µs" | "μs" => 0.001,
+            "ms" => 1.0,
+            "s" | "" => 1000.0,
+            "min" => 60_000.0,
+            "h" => 3_600_000.0,
+            "d" => 86_400_000.0,
+            _ => return None,
+        };
+        total += number * factor;
+        matched = true;
+    }
+
+    matched.then_some(total)
+}
+
+fn run_analyze(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("systemd-analyze")
+        .args(args)
+        .arg("--no-pager")
+        .output()
+        .map_err(|e| format!("Failed to execute systemd-analyze: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+pub fn parse_boot_time(output: &str) -> (Option<f64>, Vec<BootPhase>) {
+    let line = output
+        .lines()
+        .find(|l| l.starts_with("Startup finished in"))
+        .unwrap_or_default();
+    let body = line.trim_start_matches("Startup finished in").trim();
+    let (phases_part, total_part) = body.split_once('=').unwrap_or((body, ""));
+
+    let phases = phases_part
+        .split('+')
+        .filter_map(|part| {
+            let (time, name) = part.trim().split_once('(')?;
+            Some(BootPhase {
+                name: name.trim_end_matches(')').trim().to_string(),
+                duration_ms: parse_timespan_ms(time)?,
+            })
+        })
+        .collect();
+
+    (parse_timespan_ms(total_part.trim()), phases)
+}
+
+pub fn parse_blame(output: &str) -> Vec<BlameEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (time, unit) = line.rsplit_once(' ')?;
+            Some(BlameEntry {
+                unit: unit.to_string(),
+                duration_ms: parse_timespan_ms(time)?,
+            })
+        })
+        .collect()
+}
+
+pub fn parse_critical_chain(output: &str) -> Vec<ChainEntry> {
+    output
+        .lines()
+        .filter(|l| !l.starts_with("The time") && !l.trim().is_empty())
+        .filter_map(|line| {
+            let start = line.find(|c: char| !" │├└─".contains(c))?;
+            let indent = line[..start].chars().count();
+            let mut parts = line[start..].split_whitespace();
+            let unit = parts.next()?.to_string();
+
+            let mut activated_at_ms = None;
+            let mut duration_ms = None;
+            for part in parts {
+                if part.starts_with('@') {
+                    activated_at_ms = parse_timespan_ms(part);
+                } else if part.starts_with('+') {
+                    duration_ms = parse_timespan_ms(part);
+                }
+            }
+
+            Some(ChainEntry {
+                unit,
+                depth: indent / 2,
+                activated_at_ms,
+                duration_ms,
+            })
+        })
+        .collect()
+}
+
+pub async fn get_dependencies(
+    path: web::Path<String>,
+    query: web::Query<DependencyQuery>,
+) -> impl Responder {
+    let unit = path.into_inner();
+    if !systemd::is_valid_unit_name(&unit) {
+        return HttpResponse::BadRequest().json("Invalid unit name");
+    }
+
+    let depth = query.depth.unwrap_or(1).min(MAX_DEPTH);
+    match build_graph(&unit, depth) {
+        Ok(graph) => HttpResponse::Ok().json(graph),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
+
+pub async fn get_boot_analysis(query: web::Query<BootQuery>) -> impl Responder {
+    let time = match run_analyze(&["time"]) {
+        Ok(out) => out,
+        Err(e) => return HttpResponse::InternalServerError().json(e),
+    };
+    let (total_ms, phases) = parse_boot_time(&time);
+
+    let blame = run_analyze(&["blame"])
+        .map(|out| parse_blame(&out))
+        .unwrap_or_default();
+
+    let mut chain_args = vec!["critical-chain"];
+    if let Some(unit) = &query.unit {
+        if !systemd::is_valid_unit_name(unit) {
+            return HttpResponse::BadRequest().json("Invalid unit name");
+        }
+        chain_args.push(unit);
+    }
+    let critical_chain = run_analyze(&chain_args)
+        .map(|out| parse_critical_chain(&out))
+        .unwrap_or_default();
+
+    HttpResponse::Ok().json(BootAnalysis {
+        total_ms,
+        phases,
+        blame,
+        critical_chain,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Option<f64>, b: Option<f64>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => (a - b).abs() < 1e-9,
+            (a, b) => a == b,
+        }
+    }
+
+    #[test]
+    fn timespans_are_converted_to_milliseconds() {
+        let cases = [
+            ("1.508s", Some(1508.0)),
+            ("120ms", Some(120.0)),
+            ("500us", Some(0.5)),
+            ("1min 2.100s", Some(62_100.0)),
+            ("2h", Some(7_200_000.0)),
+            ("1d", Some(86_400_000.0)),
+            ("@4.512s", Some(4512.0)),
+            ("+3", Some(3000.0)),
+            ("", None),
+            ("soon", None),
+            ("5weeks", None),
+        ];
+        for (value, expected) in cases {
+            assert!(
+                close(parse_timespan_ms(value), expected),
+                "{:?} parsed as {:?}",
+                value,
+                parse_timespan_ms(value)
+            );
+        }
+    }
+
+    #[test]
+    fn blame_lines_keep_the_unit_name() {
+        let output = "          1.508s nginx.service\n 1min 2.100s apt-daily.service\n           120ms -.mount\n";
+        let blame = parse_blame(output);
+        let units: Vec<&str> = blame.iter().map(|b| b.unit.as_str()).collect();
+        assert_eq!(units, ["nginx.service", "apt-daily.service", "-.mount"]);
+        assert!(close(Some(blame[1].duration_ms), Some(62_100.0)));
+        assert!(close(Some(blame[2].duration_ms), Some(120.0)));
+    }
+
+    #[test]
+    fn critical_chain_is_parsed_with_depth() {
+        let output =
+            "The time when unit became active or started is printed after the \"@\" character.\n\
+            The time the unit took to start is printed after the \"+\" character.\n\
+            \n\
+            graphical.target @4.512s\n\
+            └─multi-user.target @4.511s\n  \
+              └─nginx.service @3.002s +1.508s\n    \
+                └─network-online.target @2.990s\n      \
+                  └─-.mount\n";
+
+        let chain = parse_critical_chain(output);
+        let units: Vec<(&str, usize)> = chain.iter().map(|c| (c.unit.as_str(), c.depth)).collect();
+        assert_eq!(
+            units,
+            [
+                ("graphical.target", 0),
+                ("multi-user.target", 1),
+                ("nginx.service", 2),
+                ("network-online.target", 3),
+                ("-.mount", 4),
+            ]
+        );
+        assert!(close(chain[2].activated_at_ms, Some(3002.0)));
+        assert!(close(chain[2].duration_ms, Some(1508.0)));
+        assert!(close(chain[4].activated_at_ms, None));
+    }
+}
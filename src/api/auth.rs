@@ -4,7 +4,7 @@ use argon2::{
     Argon2,
 };
 use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey, Header};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::sync::Mutex;
@@ -58,6 +58,19 @@ struct AuthStatus {
 
 
 
+pub fn verify_token(token: &str) -> bool {
+    if token.is_empty() {
+        return false;
+    }
+
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(JWT_SECRET),
+        &Validation::new(jsonwebtoken::Algorithm::HS256),
+    )
+    .is_ok()
+}
+
 pub fn load_auth_store() -> Option<AuthStore> {
     log::info!("Attempting to load auth store from: {}", AUTH_FILE);
     match fs::read_to_string(AUTH_FILE) {
@@ -5,18 +5,22 @@ use std::sync::Mutex;
 
 const CONFIG_FILE: &str = "wadm-config.json";
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct AppConfig {
     #[serde(default)]
     pub developer_mode: bool,
+    #[serde(default)]
+    pub user_unit_accounts: Vec<String>,
 }
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            developer_mode: false,
-        }
-    }
+// Account names as accepted by useradd: a lowercase letter or underscore,
+// then lowercase letters, digits, `_` or `-`, with an optional trailing `$`.
+fn is_valid_account(name: &str) -> bool {
+    let body = name.strip_suffix('$').unwrap_or(name);
+    let mut chars = body.chars();
+    name.len() <= 32
+        && matches!(chars.next(), Some(c) if c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-".contains(c))
 }
 
 pub fn load_config() -> AppConfig {
@@ -39,6 +43,8 @@ pub async fn get_config(data: web::Data<Mutex<AppConfig>>) -> impl Responder {
 #[derive(Deserialize)]
 pub struct UpdateConfigReq {
     pub developer_mode: bool,
+    #[serde(default)]
+    pub user_unit_accounts: Option<Vec<String>>,
 }
 
 pub async fn update_config(
@@ -46,6 +52,19 @@ pub async fn update_config(
     data: web::Data<Mutex<AppConfig>>,
 ) -> impl Responder {
     let mut config = data.lock().unwrap();
+    // The account list grants access to other users' units, so it can only be
+    // changed while Developer Mode is already on.
+    if let Some(accounts) = &body.user_unit_accounts {
+        if let Some(account) = accounts.iter().find(|a| !is_valid_account(a)) {
+            return HttpResponse::BadRequest().json(format!("Invalid account name: {}", account));
+        }
+        if *accounts != config.user_unit_accounts && !config.developer_mode {
+            log::warn!("Attempted to change user unit accounts without Developer Mode enabled.");
+            return HttpResponse::Forbidden()
+                .json("Changing user unit accounts requires Developer Mode");
+        }
+        config.user_unit_accounts = accounts.clone();
+    }
     config.developer_mode = body.developer_mode;
 
     if let Err(e) = save_config(&config) {
@@ -55,3 +74,19 @@ pub async fn update_config(
 
     HttpResponse::Ok().json(&*config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_names_follow_useradd_rules() {
+        for name in ["alice", "_build", "deploy-2", "ci_runner", "host$"] {
+            assert!(is_valid_account(name), "{}", name);
+        }
+        for name in ["", "-root", "Alice", "1user", "a b", "../root", "x$y", "$"] {
+            assert!(!is_valid_account(name), "{}", name);
+        }
+        assert!(!is_valid_account(&"a".repeat(33)));
+    }
+}
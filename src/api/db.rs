@@ -16,7 +16,7 @@ pub async fn list_dbs() -> impl Responder {
     
     
     if let Ok(output) = Command::new("mysql")
-        .args(&["-e", "SHOW DATABASES"])
+        .args(["-e", "SHOW DATABASES"])
         .output()
     {
         if output.status.success() {
@@ -37,14 +37,14 @@ pub async fn list_dbs() -> impl Responder {
     
     
     if let Ok(output) = Command::new("sudo")
-        .args(&["-u", "postgres", "psql", "-l", "-t", "-A", "-F", "|"])
+        .args(["-u", "postgres", "psql", "-l", "-t", "-A", "-F", "|"])
         .output()
     {
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
             for line in stdout.lines() {
                 let parts: Vec<&str> = line.split('|').collect();
-                if parts.len() >= 1 {
+                if !parts.is_empty() {
                     dbs.push(Database {
                         name: parts[0].to_string(),
                         engine: "postgres".to_string(),
@@ -1,10 +1,23 @@
 #![allow(deprecated)]
 use actix_web::{web, HttpResponse, Responder};
 use bollard::Docker;
-use futures_util::stream::TryStreamExt;
 use serde::{Deserialize, Serialize};
-
-use std::process::Command;
+use std::sync::{Arc, RwLock};
+
+use runtime::ContainerRuntime;
+
+pub mod compose;
+pub mod containers;
+pub mod events;
+pub mod exec;
+pub mod files;
+pub mod images;
+pub mod logs;
+pub mod networks;
+pub mod registries;
+pub mod runtime;
+pub mod stats;
+pub mod volumes;
 
 #[derive(Serialize)]
 struct ContainerInfo {
@@ -16,15 +29,9 @@ struct ContainerInfo {
     
 }
 
-#[derive(Serialize)]
-pub struct ContainerStats {
-    pub id: String,
-    pub cpu_usage: f64,    
-    pub memory_usage: u64, 
-}
-
 #[derive(Serialize)]
 pub struct DockerStatus {
+    pub runtime: String,
     pub installed: bool,
     pub running: bool,
     pub version: String,
@@ -33,14 +40,117 @@ pub struct DockerStatus {
 #[derive(Deserialize)]
 pub struct ContainerAction {
     pub action: String, 
+    pub signal: Option<String>,
 }
 
-pub async fn list_containers() -> impl Responder {
-    let docker = match Docker::connect_with_local_defaults() {
-        Ok(d) => d,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json("Failed to connect to Docker socket")
+pub struct DockerClient {
+    backend: RwLock<Backend>,
+}
+
+struct Backend {
+    runtime: Arc<dyn ContainerRuntime>,
+    client: Option<Docker>,
+}
+
+impl Backend {
+    fn detect() -> Self {
+        let runtime: Arc<dyn ContainerRuntime> = runtime::detect().into();
+        let client = runtime
+            .connect()
+            .map_err(|e| log::warn!("Failed to initialise container client: {}", e))
+            .ok();
+        Backend { runtime, client }
+    }
+}
+
+impl DockerClient {
+    pub fn connect() -> Self {
+        let backend = Backend::detect();
+        log::info!("Using {} as the container runtime", backend.runtime.name());
+        DockerClient {
+            backend: RwLock::new(backend),
         }
+    }
+
+    pub fn runtime(&self) -> Arc<dyn ContainerRuntime> {
+        self.backend.read().unwrap().runtime.clone()
+    }
+
+    pub fn get(&self) -> Result<Docker, HttpResponse> {
+        let backend = self.backend.read().unwrap();
+        backend.client.clone().ok_or_else(|| {
+            HttpResponse::InternalServerError().json(format!(
+                "Failed to connect to {} socket",
+                backend.runtime.name()
+            ))
+        })
+    }
+
+    // Returns a client that answers a ping. When the current one does not,
+    // the runtime is detected again, so a daemon started after WADM is picked
+    // up without a restart.
+    pub async fn live(&self) -> Option<Docker> {
+        let current = self.backend.read().unwrap().client.clone();
+        if let Some(docker) = current {
+            if docker.ping().await.is_ok() {
+                return Some(docker);
+            }
+        }
+
+        let backend = Backend::detect();
+        let docker = backend.client.clone()?;
+        docker.ping().await.ok()?;
+        log::info!("Using {} as the container runtime", backend.runtime.name());
+        *self.backend.write().unwrap() = backend;
+        Some(docker)
+    }
+
+    pub async fn count_running(&self) -> u32 {
+        let Ok(docker) = self.get() else {
+            return 0;
+        };
+        let mut filters = std::collections::HashMap::new();
+        filters.insert("status".to_string(), vec!["running".to_string()]);
+        docker
+            .list_containers(Some(bollard::container::ListContainersOptions::<String> {
+                filters,
+                ..Default::default()
+            }))
+            .await
+            .map(|c| c.len() as u32)
+            .unwrap_or(0)
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c))
+}
+
+fn is_valid_signal(signal: &str) -> bool {
+    !signal.is_empty()
+        && signal.len() <= 16
+        && signal
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '+' || c == '-')
+}
+
+pub fn container_name(names: Option<Vec<String>>) -> String {
+    names
+        .unwrap_or_default()
+        .first()
+        .map(|n| n.trim_start_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+pub async fn list_containers(client: web::Data<DockerClient>) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
     };
 
     let options = Some(bollard::container::ListContainersOptions::<String> {
@@ -54,14 +164,7 @@ pub async fn list_containers() -> impl Responder {
                 .into_iter()
                 .map(|c| ContainerInfo {
                     id: c.id.unwrap_or_default(),
-                    name: c
-                        .names
-                        .unwrap_or_default()
-                        .get(0)
-                        .cloned()
-                        .unwrap_or_default()
-                        .trim_start_matches('/')
-                        .to_string(),
+                    name: container_name(c.names),
                     image: c.image.unwrap_or_default(),
                     status: c.status.unwrap_or_default(),
                     state: match c.state {
@@ -78,91 +181,17 @@ pub async fn list_containers() -> impl Responder {
 
 
 
-pub async fn get_container_stats(id: web::Path<String>) -> impl Responder {
-    let docker = match Docker::connect_with_local_defaults() {
-        Ok(d) => d,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json("Failed to connect to Docker socket")
-        }
-    };
-
-    let options = Some(bollard::container::StatsOptions {
-        stream: false,
-        one_shot: true,
-    });
-
-    match docker.stats(&id.into_inner(), options).try_next().await {
-        Ok(Some(stats)) => {
-            
-            
-            
-            
-
-            let cpu_stats = stats.cpu_stats;
-            let precpu_stats = stats.precpu_stats;
-
-            let cpu_usage_total = cpu_stats
-                .as_ref()
-                .and_then(|c| c.cpu_usage.as_ref())
-                .and_then(|u| u.total_usage)
-                .unwrap_or(0);
-
-            let precpu_usage_total = precpu_stats
-                .as_ref()
-                .and_then(|c| c.cpu_usage.as_ref())
-                .and_then(|u| u.total_usage)
-                .unwrap_or(0);
-
-            let system_cpu_usage = cpu_stats
-                .as_ref()
-                .and_then(|c| c.system_cpu_usage)
-                .unwrap_or(0);
-
-            let presystem_cpu_usage = precpu_stats
-                .as_ref()
-                .and_then(|c| c.system_cpu_usage)
-                .unwrap_or(0);
-
-            let online_cpus = cpu_stats.as_ref().and_then(|c| c.online_cpus).unwrap_or(1);
-
-            let cpu_delta = cpu_usage_total as f64 - precpu_usage_total as f64;
-            let system_delta = system_cpu_usage as f64 - presystem_cpu_usage as f64;
-
-            let mut cpu_percent = 0.0;
-            if system_delta > 0.0 && cpu_delta > 0.0 {
-                cpu_percent = (cpu_delta / system_delta) * (online_cpus as f64) * 100.0;
-            }
-
-            
-            let memory_usage = stats
-                .memory_stats
-                .as_ref()
-                .and_then(|m| m.usage)
-                .unwrap_or(0);
-
-            HttpResponse::Ok().json(ContainerStats {
-                id: "".to_string(),
-                cpu_usage: cpu_percent,
-                memory_usage,
-            })
-        }
-        Ok(None) => HttpResponse::NotFound().json("No stats found"),
-        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to fetch stats: {}", e)),
-    }
-}
-
 pub async fn control_container(
     id: web::Path<String>,
     body: web::Json<ContainerAction>,
+    client: web::Data<DockerClient>,
 ) -> impl Responder {
     let container_id = id.into_inner();
     let action = &body.action;
 
-    let docker = match Docker::connect_with_local_defaults() {
+    let docker = match client.get() {
         Ok(d) => d,
-        Err(_) => {
-            return HttpResponse::InternalServerError().json("Failed to connect to Docker socket")
-        }
+        Err(res) => return res,
     };
 
     let result = match action.as_str() {
@@ -190,66 +219,65 @@ pub async fn control_container(
                 )
                 .await
         }
+        "pause" => docker.pause_container(&container_id).await,
+        "unpause" => docker.unpause_container(&container_id).await,
+        "kill" => {
+            let signal = body.signal.clone().unwrap_or_else(|| "SIGKILL".to_string());
+            if !is_valid_signal(&signal) {
+                return HttpResponse::BadRequest().json("Invalid signal");
+            }
+            docker
+                .kill_container(
+                    &container_id,
+                    Some(bollard::container::KillContainerOptions { signal }),
+                )
+                .await
+        }
         _ => return HttpResponse::BadRequest().json("Invalid action"),
     };
 
+    let done = match action.as_str() {
+        "stop" => "stopped",
+        "pause" => "paused",
+        "unpause" => "unpaused",
+        "kill" => "killed",
+        "start" => "started",
+        _ => "restarted",
+    };
+
     match result {
-        Ok(_) => HttpResponse::Ok().json(format!("Container {} {}ed", container_id, action)),
+        Ok(_) => HttpResponse::Ok().json(format!("Container {} {}", container_id, done)),
         Err(e) => HttpResponse::InternalServerError().json(format!("Docker action failed: {}", e)),
     }
 }
 
-pub async fn get_status() -> impl Responder {
-    let installed = Command::new("which")
-        .arg("docker")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
-
-    let mut running = false;
-    if installed {
-        
-        if let Ok(output) = Command::new("systemctl")
-            .arg("is-active")
-            .arg("docker")
-            .output()
-        {
-            let s = String::from_utf8_lossy(&output.stdout);
-            if s.trim() == "active" {
-                running = true;
-            }
-        }
-    }
+pub async fn get_status(client: web::Data<DockerClient>) -> impl Responder {
+    let running = client.live().await.is_some();
+    let runtime = client.runtime();
+    let installed = runtime.is_installed();
 
-    let mut version = String::new();
-    if installed {
-        if let Ok(output) = Command::new("docker").arg("--version").output() {
-            version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        }
-    }
+    let version = if installed {
+        runtime.version()
+    } else {
+        String::new()
+    };
 
     HttpResponse::Ok().json(DockerStatus {
+        runtime: runtime.name().to_string(),
         installed,
         running,
         version,
     })
 }
 
-pub async fn start_service() -> impl Responder {
-    let status = Command::new("sudo") 
-        .arg("systemctl")
-        .arg("start")
-        .arg("docker")
-        .status();
-
-    match status {
-        Ok(s) => {
-            if s.success() {
-                HttpResponse::Ok().json("Docker service started")
-            } else {
-                HttpResponse::InternalServerError().json("Failed to start docker service")
-            }
-        }
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+pub async fn start_service(client: web::Data<DockerClient>) -> impl Responder {
+    let runtime = client.runtime();
+    match runtime.start() {
+        Ok(_) => HttpResponse::Ok().json(format!("{} service started", runtime.name())),
+        Err(e) => HttpResponse::InternalServerError().json(format!(
+            "Failed to start {} service: {}",
+            runtime.name(),
+            e
+        )),
     }
 }
@@ -0,0 +1,453 @@
+use actix_web::{web, HttpResponse, Responder};
+use bollard::container::ListContainersOptions;
+use chrono::Local;
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::mpsc;
+
+use super::registries::RegistryStore;
+use super::{container_name, DockerClient};
+
+const STACKS_DIR: &str = "wadm-stacks";
+const COMPOSE_FILE: &str = "compose.yaml";
+const PENDING_FILE: &str = "compose.yaml.pending";
+const HISTORY_DIR: &str = "history";
+const PROJECT_LABEL: &str = "com.docker.compose.project";
+const SERVICE_LABEL: &str = "com.docker.compose.service";
+
+#[derive(Serialize)]
+pub struct StackContainer {
+    pub id: String,
+    pub name: String,
+    pub service: String,
+    pub image: String,
+    pub state: String,
+    pub status: String,
+}
+
+#[derive(Serialize)]
+pub struct StackInfo {
+    pub name: String,
+    pub managed: bool,
+    pub running: usize,
+    pub containers: Vec<StackContainer>,
+}
+
+#[derive(Serialize)]
+pub struct StackFile {
+    pub name: String,
+    pub content: String,
+    pub history: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct StackContent {
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+pub struct StackAction {
+    pub action: String,
+}
+
+pub fn is_valid_stack_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+fn is_valid_version(version: &str) -> bool {
+    !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == 'T' || c == '-')
+}
+
+fn stack_dir(name: &str) -> PathBuf {
+    PathBuf::from(STACKS_DIR).join(name)
+}
+
+fn compose_path(name: &str) -> PathBuf {
+    stack_dir(name).join(COMPOSE_FILE)
+}
+
+fn list_history(name: &str) -> Vec<String> {
+    let mut versions: Vec<String> = fs::read_dir(stack_dir(name).join(HISTORY_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| {
+                    e.file_name()
+                        .to_str()
+                        .and_then(|f| f.strip_suffix(".yaml"))
+                        .map(|v| v.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort();
+    versions.reverse();
+    versions
+}
+
+fn managed_stacks() -> Vec<String> {
+    fs::read_dir(STACKS_DIR)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().join(COMPOSE_FILE).exists())
+                .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn validate_compose(binary: &str, name: &str, path: &Path) -> Result<(), String> {
+    let output = std::process::Command::new(binary)
+        .arg("compose")
+        .arg("--project-name")
+        .arg(name)
+        .arg("--project-directory")
+        .arg(stack_dir(name))
+        .arg("--file")
+        .arg(path)
+        .args(["config", "--quiet"])
+        .output()
+        .map_err(|e| format!("Failed to execute {} compose: {}", binary, e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// The previous compose file is archived under history/ before being replaced,
+// so every deployed revision can be inspected or restored later.
+fn save_compose(binary: &str, name: &str, content: &str) -> Result<(), String> {
+    let dir = stack_dir(name);
+    fs::create_dir_all(dir.join(HISTORY_DIR))
+        .map_err(|e| format!("Failed to create stack directory: {}", e))?;
+
+    let pending = dir.join(PENDING_FILE);
+    fs::write(&pending, content).map_err(|e| format!("Failed to write compose file: {}", e))?;
+    if let Err(e) = validate_compose(binary, name, &pending) {
+        let _ = fs::remove_file(&pending);
+        return Err(e);
+    }
+
+    let current = dir.join(COMPOSE_FILE);
+    if let Ok(previous) = fs::read_to_string(&current) {
+        if previous == content {
+            let _ = fs::remove_file(&pending);
+            return Ok(());
+        }
+        let stamp = Local::now().format("%Y%m%dT%H%M%S%6f").to_string();
+        let mut archive = dir.join(HISTORY_DIR).join(format!("{}.yaml", stamp));
+        let mut n = 1;
+        while archive.exists() {
+            archive = dir.join(HISTORY_DIR).join(format!("{}-{}.yaml", stamp, n));
+            n += 1;
+        }
+        fs::write(archive, previous)
+            .map_err(|e| format!("Failed to archive previous compose file: {}", e))?;
+    }
+
+    fs::rename(&pending, &current).map_err(|e| format!("Failed to save compose file: {}", e))
+}
+
+async fn forward_lines<R: AsyncRead + Unpin>(reader: R, tx: mpsc::Sender<String>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if tx.send(format!("{}\n", line)).await.is_err() {
+            break;
+        }
+    }
+}
+
+// Compose pulls with the credentials in the CLI config rather than the ones
+// WADM stores, so those are written to a private config dir for the run. Logins
+// already in the host's config are kept.
+fn write_auth_config(registries: &RegistryStore) -> Result<Option<PathBuf>, String> {
+    let home = std::env::var_os("DOCKER_CONFIG")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| Path::new(&h).join(".docker")));
+    let base = home.and_then(|h| fs::read_to_string(h.join("config.json")).ok());
+    let Some(config) = registries.docker_config(base.as_deref()) else {
+        return Ok(None);
+    };
+
+    let dir = std::env::temp_dir().join(format!("wadm-compose-{:016x}", rand::random::<u64>()));
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .map_err(|e| format!("Failed to create credential directory: {}", e))?;
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(dir.join("config.json"))
+        .and_then(|mut f| f.write_all(config.as_bytes()));
+    if let Err(e) = written {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(format!("Failed to write registry credentials: {}", e));
+    }
+    Ok(Some(dir))
+}
+
+fn stream_compose(
+    binary: &str,
+    name: &str,
+    args: &[&str],
+    auth_dir: Option<PathBuf>,
+) -> HttpResponse {
+    let mut command = tokio::process::Command::new(binary);
+    command.arg("compose").arg("--project-name").arg(name);
+    if let Some(dir) = &auth_dir {
+        // Podman reads REGISTRY_AUTH_FILE, Docker the config dir.
+        command
+            .env("DOCKER_CONFIG", dir)
+            .env("REGISTRY_AUTH_FILE", dir.join("config.json"));
+    }
+    let path = compose_path(name);
+    if path.exists() {
+        command
+            .arg("--project-directory")
+            .arg(stack_dir(name))
+            .arg("--file")
+            .arg(path);
+    }
+
+    let child = command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            if let Some(dir) = &auth_dir {
+                let _ = fs::remove_dir_all(dir);
+            }
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to execute {} compose: {}", binary, e));
+        }
+    };
+
+    let stdout = child.stdout.take().expect("docker compose stdout is piped");
+    let stderr = child.stderr.take().expect("docker compose stderr is piped");
+    let (tx, rx) = mpsc::channel(64);
+
+    actix_web::rt::spawn(async move {
+        tokio::join!(
+            forward_lines(stdout, tx.clone()),
+            forward_lines(stderr, tx.clone())
+        );
+        let summary = match child.wait().await {
+            Ok(status) if status.success() => "Completed successfully\n".to_string(),
+            Ok(status) => format!("Failed with {}\n", status),
+            Err(e) => format!("Failed to wait for docker compose: {}\n", e),
+        };
+        if let Some(dir) = &auth_dir {
+            let _ = fs::remove_dir_all(dir);
+        }
+        let _ = tx.send(summary).await;
+    });
+
+    let body = stream::unfold(rx, |mut rx| async move {
+        rx.recv()
+            .await
+            .map(|line| (Ok::<_, actix_web::Error>(web::Bytes::from(line)), rx))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .streaming(body)
+}
+
+async fn project_containers(
+    client: &DockerClient,
+) -> Result<BTreeMap<String, Vec<StackContainer>>, HttpResponse> {
+    let docker = client.get()?;
+
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![PROJECT_LABEL.to_string()]);
+    let containers = docker
+        .list_containers(Some(ListContainersOptions::<String> {
+            all: true,
+            filters,
+            ..Default::default()
+        }))
+        .await
+        .map_err(|e| HttpResponse::InternalServerError().json(format!("Docker error: {}", e)))?;
+
+    let mut projects: BTreeMap<String, Vec<StackContainer>> = BTreeMap::new();
+    for c in containers {
+        let labels = c.labels.unwrap_or_default();
+        let Some(project) = labels.get(PROJECT_LABEL) else {
+            continue;
+        };
+        projects
+            .entry(project.clone())
+            .or_default()
+            .push(StackContainer {
+                id: c.id.unwrap_or_default(),
+                name: container_name(c.names),
+                service: labels.get(SERVICE_LABEL).cloned().unwrap_or_default(),
+                image: c.image.unwrap_or_default(),
+                state: match c.state {
+                    Some(s) => format!("{:?}", s).to_lowercase(),
+                    None => "unknown".to_string(),
+                },
+                status: c.status.unwrap_or_default(),
+            });
+    }
+    Ok(projects)
+}
+
+pub async fn list_stacks(client: web::Data<DockerClient>) -> impl Responder {
+    let mut projects = match project_containers(&client).await {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+    for name in managed_stacks() {
+        projects.entry(name).or_default();
+    }
+
+    let stacks: Vec<StackInfo> = projects
+        .into_iter()
+        .map(|(name, containers)| StackInfo {
+            managed: compose_path(&name).exists(),
+            running: containers.iter().filter(|c| c.state == "running").count(),
+            name,
+            containers,
+        })
+        .collect();
+
+    HttpResponse::Ok().json(stacks)
+}
+
+pub async fn get_stack(name: web::Path<String>) -> impl Responder {
+    let name = name.into_inner();
+    if !is_valid_stack_name(&name) {
+        return HttpResponse::BadRequest().json("Invalid stack name");
+    }
+
+    match fs::read_to_string(compose_path(&name)) {
+        Ok(content) => HttpResponse::Ok().json(StackFile {
+            history: list_history(&name),
+            name,
+            content,
+        }),
+        Err(_) => HttpResponse::NotFound().json("Stack is not managed by WADM"),
+    }
+}
+
+pub async fn save_stack(
+    name: web::Path<String>,
+    body: web::Json<StackContent>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let name = name.into_inner();
+    if !is_valid_stack_name(&name) {
+        return HttpResponse::BadRequest().json("Invalid stack name");
+    }
+    let binary = client.runtime().binary();
+
+    match web::block(move || save_compose(binary, &name, &body.content)).await {
+        Ok(Ok(())) => HttpResponse::Ok().json("Compose file saved"),
+        Ok(Err(e)) => HttpResponse::BadRequest().json(e),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to save stack: {}", e)),
+    }
+}
+
+pub async fn delete_stack(
+    name: web::Path<String>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let name = name.into_inner();
+    if !is_valid_stack_name(&name) {
+        return HttpResponse::BadRequest().json("Invalid stack name");
+    }
+    if !compose_path(&name).exists() {
+        return HttpResponse::NotFound().json("Stack is not managed by WADM");
+    }
+
+    let projects = match project_containers(&client).await {
+        Ok(p) => p,
+        Err(res) => return res,
+    };
+    if projects.get(&name).is_some_and(|c| !c.is_empty()) {
+        return HttpResponse::Conflict().json("Bring the stack down before deleting it");
+    }
+
+    match fs::remove_dir_all(stack_dir(&name)) {
+        Ok(_) => HttpResponse::Ok().json(format!("Stack {} deleted", name)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to delete stack: {}", e))
+        }
+    }
+}
+
+pub async fn get_stack_version(path: web::Path<(String, String)>) -> impl Responder {
+    let (name, version) = path.into_inner();
+    if !is_valid_stack_name(&name) || !is_valid_version(&version) {
+        return HttpResponse::BadRequest().json("Invalid stack name or version");
+    }
+
+    let file = stack_dir(&name)
+        .join(HISTORY_DIR)
+        .join(format!("{}.yaml", version));
+    match fs::read_to_string(file) {
+        Ok(content) => HttpResponse::Ok().json(content),
+        Err(_) => HttpResponse::NotFound().json("Version not found"),
+    }
+}
+
+pub async fn control_stack(
+    name: web::Path<String>,
+    body: web::Json<StackAction>,
+    client: web::Data<DockerClient>,
+    registries: web::Data<RegistryStore>,
+) -> impl Responder {
+    let name = name.into_inner();
+    if !is_valid_stack_name(&name) {
+        return HttpResponse::BadRequest().json("Invalid stack name");
+    }
+
+    let args: &[&str] = match body.action.as_str() {
+        "up" => &["up", "--detach", "--remove-orphans"],
+        "down" => &["down"],
+        "pull" => &["pull"],
+        "restart" => &["restart"],
+        _ => return HttpResponse::BadRequest().json("Invalid action"),
+    };
+    if ["up", "pull"].contains(&body.action.as_str()) && !compose_path(&name).exists() {
+        return HttpResponse::BadRequest().json("Stack has no compose file stored in WADM");
+    }
+
+    // `up` pulls missing images as well.
+    let auth_dir = if ["up", "pull"].contains(&body.action.as_str()) {
+        match write_auth_config(&registries) {
+            Ok(dir) => dir,
+            Err(e) => return HttpResponse::InternalServerError().json(e),
+        }
+    } else {
+        None
+    };
+
+    log::info!("Running docker compose {} for stack {}", body.action, name);
+    stream_compose(client.runtime().binary(), &name, args, auth_dir)
+}
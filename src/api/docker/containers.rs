@@ -0,0 +1,837 @@
+use actix_web::{web, HttpResponse, Responder};
+use bollard::container::{
+    Config, CreateContainerOptions, InspectContainerOptions, NetworkingConfig,
+    RemoveContainerOptions, RenameContainerOptions, StartContainerOptions, StopContainerOptions,
+    UpdateContainerOptions,
+};
+use bollard::models::{
+    ContainerConfig, EndpointSettings, HostConfig, PortBinding, RestartPolicy,
+    RestartPolicyNameEnum,
+};
+use bollard::network::ConnectNetworkOptions;
+use bollard::Docker;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use super::images::{is_valid_reference, pull};
+use super::registries::RegistryStore;
+use super::{is_valid_name, DockerClient};
+
+const DEFAULT_NETWORKS: [&str; 3] = ["bridge", "host", "none"];
+const SECRET_MARKERS: [&str; 7] = [
+    "PASSWORD",
+    "PASSWD",
+    "SECRET",
+    "TOKEN",
+    "KEY",
+    "CREDENTIAL",
+    "AUTH",
+];
+const MASK: &str = "********";
+
+#[derive(Deserialize)]
+pub struct PortMapping {
+    pub container_port: u16,
+    pub host_port: Option<u16>,
+    pub host_ip: Option<String>,
+    #[serde(default = "default_protocol")]
+    pub protocol: String,
+}
+
+#[derive(Deserialize)]
+pub struct VolumeMount {
+    pub source: String,
+    pub target: String,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ContainerLimits {
+    pub memory: Option<i64>,
+    pub cpus: Option<f64>,
+    pub pids_limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ContainerSpec {
+    pub image: String,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub ports: Vec<PortMapping>,
+    #[serde(default)]
+    pub volumes: Vec<VolumeMount>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub restart_policy: Option<String>,
+    #[serde(default)]
+    pub networks: Vec<String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub resources: ContainerLimits,
+    #[serde(default)]
+    pub pull: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RecreateRequest {
+    pub image: Option<String>,
+    #[serde(default = "default_true")]
+    pub pull: bool,
+}
+
+#[derive(Serialize)]
+pub struct CreatedContainer {
+    pub id: String,
+    pub name: String,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RenameRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveQuery {
+    #[serde(default)]
+    pub volumes: bool,
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateRequest {
+    pub restart_policy: Option<String>,
+    pub memory: Option<i64>,
+    pub memory_swap: Option<i64>,
+    pub cpus: Option<f64>,
+    pub pids_limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct PortInfo {
+    pub container_port: String,
+    pub host_ip: Option<String>,
+    pub host_port: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MountInfo {
+    pub kind: String,
+    pub name: Option<String>,
+    pub source: String,
+    pub destination: String,
+    pub read_only: bool,
+}
+
+#[derive(Serialize)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+    pub masked: bool,
+}
+
+#[derive(Serialize)]
+pub struct HealthInfo {
+    pub status: String,
+    pub failing_streak: i64,
+    pub last_output: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NetworkEndpoint {
+    pub network: String,
+    pub ip_address: Option<String>,
+    pub gateway: Option<String>,
+    pub mac_address: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ContainerDetails {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub image_id: String,
+    pub created: Option<String>,
+    pub status: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub exit_code: Option<i64>,
+    pub oom_killed: bool,
+    pub restart_count: i64,
+    pub restart_policy: String,
+    pub health: Option<HealthInfo>,
+    pub command: Vec<String>,
+    pub memory: Option<i64>,
+    pub cpus: Option<f64>,
+    pub pids_limit: Option<i64>,
+    pub ports: Vec<PortInfo>,
+    pub mounts: Vec<MountInfo>,
+    pub env: Vec<EnvVar>,
+    pub networks: Vec<NetworkEndpoint>,
+    pub labels: HashMap<String, String>,
+}
+
+fn default_protocol() -> String {
+    "tcp".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn parse_restart_policy(policy: &str) -> Result<RestartPolicy, String> {
+    let (name, retries) = match policy.split_once(':') {
+        Some((name, count)) => (
+            name,
+            Some(
+                count
+                    .parse::<i64>()
+                    .map_err(|_| "Invalid restart retry count".to_string())?,
+            ),
+        ),
+        None => (policy, None),
+    };
+    let name = match name {
+        "no" => RestartPolicyNameEnum::NO,
+        "always" => RestartPolicyNameEnum::ALWAYS,
+        "unless-stopped" => RestartPolicyNameEnum::UNLESS_STOPPED,
+        "on-failure" => RestartPolicyNameEnum::ON_FAILURE,
+        _ => return Err(format!("Invalid restart policy: {}", policy)),
+    };
+    if retries.is_some() && name != RestartPolicyNameEnum::ON_FAILURE {
+        return Err("Retry count is only valid with on-failure".to_string());
+    }
+    Ok(RestartPolicy {
+        name: Some(name),
+        maximum_retry_count: retries,
+    })
+}
+
+fn build_config(spec: &ContainerSpec) -> Result<Config<String>, String> {
+    if !is_valid_reference(&spec.image) {
+        return Err("Invalid image reference".to_string());
+    }
+    if let Some(name) = &spec.name {
+        if !is_valid_name(name) {
+            return Err("Invalid container name".to_string());
+        }
+    }
+    if spec.resources.cpus.is_some_and(|c| c < 0.0) {
+        return Err("CPU limit cannot be negative".to_string());
+    }
+
+    let mut exposed_ports = HashMap::new();
+    let mut port_bindings = HashMap::new();
+    for port in &spec.ports {
+        if !["tcp", "udp", "sctp"].contains(&port.protocol.as_str()) {
+            return Err(format!("Invalid protocol: {}", port.protocol));
+        }
+        let key = format!("{}/{}", port.container_port, port.protocol);
+        exposed_ports.insert(key.clone(), HashMap::new());
+        if let Some(host_port) = port.host_port {
+            port_bindings
+                .entry(key)
+                .or_insert_with(|| Some(Vec::new()))
+                .get_or_insert_with(Vec::new)
+                .push(PortBinding {
+                    host_ip: port.host_ip.clone(),
+                    host_port: Some(host_port.to_string()),
+                });
+        }
+    }
+
+    let mut binds = Vec::new();
+    for volume in &spec.volumes {
+        if !volume.target.starts_with('/') {
+            return Err(format!("Mount target must be absolute: {}", volume.target));
+        }
+        if volume.source.is_empty() || volume.source.contains(':') || volume.target.contains(':') {
+            return Err("Invalid volume mount".to_string());
+        }
+        let mode = if volume.read_only { ":ro" } else { "" };
+        binds.push(format!("{}:{}{}", volume.source, volume.target, mode));
+    }
+
+    let mut env = Vec::new();
+    for (key, value) in &spec.env {
+        if key.is_empty() || key.contains('=') {
+            return Err(format!("Invalid environment variable name: {}", key));
+        }
+        env.push(format!("{}={}", key, value));
+    }
+
+    let restart_policy = match &spec.restart_policy {
+        Some(p) => Some(parse_restart_policy(p)?),
+        None => None,
+    };
+
+    let mut endpoints = HashMap::new();
+    if let Some(network) = spec.networks.first() {
+        endpoints.insert(network.clone(), EndpointSettings::default());
+    }
+
+    Ok(Config {
+        image: Some(spec.image.clone()),
+        cmd: (!spec.command.is_empty()).then(|| spec.command.clone()),
+        env: Some(env),
+        labels: Some(spec.labels.clone()),
+        exposed_ports: Some(exposed_ports),
+        host_config: Some(HostConfig {
+            binds: Some(binds),
+            port_bindings: Some(port_bindings),
+            restart_policy,
+            network_mode: spec.networks.first().cloned(),
+            memory: spec.resources.memory,
+            nano_cpus: spec.resources.cpus.map(|c| (c * 1_000_000_000.0) as i64),
+            pids_limit: spec.resources.pids_limit,
+            ..Default::default()
+        }),
+        networking_config: Some(NetworkingConfig {
+            endpoints_config: endpoints,
+        }),
+        ..Default::default()
+    })
+}
+
+async fn connect_networks(
+    docker: &Docker,
+    container: &str,
+    networks: Vec<(String, EndpointSettings)>,
+) -> Result<(), String> {
+    for (network, endpoint_config) in networks {
+        docker
+            .connect_network(
+                &network,
+                ConnectNetworkOptions {
+                    container: container.to_string(),
+                    endpoint_config,
+                },
+            )
+            .await
+            .map_err(|e| format!("Failed to connect to network {}: {}", network, e))?;
+    }
+    Ok(())
+}
+
+pub async fn create_container(
+    body: web::Json<ContainerSpec>,
+    client: web::Data<DockerClient>,
+    registries: web::Data<RegistryStore>,
+) -> impl Responder {
+    let config = match build_config(&body) {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    if body.pull {
+        if let Err(e) = pull(
+            &docker,
+            &body.image,
+            registries.credentials_for(&body.image),
+        )
+        .await
+        {
+            return HttpResponse::InternalServerError().json(e);
+        }
+    }
+
+    let options = body.name.clone().map(|name| CreateContainerOptions {
+        name,
+        platform: None,
+    });
+    let created = match docker.create_container(options, config).await {
+        Ok(c) => c,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to create container: {}", e))
+        }
+    };
+
+    let extra = body
+        .networks
+        .iter()
+        .skip(1)
+        .map(|n| (n.clone(), EndpointSettings::default()))
+        .collect();
+    if let Err(e) = connect_networks(&docker, &created.id, extra).await {
+        let _ = docker
+            .remove_container(
+                &created.id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
+            .await;
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    if let Err(e) = docker
+        .start_container(&created.id, None::<StartContainerOptions<String>>)
+        .await
+    {
+        return HttpResponse::InternalServerError().json(format!(
+            "Container {} created but failed to start: {}",
+            created.id, e
+        ));
+    }
+
+    log::info!("Created container {} from {}", created.id, body.image);
+    HttpResponse::Ok().json(CreatedContainer {
+        name: body.name.clone().unwrap_or_default(),
+        id: created.id,
+        warnings: created.warnings,
+    })
+}
+
+// Values the old image baked into the container config are dropped so the new
+// image's defaults (env, labels, command) take effect after recreation.
+async fn strip_image_defaults(docker: &Docker, image_id: &str, config: &mut ContainerConfig) {
+    let Ok(image) = docker.inspect_image(image_id).await else {
+        return;
+    };
+    let Some(defaults) = image.config else {
+        return;
+    };
+
+    if let (Some(env), Some(image_env)) = (config.env.as_mut(), defaults.env.as_ref()) {
+        env.retain(|e| !image_env.contains(e));
+    }
+    if let (Some(labels), Some(image_labels)) = (config.labels.as_mut(), defaults.labels.as_ref()) {
+        labels.retain(|k, v| image_labels.get(k) != Some(v));
+    }
+    if config.cmd == defaults.cmd {
+        config.cmd = None;
+    }
+    if config.entrypoint == defaults.entrypoint {
+        config.entrypoint = None;
+    }
+    if config.working_dir == defaults.working_dir {
+        config.working_dir = None;
+    }
+    if config.user == defaults.user {
+        config.user = None;
+    }
+}
+
+async fn rename(docker: &Docker, container: &str, name: &str) -> Result<(), String> {
+    docker
+        .rename_container(
+            container,
+            RenameContainerOptions {
+                name: name.to_string(),
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to rename container: {}", e))
+}
+
+async fn restore(docker: &Docker, old_id: &str, name: &str, was_running: bool) {
+    let _ = rename(docker, old_id, name).await;
+    if was_running {
+        let _ = docker
+            .start_container(old_id, None::<StartContainerOptions<String>>)
+            .await;
+    }
+}
+
+pub async fn recreate_container(
+    id: web::Path<String>,
+    body: web::Json<RecreateRequest>,
+    client: web::Data<DockerClient>,
+    registries: web::Data<RegistryStore>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let inspect = match docker
+        .inspect_container(&id.into_inner(), None::<InspectContainerOptions>)
+        .await
+    {
+        Ok(i) => i,
+        Err(e) => return HttpResponse::NotFound().json(format!("Container not found: {}", e)),
+    };
+    let old_id = inspect.id.unwrap_or_default();
+    let name = inspect
+        .name
+        .unwrap_or_default()
+        .trim_start_matches('/')
+        .to_string();
+    let mut container_config = inspect.config.unwrap_or_default();
+    let image = match body.image.clone().or(container_config.image.clone()) {
+        Some(i) if is_valid_reference(&i) => i,
+        _ => return HttpResponse::BadRequest().json("Invalid image reference"),
+    };
+    let was_running = inspect.state.and_then(|s| s.running).unwrap_or(false);
+
+    if body.pull {
+        if let Err(e) = pull(&docker, &image, registries.credentials_for(&image)).await {
+            return HttpResponse::InternalServerError().json(e);
+        }
+    }
+
+    if let Some(old_image) = &inspect.image {
+        strip_image_defaults(&docker, old_image, &mut container_config).await;
+    }
+    if container_config
+        .hostname
+        .as_ref()
+        .is_some_and(|h| old_id.starts_with(h.as_str()))
+    {
+        container_config.hostname = None;
+    }
+    container_config.image = Some(image.clone());
+
+    let host_config = inspect.host_config.unwrap_or_default();
+    // Containers created without a network report "default" while attached
+    // to "bridge".
+    let primary = match host_config.network_mode.as_deref() {
+        Some("default") => "bridge".to_string(),
+        mode => mode.unwrap_or_default().to_string(),
+    };
+    let mut primary_endpoint = HashMap::new();
+    let mut extra = Vec::new();
+    for (network, settings) in inspect
+        .network_settings
+        .and_then(|n| n.networks)
+        .unwrap_or_default()
+    {
+        let endpoint = if DEFAULT_NETWORKS.contains(&network.as_str()) {
+            EndpointSettings::default()
+        } else {
+            EndpointSettings {
+                aliases: settings.aliases.map(|a| {
+                    a.into_iter()
+                        .filter(|alias| !old_id.starts_with(alias.as_str()))
+                        .collect()
+                }),
+                ipam_config: settings.ipam_config,
+                links: settings.links,
+                driver_opts: settings.driver_opts,
+                ..Default::default()
+            }
+        };
+        if network == primary {
+            primary_endpoint.insert(network, endpoint);
+        } else {
+            extra.push((network, endpoint));
+        }
+    }
+
+    let mut config: Config<String> = container_config.into();
+    config.host_config = Some(host_config);
+    config.networking_config = Some(NetworkingConfig {
+        endpoints_config: primary_endpoint,
+    });
+
+    if was_running {
+        if let Err(e) = docker
+            .stop_container(&old_id, None::<StopContainerOptions>)
+            .await
+        {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to stop container: {}", e));
+        }
+    }
+    let backup_name = format!("{}-wadm-old", name);
+    if let Err(e) = rename(&docker, &old_id, &backup_name).await {
+        restore(&docker, &old_id, &name, was_running).await;
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    let created = match docker
+        .create_container(
+            Some(CreateContainerOptions {
+                name: name.clone(),
+                platform: None,
+            }),
+            config,
+        )
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            restore(&docker, &old_id, &name, was_running).await;
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to create container: {}", e));
+        }
+    };
+
+    let mut result = connect_networks(&docker, &created.id, extra).await;
+    if result.is_ok() && was_running {
+        result = docker
+            .start_container(&created.id, None::<StartContainerOptions<String>>)
+            .await
+            .map_err(|e| format!("Failed to start container: {}", e));
+    }
+    if let Err(e) = result {
+        let _ = docker
+            .remove_container(
+                &created.id,
+                Some(RemoveContainerOptions {
+                    force: true,
+                    ..Default::default()
+                }),
+            )
+            .await;
+        restore(&docker, &old_id, &name, was_running).await;
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    if let Err(e) = docker
+        .remove_container(
+            &old_id,
+            Some(RemoveContainerOptions {
+                force: true,
+                ..Default::default()
+            }),
+        )
+        .await
+    {
+        log::warn!("Failed to remove old container {}: {}", backup_name, e);
+    }
+
+    log::info!("Recreated container {} with image {}", name, image);
+    HttpResponse::Ok().json(CreatedContainer {
+        id: created.id,
+        name,
+        warnings: created.warnings,
+    })
+}
+
+fn is_secret(key: &str) -> bool {
+    let key = key.to_uppercase();
+    SECRET_MARKERS.iter().any(|m| key.contains(m))
+}
+
+fn mask_env(env: Vec<String>) -> Vec<EnvVar> {
+    env.into_iter()
+        .map(|entry| {
+            let (key, value) = entry.split_once('=').unwrap_or((&entry, ""));
+            let masked = is_secret(key) && !value.is_empty();
+            EnvVar {
+                key: key.to_string(),
+                value: if masked { MASK } else { value }.to_string(),
+                masked,
+            }
+        })
+        .collect()
+}
+
+pub async fn inspect_container(
+    id: web::Path<String>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let inspect = match docker
+        .inspect_container(&id.into_inner(), None::<InspectContainerOptions>)
+        .await
+    {
+        Ok(i) => i,
+        Err(e) => return HttpResponse::NotFound().json(format!("Container not found: {}", e)),
+    };
+
+    let config = inspect.config.unwrap_or_default();
+    let host_config = inspect.host_config.unwrap_or_default();
+    let state = inspect.state.unwrap_or_default();
+    let network_settings = inspect.network_settings.unwrap_or_default();
+
+    let health = state.health.map(|h| HealthInfo {
+        status: h.status.map(|s| s.to_string()).unwrap_or_default(),
+        failing_streak: h.failing_streak.unwrap_or(0),
+        last_output: h
+            .log
+            .and_then(|l| l.last().and_then(|r| r.output.clone()))
+            .map(|o| o.trim().to_string()),
+    });
+
+    let mut ports: Vec<PortInfo> = Vec::new();
+    for (port, bindings) in network_settings.ports.unwrap_or_default() {
+        match bindings {
+            Some(bindings) if !bindings.is_empty() => {
+                ports.extend(bindings.into_iter().map(|b| PortInfo {
+                    container_port: port.clone(),
+                    host_ip: b.host_ip,
+                    host_port: b.host_port,
+                }))
+            }
+            _ => ports.push(PortInfo {
+                container_port: port,
+                host_ip: None,
+                host_port: None,
+            }),
+        }
+    }
+    ports.sort_by(|a, b| a.container_port.cmp(&b.container_port));
+
+    let mounts = inspect
+        .mounts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|m| MountInfo {
+            kind: m.typ.map(|t| t.to_string()).unwrap_or_default(),
+            name: m.name,
+            source: m.source.unwrap_or_default(),
+            destination: m.destination.unwrap_or_default(),
+            read_only: !m.rw.unwrap_or(true),
+        })
+        .collect();
+
+    let mut networks: Vec<NetworkEndpoint> = network_settings
+        .networks
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(network, e)| NetworkEndpoint {
+            network,
+            ip_address: e.ip_address.filter(|a| !a.is_empty()),
+            gateway: e.gateway.filter(|g| !g.is_empty()),
+            mac_address: e.mac_address.filter(|m| !m.is_empty()),
+        })
+        .collect();
+    networks.sort_by(|a, b| a.network.cmp(&b.network));
+
+    let restart_policy = host_config
+        .restart_policy
+        .as_ref()
+        .map(|p| match (&p.name, p.maximum_retry_count) {
+            (Some(RestartPolicyNameEnum::ON_FAILURE), Some(n)) if n > 0 => {
+                format!("on-failure:{}", n)
+            }
+            (Some(name), _) => name.to_string(),
+            (None, _) => String::new(),
+        })
+        .unwrap_or_default();
+
+    let mut command = config.entrypoint.unwrap_or_default();
+    command.extend(config.cmd.unwrap_or_default());
+
+    HttpResponse::Ok().json(ContainerDetails {
+        id: inspect.id.unwrap_or_default(),
+        name: inspect
+            .name
+            .unwrap_or_default()
+            .trim_start_matches('/')
+            .to_string(),
+        image: config.image.unwrap_or_default(),
+        image_id: inspect.image.unwrap_or_default(),
+        created: inspect.created,
+        status: state.status.map(|s| s.to_string()).unwrap_or_default(),
+        started_at: state.started_at,
+        finished_at: state.finished_at,
+        exit_code: state.exit_code,
+        oom_killed: state.oom_killed.unwrap_or(false),
+        restart_count: inspect.restart_count.unwrap_or(0),
+        restart_policy,
+        health,
+        command,
+        memory: host_config.memory.filter(|m| *m > 0),
+        cpus: host_config
+            .nano_cpus
+            .filter(|c| *c > 0)
+            .map(|c| c as f64 / 1_000_000_000.0),
+        pids_limit: host_config.pids_limit.filter(|p| *p > 0),
+        ports,
+        mounts,
+        env: mask_env(config.env.unwrap_or_default()),
+        networks,
+        labels: config.labels.unwrap_or_default(),
+    })
+}
+
+pub async fn rename_container(
+    id: web::Path<String>,
+    body: web::Json<RenameRequest>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    if !is_valid_name(&body.name) {
+        return HttpResponse::BadRequest().json("Invalid container name");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    match rename(&docker, &id.into_inner(), &body.name).await {
+        Ok(_) => HttpResponse::Ok().json(format!("Container renamed to {}", body.name)),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
+
+pub async fn remove_container(
+    id: web::Path<String>,
+    query: web::Query<RemoveQuery>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let container_id = id.into_inner();
+
+    let options = RemoveContainerOptions {
+        v: query.volumes,
+        force: query.force,
+        ..Default::default()
+    };
+
+    match docker.remove_container(&container_id, Some(options)).await {
+        Ok(_) => HttpResponse::Ok().json(format!("Container {} removed", container_id)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to remove container: {}", e))
+        }
+    }
+}
+
+pub async fn update_container(
+    id: web::Path<String>,
+    body: web::Json<UpdateRequest>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let restart_policy = match body.restart_policy.as_deref().map(parse_restart_policy) {
+        Some(Ok(p)) => Some(p),
+        Some(Err(e)) => return HttpResponse::BadRequest().json(e),
+        None => None,
+    };
+    if body.cpus.is_some_and(|c| c < 0.0) {
+        return HttpResponse::BadRequest().json("CPU limit cannot be negative");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let container_id = id.into_inner();
+
+    let options = UpdateContainerOptions::<String> {
+        restart_policy,
+        memory: body.memory,
+        memory_swap: body.memory_swap,
+        nano_cpus: body.cpus.map(|c| (c * 1_000_000_000.0) as i64),
+        pids_limit: body.pids_limit,
+        ..Default::default()
+    };
+
+    match docker.update_container(&container_id, options).await {
+        Ok(_) => HttpResponse::Ok().json(format!("Container {} updated", container_id)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to update container: {}", e))
+        }
+    }
+}
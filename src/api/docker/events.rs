@@ -0,0 +1,364 @@
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use actix_ws::AggregatedMessage;
+use bollard::system::EventsOptions;
+use chrono::DateTime;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use super::DockerClient;
+use crate::api::alerts::AlertLog;
+use crate::api::auth::verify_token;
+
+const MAX_EVENTS: usize = 1000;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const RESTART_WINDOW_SECS: i64 = 300;
+const RESTART_LOOP_THRESHOLD: usize = 3;
+
+#[derive(Serialize, Clone)]
+pub struct DockerEvent {
+    pub id: u64,
+    pub time: i64,
+    pub timestamp: String,
+    pub kind: String,
+    pub action: String,
+    pub actor_id: String,
+    pub name: String,
+    pub image: Option<String>,
+    pub exit_code: Option<String>,
+}
+
+pub struct EventLog {
+    events: Mutex<VecDeque<DockerEvent>>,
+    next_id: Mutex<u64>,
+    live: broadcast::Sender<DockerEvent>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        EventLog {
+            events: Mutex::new(VecDeque::new()),
+            next_id: Mutex::new(0),
+            live: broadcast::channel(256).0,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EventQuery {
+    pub container: Option<String>,
+    pub kind: Option<String>,
+    pub action: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub q: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct EventStreamQuery {
+    pub token: String,
+}
+
+impl EventLog {
+    fn push(&self, mut event: DockerEvent) -> DockerEvent {
+        event.id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= MAX_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+        let _ = self.live.send(event.clone());
+        event
+    }
+}
+
+impl EventQuery {
+    fn matches(&self, e: &DockerEvent) -> bool {
+        self.container
+            .as_ref()
+            .is_none_or(|c| e.actor_id.starts_with(c.as_str()) || &e.name == c)
+            && self.kind.as_ref().is_none_or(|k| &e.kind == k)
+            && self
+                .action
+                .as_ref()
+                .is_none_or(|a| e.action.starts_with(a.as_str()))
+            && self.since.is_none_or(|s| e.time >= s)
+            && self.until.is_none_or(|u| e.time <= u)
+            && self.q.as_ref().is_none_or(|q| {
+                let q = q.to_lowercase();
+                e.name.to_lowercase().contains(&q)
+                    || e.action.to_lowercase().contains(&q)
+                    || e.image
+                        .as_ref()
+                        .is_some_and(|i| i.to_lowercase().contains(&q))
+            })
+    }
+}
+
+// Tracks per-container state across events so a `die` caused by a stop or
+// kill request is not reported, and repeated crash/start cycles inside the
+// restart window are raised once as a crash loop.
+#[derive(Default)]
+struct Tracker {
+    stopping: HashSet<String>,
+    crashed: HashSet<String>,
+    starts: HashMap<String, VecDeque<i64>>,
+    looping: HashSet<String>,
+}
+
+impl Tracker {
+    fn observe(&mut self, event: &DockerEvent, alerts: &AlertLog) {
+        if event.kind != "container" {
+            return;
+        }
+        let id = &event.actor_id;
+
+        match event.action.as_str() {
+            "kill" | "stop" => {
+                self.stopping.insert(id.clone());
+            }
+            "die" => {
+                let expected = self.stopping.remove(id);
+                let code = event.exit_code.as_deref().unwrap_or("0");
+                if !expected && code != "0" {
+                    self.crashed.insert(id.clone());
+                    if !self.looping.contains(id) {
+                        alerts.push(
+                            "docker",
+                            &event.name,
+                            "warning",
+                            format!("Container exited unexpectedly with code {}", code),
+                        );
+                    }
+                }
+            }
+            "oom" => alerts.push(
+                "docker",
+                &event.name,
+                "critical",
+                "Container was killed after running out of memory".to_string(),
+            ),
+            "health_status: unhealthy" => alerts.push(
+                "docker",
+                &event.name,
+                "warning",
+                "Container health check is failing".to_string(),
+            ),
+            "start" => {
+                // A stop request also emits `stop` after the `die`, which
+                // must not mark the next run as stopping.
+                self.stopping.remove(id);
+                if !self.crashed.remove(id) {
+                    return;
+                }
+                let starts = self.starts.entry(id.clone()).or_default();
+                starts.push_back(event.time);
+                while starts
+                    .front()
+                    .is_some_and(|t| event.time - t > RESTART_WINDOW_SECS)
+                {
+                    starts.pop_front();
+                }
+                if starts.len() < RESTART_LOOP_THRESHOLD {
+                    self.looping.remove(id);
+                } else if self.looping.insert(id.clone()) {
+                    alerts.push(
+                        "docker",
+                        &event.name,
+                        "critical",
+                        format!(
+                            "Container is crash-looping: restarted {} times in {} minutes",
+                            starts.len(),
+                            RESTART_WINDOW_SECS / 60
+                        ),
+                    );
+                }
+            }
+            "destroy" => {
+                self.stopping.remove(id);
+                self.crashed.remove(id);
+                self.starts.remove(id);
+                self.looping.remove(id);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn start_event_watcher(
+    client: &web::Data<DockerClient>,
+    events: &web::Data<EventLog>,
+    alerts: &web::Data<AlertLog>,
+) {
+    let client = client.clone();
+    let events = events.clone();
+    let alerts = alerts.clone();
+
+    tokio::spawn(async move {
+        let mut tracker = Tracker::default();
+        let mut last_nano: i64 = 0;
+
+        loop {
+            let Some(docker) = client.live().await else {
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            };
+            let options = EventsOptions::<String> {
+                since: (last_nano > 0).then(|| (last_nano / 1_000_000_000).to_string()),
+                ..Default::default()
+            };
+            let mut stream = docker.events(Some(options));
+
+            while let Some(message) = stream.next().await {
+                let message = match message {
+                    Ok(m) => m,
+                    Err(e) => {
+                        log::warn!("Docker event stream error: {}", e);
+                        break;
+                    }
+                };
+                let action = message.action.unwrap_or_default();
+                let time_nano = message.time_nano.unwrap_or(0);
+                if action.starts_with("exec_") || time_nano <= last_nano {
+                    continue;
+                }
+                last_nano = time_nano;
+
+                let actor = message.actor.unwrap_or_default();
+                let attributes = actor.attributes.unwrap_or_default();
+                let time = message.time.unwrap_or(time_nano / 1_000_000_000);
+                let event = events.push(DockerEvent {
+                    id: 0,
+                    time,
+                    timestamp: DateTime::from_timestamp(time, 0)
+                        .map(|d| d.to_rfc3339())
+                        .unwrap_or_default(),
+                    kind: message.typ.map(|t| t.to_string()).unwrap_or_default(),
+                    action,
+                    actor_id: actor.id.unwrap_or_default(),
+                    name: attributes.get("name").cloned().unwrap_or_default(),
+                    image: attributes.get("image").cloned(),
+                    exit_code: attributes.get("exitCode").cloned(),
+                });
+                tracker.observe(&event, &alerts);
+            }
+
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+}
+
+pub async fn list_events(
+    query: web::Query<EventQuery>,
+    data: web::Data<EventLog>,
+) -> impl Responder {
+    let events = data.events.lock().unwrap();
+    let limit = query.limit.unwrap_or(200);
+
+    let recent: Vec<&DockerEvent> = events
+        .iter()
+        .rev()
+        .filter(|e| query.matches(e))
+        .take(limit)
+        .collect();
+
+    HttpResponse::Ok().json(recent)
+}
+
+pub async fn ws_events(
+    req: HttpRequest,
+    stream: web::Payload,
+    query: web::Query<EventStreamQuery>,
+    data: web::Data<EventLog>,
+) -> Result<HttpResponse, Error> {
+    if !verify_token(&query.token) {
+        return Ok(HttpResponse::Unauthorized().body("Invalid token"));
+    }
+
+    let mut live = data.live.subscribe();
+    let (res, mut session, msg_stream) = actix_ws::handle(&req, stream)?;
+    let mut msg_stream = msg_stream.aggregate_continuations();
+
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                event = live.recv() => {
+                    let event = match event {
+                        Ok(e) => e,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    let json = serde_json::to_string(&event).unwrap_or_default();
+                    if session.text(json).await.is_err() {
+                        break;
+                    }
+                }
+
+                Some(msg) = msg_stream.next() => {
+                    match msg {
+                        Ok(AggregatedMessage::Ping(msg)) => {
+                            let _ = session.pong(&msg).await;
+                        }
+                        Ok(AggregatedMessage::Close(_)) | Err(_) => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(action: &str, exit_code: Option<&str>) -> DockerEvent {
+        DockerEvent {
+            id: 0,
+            time: 1_760_781_600,
+            timestamp: String::new(),
+            kind: "container".to_string(),
+            action: action.to_string(),
+            actor_id: "abc".to_string(),
+            name: "web".to_string(),
+            image: None,
+            exit_code: exit_code.map(String::from),
+        }
+    }
+
+    #[test]
+    fn a_crash_after_a_requested_stop_is_reported() {
+        let mut tracker = Tracker::default();
+        let alerts = AlertLog::default();
+
+        for (action, code) in [
+            ("kill", None),
+            ("die", Some("137")),
+            ("stop", None),
+            ("start", None),
+        ] {
+            tracker.observe(&event(action, code), &alerts);
+        }
+        assert!(tracker.crashed.is_empty());
+        assert!(tracker.stopping.is_empty());
+
+        tracker.observe(&event("die", Some("1")), &alerts);
+        assert!(tracker.crashed.contains("abc"));
+
+        tracker.observe(&event("start", None), &alerts);
+        assert_eq!(tracker.starts["abc"].len(), 1);
+    }
+}
@@ -0,0 +1,171 @@
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_ws::AggregatedMessage;
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecOptions, StartExecResults};
+use futures_util::StreamExt;
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+
+use super::DockerClient;
+use crate::api::auth::verify_token;
+use crate::api::config::AppConfig;
+
+const DEFAULT_SHELL: &str = "/bin/sh";
+
+#[derive(serde::Deserialize)]
+pub struct ExecQuery {
+    token: String,
+    shell: Option<String>,
+    user: Option<String>,
+}
+
+fn is_valid_shell(shell: &str) -> bool {
+    shell.starts_with('/')
+        && shell
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/_-.".contains(c))
+}
+
+fn is_valid_user(user: &str) -> bool {
+    !user.is_empty()
+        && user
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.:".contains(c))
+}
+
+pub async fn ws_container_exec(
+    req: HttpRequest,
+    stream: web::Payload,
+    id: web::Path<String>,
+    config_data: web::Data<Mutex<AppConfig>>,
+    query: web::Query<ExecQuery>,
+    client: web::Data<DockerClient>,
+) -> Result<HttpResponse, Error> {
+    if !verify_token(&query.token) {
+        return Ok(HttpResponse::Unauthorized().body("Invalid token"));
+    }
+
+    {
+        let config = config_data.lock().unwrap();
+        if !config.developer_mode {
+            log::warn!("Attempted container exec without Developer Mode enabled.");
+            return Ok(HttpResponse::Forbidden().body("Developer Mode is disabled"));
+        }
+    }
+
+    let shell = query.shell.as_deref().unwrap_or(DEFAULT_SHELL);
+    if !is_valid_shell(shell) {
+        return Ok(HttpResponse::BadRequest().json("Shell must be an absolute path"));
+    }
+    if let Some(user) = &query.user {
+        if !is_valid_user(user) {
+            return Ok(HttpResponse::BadRequest().json("Invalid user"));
+        }
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return Ok(res),
+    };
+
+    let exec = docker
+        .create_exec(
+            &id.into_inner(),
+            CreateExecOptions {
+                attach_stdin: Some(true),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                tty: Some(true),
+                cmd: Some(vec![shell.to_string()]),
+                user: query.user.clone(),
+                env: Some(vec!["TERM=xterm-256color".to_string()]),
+                ..Default::default()
+            },
+        )
+        .await;
+    let exec_id = match exec {
+        Ok(e) => e.id,
+        Err(e) => {
+            return Ok(HttpResponse::InternalServerError()
+                .json(format!("Failed to create exec instance: {}", e)))
+        }
+    };
+
+    let started = docker
+        .start_exec(
+            &exec_id,
+            Some(StartExecOptions {
+                detach: false,
+                tty: true,
+                output_capacity: None,
+            }),
+        )
+        .await;
+    let (mut output, mut input) = match started {
+        Ok(StartExecResults::Attached { output, input }) => (output, input),
+        Ok(StartExecResults::Detached) => {
+            return Ok(HttpResponse::InternalServerError().json("Exec instance detached"))
+        }
+        Err(e) => {
+            return Ok(HttpResponse::InternalServerError()
+                .json(format!("Failed to start exec instance: {}", e)))
+        }
+    };
+
+    let (res, mut session, stream) = actix_ws::handle(&req, stream)?;
+
+    let mut stream = stream
+        .aggregate_continuations()
+        .max_continuation_size(2 * 1024 * 1024);
+
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(chunk) = output.next() => {
+                    match chunk {
+                        Ok(out) => {
+                            if session.binary(out.into_bytes()).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(_) => break,
+                    }
+                }
+
+                Some(msg) = stream.next() => {
+                    match msg {
+                        Ok(AggregatedMessage::Binary(bin)) => {
+                            if let Err(e) = input.write_all(&bin).await {
+                                log::warn!("Failed to write to exec session: {}", e);
+                                break;
+                            }
+                        }
+                        Ok(AggregatedMessage::Text(text)) => {
+                            if let Some(dims) = text.strip_prefix("RESIZE:") {
+                                let parts: Vec<&str> = dims.split('x').collect();
+                                if parts.len() == 2 {
+                                    if let (Ok(width), Ok(height)) = (parts[0].parse(), parts[1].parse()) {
+                                        let _ = docker
+                                            .resize_exec(&exec_id, ResizeExecOptions { height, width })
+                                            .await;
+                                    }
+                                }
+                            } else if input.write_all(text.as_bytes()).await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(AggregatedMessage::Ping(msg)) => {
+                            let _ = session.pong(&msg).await;
+                        }
+                        Ok(AggregatedMessage::Close(_)) => break,
+                        _ => {}
+                    }
+                }
+                else => break,
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(res)
+}
@@ -0,0 +1,364 @@
+use actix_web::{web, HttpResponse, Responder};
+use bollard::body_full;
+use bollard::container::{DownloadFromContainerOptions, UploadToContainerOptions};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read};
+use std::path::Component;
+use tar::EntryType;
+use tokio::sync::mpsc;
+
+use super::DockerClient;
+
+pub const MAX_UPLOAD_SIZE: usize = 64 * 1024 * 1024;
+
+// Archive chunks waiting to be parsed while the rest are still downloading.
+const LISTING_BUFFER: usize = 16;
+
+#[derive(Serialize)]
+pub struct FileEntry {
+    pub name: String,
+    pub path: String,
+    pub kind: String,
+    pub size: u64,
+    pub modified: i64,
+    pub mode: String,
+}
+
+#[derive(Deserialize)]
+pub struct PathQuery {
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    pub path: String,
+    pub name: Option<String>,
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.starts_with('/') && !path.contains('\0')
+}
+
+fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\0'])
+}
+
+fn base_name(path: &str) -> &str {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("root")
+}
+
+// Renders permission bits the way `ls -l` does, e.g. `drwxr-xr-x`.
+fn mode_string(kind: char, mode: u32) -> String {
+    let mut out = String::from(kind);
+    for (shift, special, set) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => set,
+            (false, true) => set.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+// Lists the direct children of `dir` from the archive Docker returns for it.
+// Entries are named relative to the directory's parent (`etc/passwd` for
+// `/etc`), or to the directory itself for `/` (`./etc`). Only the headers are
+// read, so file names need no escaping and the container may be stopped.
+fn list_archive(reader: impl Read, dir: &str) -> Result<Vec<FileEntry>, String> {
+    let depth = if dir.trim_end_matches('/').is_empty() {
+        1
+    } else {
+        2
+    };
+    let parent = dir.trim_end_matches('/');
+
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+
+    let mut listing = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read archive: {}", e))?;
+        let path = entry
+            .path()
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        let components: Vec<String> = path
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        if components.len() != depth {
+            continue;
+        }
+
+        let header = entry.header();
+        let (kind, symbol) = match header.entry_type() {
+            EntryType::Directory => ("directory", 'd'),
+            EntryType::Symlink => ("symlink", 'l'),
+            EntryType::Regular | EntryType::Continuous | EntryType::Link => ("file", '-'),
+            EntryType::Char => ("other", 'c'),
+            EntryType::Block => ("other", 'b'),
+            EntryType::Fifo => ("other", 'p'),
+            _ => ("other", '?'),
+        };
+        let name = components[depth - 1].clone();
+        listing.push(FileEntry {
+            path: format!("{}/{}", parent, name),
+            name,
+            kind: kind.to_string(),
+            size: header.size().unwrap_or(0),
+            modified: header.mtime().unwrap_or(0) as i64,
+            mode: mode_string(symbol, header.mode().unwrap_or(0)),
+        });
+    }
+    Ok(listing)
+}
+
+// Hands archive chunks from the async download to the blocking tar reader.
+struct ChunkReader {
+    chunks: mpsc::Receiver<Result<web::Bytes, String>>,
+    current: web::Bytes,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.blocking_recv() {
+                Some(Ok(chunk)) => self.current = chunk,
+                Some(Err(e)) => return Err(io::Error::other(e)),
+                None => return Ok(0),
+            }
+        }
+        let chunk = self.current.split_to(buf.len().min(self.current.len()));
+        buf[..chunk.len()].copy_from_slice(&chunk);
+        Ok(chunk.len())
+    }
+}
+
+fn archive_file(name: &str, content: &[u8]) -> Result<Vec<u8>, String> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder
+        .append_data(&mut header, name, content)
+        .and_then(|_| builder.into_inner())
+        .map_err(|e| format!("Failed to build archive: {}", e))
+}
+
+pub async fn list_files(
+    id: web::Path<String>,
+    query: web::Query<PathQuery>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    if !is_valid_path(&query.path) {
+        return HttpResponse::BadRequest().json("Path must be absolute");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let dir = query.path.clone();
+    let mut archive = docker.download_from_container(
+        &id.into_inner(),
+        Some(DownloadFromContainerOptions { path: dir.clone() }),
+    );
+
+    let first = match archive.next().await {
+        Some(Ok(chunk)) => chunk,
+        Some(Err(e)) => return HttpResponse::NotFound().json(format!("Path not found: {}", e)),
+        None => web::Bytes::new(),
+    };
+
+    let (sender, chunks) = mpsc::channel(LISTING_BUFFER);
+    let reader = ChunkReader {
+        chunks,
+        current: first,
+    };
+    let parse = tokio::task::spawn_blocking(move || list_archive(reader, &dir));
+
+    // The archive holds the whole tree, so it is parsed as it arrives rather
+    // than buffered. A closed channel means the parser already gave up.
+    while let Some(chunk) = archive.next().await {
+        let chunk = chunk.map_err(|e| e.to_string());
+        if sender.send(chunk).await.is_err() {
+            break;
+        }
+    }
+    drop(sender);
+
+    match parse.await {
+        Ok(Ok(mut entries)) => {
+            entries.sort_by(|a, b| {
+                (a.kind != "directory")
+                    .cmp(&(b.kind != "directory"))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            HttpResponse::Ok().json(entries)
+        }
+        Ok(Err(e)) => {
+            HttpResponse::BadRequest().json(format!("Failed to list {}: {}", query.path, e))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(format!("Listing failed: {}", e)),
+    }
+}
+
+pub async fn download_files(
+    id: web::Path<String>,
+    query: web::Query<PathQuery>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    if !is_valid_path(&query.path) {
+        return HttpResponse::BadRequest().json("Path must be absolute");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let mut archive = docker.download_from_container(
+        &id.into_inner(),
+        Some(DownloadFromContainerOptions {
+            path: query.path.clone(),
+        }),
+    );
+
+    // Docker only reports a missing path once the body is requested, so the
+    // first chunk is read before committing to a successful response.
+    let first = match archive.next().await {
+        Some(Ok(chunk)) => chunk,
+        Some(Err(e)) => return HttpResponse::NotFound().json(format!("Path not found: {}", e)),
+        None => web::Bytes::new(),
+    };
+
+    let body =
+        stream::once(async move { Ok(first) }).chain(archive.map(|chunk| {
+            chunk.map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))
+        }));
+
+    HttpResponse::Ok()
+        .content_type("application/x-tar")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}.tar\"", base_name(&query.path)),
+        ))
+        .streaming(body)
+}
+
+// Without a `name` the body is taken to be a tar archive and extracted into
+// `path` as is; with one it is wrapped into an archive holding that file.
+pub async fn upload_files(
+    id: web::Path<String>,
+    query: web::Query<UploadQuery>,
+    body: web::Bytes,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    if !is_valid_path(&query.path) {
+        return HttpResponse::BadRequest().json("Path must be absolute");
+    }
+
+    let archive = match &query.name {
+        Some(name) if !is_valid_file_name(name) => {
+            return HttpResponse::BadRequest().json("Invalid file name");
+        }
+        Some(name) => match archive_file(name, &body) {
+            Ok(a) => web::Bytes::from(a),
+            Err(e) => return HttpResponse::InternalServerError().json(e),
+        },
+        None => body,
+    };
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let options = UploadToContainerOptions {
+        path: query.path.clone(),
+        ..Default::default()
+    };
+
+    match docker
+        .upload_to_container(&id.into_inner(), Some(options), body_full(archive))
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(format!("Uploaded to {}", query.path)),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to upload: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(builder: &mut tar::Builder<Vec<u8>>, path: &str, kind: EntryType, mode: u32) {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(kind);
+        header.set_mode(mode);
+        header.set_size(if kind == EntryType::Regular { 3 } else { 0 });
+        header.set_mtime(1_700_000_000);
+        let data: &[u8] = if kind == EntryType::Regular {
+            b"abc"
+        } else {
+            b""
+        };
+        builder.append_data(&mut header, path, data).unwrap();
+    }
+
+    #[test]
+    fn only_direct_children_are_listed_from_the_archive() {
+        let mut builder = tar::Builder::new(Vec::new());
+        entry(&mut builder, "etc/", EntryType::Directory, 0o755);
+        entry(&mut builder, "etc/ssl/", EntryType::Directory, 0o755);
+        entry(&mut builder, "etc/ssl/cert.pem", EntryType::Regular, 0o644);
+        entry(&mut builder, "etc/a|b\nc", EntryType::Regular, 0o4755);
+        entry(&mut builder, "etc/tmp/", EntryType::Directory, 0o1777);
+        let archive = builder.into_inner().unwrap();
+
+        let listing = list_archive(archive.as_slice(), "/etc/").unwrap();
+        let names: Vec<(&str, &str, &str)> = listing
+            .iter()
+            .map(|e| (e.path.as_str(), e.kind.as_str(), e.mode.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("/etc/ssl", "directory", "drwxr-xr-x"),
+                ("/etc/a|b\nc", "file", "-rwsr-xr-x"),
+                ("/etc/tmp", "directory", "drwxrwxrwt"),
+            ]
+        );
+        assert_eq!(listing[1].size, 3);
+        assert_eq!(listing[1].modified, 1_700_000_000);
+    }
+
+    #[test]
+    fn the_root_directory_is_listed_relative_to_itself() {
+        let mut builder = tar::Builder::new(Vec::new());
+        entry(&mut builder, "./", EntryType::Directory, 0o755);
+        entry(&mut builder, "./bin/", EntryType::Directory, 0o755);
+        entry(&mut builder, "./bin/sh", EntryType::Regular, 0o755);
+        let archive = builder.into_inner().unwrap();
+
+        let listing = list_archive(archive.as_slice(), "/").unwrap();
+        assert_eq!(listing.len(), 1);
+        assert_eq!(listing[0].path, "/bin");
+    }
+}
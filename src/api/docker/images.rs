@@ -0,0 +1,378 @@
+use actix_web::{web, HttpResponse, Responder};
+use bollard::auth::DockerCredentials;
+use bollard::container::ListContainersOptions;
+use bollard::image::{
+    CreateImageOptions, ListImagesOptions, PruneImagesOptions, RemoveImageOptions, TagImageOptions,
+};
+use bollard::Docker;
+use chrono::DateTime;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::registries::RegistryStore;
+use super::{container_name, DockerClient};
+
+#[derive(Serialize)]
+pub struct ImageInfo {
+    pub id: String,
+    pub tags: Vec<String>,
+    pub size: i64,
+    pub created: String,
+    pub containers: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ImageLayer {
+    pub id: String,
+    pub created: String,
+    pub created_by: String,
+    pub tags: Vec<String>,
+    pub size: i64,
+    pub comment: String,
+}
+
+#[derive(Serialize)]
+pub struct ImageHistory {
+    pub id: String,
+    pub layers: Vec<String>,
+    pub history: Vec<ImageLayer>,
+}
+
+#[derive(Serialize)]
+pub struct PullProgress {
+    pub id: Option<String>,
+    pub status: Option<String>,
+    pub progress: Option<String>,
+    pub current: Option<i64>,
+    pub total: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PruneReport {
+    pub deleted: Vec<String>,
+    pub space_reclaimed: i64,
+}
+
+#[derive(Deserialize)]
+pub struct PullRequest {
+    pub image: String,
+    pub tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TagRequest {
+    pub repo: String,
+    pub tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PruneRequest {
+    #[serde(default)]
+    pub all: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveQuery {
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn format_timestamp(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0)
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_default()
+}
+
+pub fn is_valid_reference(reference: &str) -> bool {
+    !reference.is_empty()
+        && !reference.starts_with('-')
+        && reference
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/_-.:@".contains(c))
+}
+
+// Docker pulls every tag of a repository when the tag is left empty, so an
+// untagged reference defaults to "latest" like the docker CLI does.
+fn split_reference(image: &str, tag: Option<&str>) -> (String, String) {
+    if let Some(tag) = tag.filter(|t| !t.is_empty()) {
+        return (image.to_string(), tag.to_string());
+    }
+    let name_start = image.rfind('/').map(|i| i + 1).unwrap_or(0);
+    if image.contains('@') {
+        return (image.to_string(), String::new());
+    }
+    match image[name_start..].rfind(':') {
+        Some(i) => (
+            image[..name_start + i].to_string(),
+            image[name_start + i + 1..].to_string(),
+        ),
+        None => (image.to_string(), "latest".to_string()),
+    }
+}
+
+pub async fn pull(
+    docker: &Docker,
+    image: &str,
+    credentials: Option<DockerCredentials>,
+) -> Result<(), String> {
+    let (from_image, tag) = split_reference(image, None);
+    let mut progress = docker.create_image(
+        Some(CreateImageOptions {
+            from_image,
+            tag,
+            ..Default::default()
+        }),
+        None,
+        credentials,
+    );
+    while let Some(item) = progress.next().await {
+        item.map_err(|e| format!("Failed to pull {}: {}", image, e))?;
+    }
+    Ok(())
+}
+
+pub async fn list_images(client: web::Data<DockerClient>) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let images = match docker
+        .list_images(Some(ListImagesOptions::<String> {
+            all: false,
+            ..Default::default()
+        }))
+        .await
+    {
+        Ok(i) => i,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Docker error: {}", e)),
+    };
+
+    let containers = docker
+        .list_containers(Some(ListContainersOptions::<String> {
+            all: true,
+            ..Default::default()
+        }))
+        .await
+        .unwrap_or_default();
+
+    let mut users: HashMap<String, Vec<String>> = HashMap::new();
+    for c in containers {
+        if let Some(image_id) = c.image_id {
+            users
+                .entry(image_id)
+                .or_default()
+                .push(container_name(c.names));
+        }
+    }
+
+    let info: Vec<ImageInfo> = images
+        .into_iter()
+        .map(|i| ImageInfo {
+            containers: users.remove(&i.id).unwrap_or_default(),
+            tags: i
+                .repo_tags
+                .into_iter()
+                .filter(|t| t != "<none>:<none>")
+                .collect(),
+            size: i.size,
+            created: format_timestamp(i.created),
+            id: i.id,
+        })
+        .collect();
+
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn pull_image(
+    body: web::Json<PullRequest>,
+    client: web::Data<DockerClient>,
+    registries: web::Data<RegistryStore>,
+) -> impl Responder {
+    if !is_valid_reference(&body.image)
+        || body.tag.as_deref().is_some_and(|t| !is_valid_reference(t))
+    {
+        return HttpResponse::BadRequest().json("Invalid image reference");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let (from_image, tag) = split_reference(&body.image, body.tag.as_deref());
+    log::info!("Pulling image {}:{}", from_image, tag);
+    let credentials = registries.credentials_for(&from_image);
+
+    let progress = docker
+        .create_image(
+            Some(CreateImageOptions {
+                from_image,
+                tag,
+                ..Default::default()
+            }),
+            None,
+            credentials,
+        )
+        .map(|item| {
+            let line = match item {
+                Ok(info) => PullProgress {
+                    id: info.id,
+                    status: info.status,
+                    progress: info.progress,
+                    current: info.progress_detail.as_ref().and_then(|p| p.current),
+                    total: info.progress_detail.as_ref().and_then(|p| p.total),
+                    error: info.error,
+                },
+                Err(e) => PullProgress {
+                    id: None,
+                    status: None,
+                    progress: None,
+                    current: None,
+                    total: None,
+                    error: Some(e.to_string()),
+                },
+            };
+            let mut json = serde_json::to_vec(&line).unwrap_or_default();
+            json.push(b'\n');
+            Ok::<_, actix_web::Error>(web::Bytes::from(json))
+        });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(progress)
+}
+
+pub async fn remove_image(
+    id: web::Path<String>,
+    query: web::Query<RemoveQuery>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let options = RemoveImageOptions {
+        force: query.force,
+        noprune: false,
+    };
+
+    match docker
+        .remove_image(&id.into_inner(), Some(options), None)
+        .await
+    {
+        Ok(items) => {
+            let removed: Vec<String> = items
+                .into_iter()
+                .filter_map(|i| i.deleted.or(i.untagged))
+                .collect();
+            HttpResponse::Ok().json(removed)
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to remove image: {}", e))
+        }
+    }
+}
+
+pub async fn tag_image(
+    id: web::Path<String>,
+    body: web::Json<TagRequest>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let tag = body.tag.clone().unwrap_or_else(|| "latest".to_string());
+    if !is_valid_reference(&body.repo) || !is_valid_reference(&tag) {
+        return HttpResponse::BadRequest().json("Invalid repository or tag");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let options = TagImageOptions {
+        repo: body.repo.clone(),
+        tag: tag.clone(),
+    };
+
+    match docker.tag_image(&id.into_inner(), Some(options)).await {
+        Ok(_) => HttpResponse::Ok().json(format!("Tagged as {}:{}", body.repo, tag)),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to tag image: {}", e)),
+    }
+}
+
+pub async fn prune_images(
+    body: web::Json<PruneRequest>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let dangling = if body.all { "false" } else { "true" };
+    let mut filters = HashMap::new();
+    filters.insert("dangling".to_string(), vec![dangling.to_string()]);
+
+    match docker
+        .prune_images(Some(PruneImagesOptions { filters }))
+        .await
+    {
+        Ok(res) => {
+            let deleted = res
+                .images_deleted
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|i| i.deleted.or(i.untagged))
+                .collect();
+            HttpResponse::Ok().json(PruneReport {
+                deleted,
+                space_reclaimed: res.space_reclaimed.unwrap_or(0),
+            })
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to prune images: {}", e))
+        }
+    }
+}
+
+pub async fn get_image_history(
+    id: web::Path<String>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let image = id.into_inner();
+
+    let inspect = match docker.inspect_image(&image).await {
+        Ok(i) => i,
+        Err(e) => return HttpResponse::NotFound().json(format!("Image not found: {}", e)),
+    };
+    let history = match docker.image_history(&image).await {
+        Ok(h) => h,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to read image history: {}", e))
+        }
+    };
+
+    HttpResponse::Ok().json(ImageHistory {
+        id: inspect.id.unwrap_or(image),
+        layers: inspect.root_fs.and_then(|r| r.layers).unwrap_or_default(),
+        history: history
+            .into_iter()
+            .map(|h| ImageLayer {
+                id: h.id,
+                created: format_timestamp(h.created),
+                created_by: h.created_by,
+                tags: h.tags,
+                size: h.size,
+                comment: h.comment,
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,184 @@
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use actix_ws::AggregatedMessage;
+use bollard::container::{LogOutput, LogsOptions};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::DockerClient;
+use crate::api::auth::verify_token;
+
+const DEFAULT_TAIL: &str = "200";
+
+#[derive(Deserialize)]
+pub struct LogQuery {
+    #[serde(default)]
+    pub token: String,
+    pub tail: Option<String>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    #[serde(default)]
+    pub timestamps: bool,
+    #[serde(default = "default_true")]
+    pub stdout: bool,
+    #[serde(default = "default_true")]
+    pub stderr: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+pub struct LogLine {
+    pub stream: String,
+    pub timestamp: Option<String>,
+    pub message: String,
+}
+
+fn log_options(query: &LogQuery, follow: bool) -> Result<LogsOptions<String>, String> {
+    let tail = query
+        .tail
+        .clone()
+        .unwrap_or_else(|| DEFAULT_TAIL.to_string());
+    if tail != "all" && tail.parse::<u64>().is_err() {
+        return Err("Tail must be a number or \"all\"".to_string());
+    }
+    if !query.stdout && !query.stderr {
+        return Err("At least one of stdout or stderr must be selected".to_string());
+    }
+
+    Ok(LogsOptions {
+        follow,
+        stdout: query.stdout,
+        stderr: query.stderr,
+        since: query.since.unwrap_or(0),
+        until: query.until.unwrap_or(0),
+        timestamps: query.timestamps,
+        tail,
+    })
+}
+
+// Splits one chunk of log output into lines. With timestamps enabled Docker
+// prefixes every line with an RFC 3339 timestamp followed by a space.
+pub fn split_output(output: LogOutput, timestamps: bool) -> Vec<LogLine> {
+    let (stream, message) = match output {
+        LogOutput::StdOut { message } => ("stdout", message),
+        LogOutput::StdErr { message } => ("stderr", message),
+        LogOutput::StdIn { message } => ("stdin", message),
+        LogOutput::Console { message } => ("console", message),
+    };
+
+    String::from_utf8_lossy(&message)
+        .lines()
+        .map(|line| {
+            let (timestamp, message) = match line.split_once(' ') {
+                Some((ts, rest)) if timestamps => (Some(ts.to_string()), rest.to_string()),
+                _ => (None, line.to_string()),
+            };
+            LogLine {
+                stream: stream.to_string(),
+                timestamp,
+                message,
+            }
+        })
+        .collect()
+}
+
+pub async fn get_container_logs(
+    id: web::Path<String>,
+    query: web::Query<LogQuery>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let options = match log_options(&query, false) {
+        Ok(o) => o,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+
+    let mut stream = docker.logs(&id.into_inner(), Some(options));
+    let mut lines = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(output) => lines.extend(split_output(output, query.timestamps)),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .json(format!("Failed to fetch logs: {}", e))
+            }
+        }
+    }
+
+    HttpResponse::Ok().json(lines)
+}
+
+pub async fn ws_container_logs(
+    req: HttpRequest,
+    stream: web::Payload,
+    id: web::Path<String>,
+    query: web::Query<LogQuery>,
+    client: web::Data<DockerClient>,
+) -> Result<HttpResponse, Error> {
+    if !verify_token(&query.token) {
+        return Ok(HttpResponse::Unauthorized().body("Invalid token"));
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return Ok(res),
+    };
+    let options = match log_options(&query, true) {
+        Ok(o) => o,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(e)),
+    };
+    let timestamps = query.timestamps;
+    let container_id = id.into_inner();
+
+    let (res, mut session, msg_stream) = actix_ws::handle(&req, stream)?;
+    let mut msg_stream = msg_stream.aggregate_continuations();
+
+    actix_web::rt::spawn(async move {
+        let mut logs = docker.logs(&container_id, Some(options));
+
+        loop {
+            tokio::select! {
+                chunk = logs.next() => {
+                    let output = match chunk {
+                        Some(Ok(output)) => output,
+                        Some(Err(e)) => {
+                            let _ = session.text(format!("Log stream error: {}", e)).await;
+                            break;
+                        }
+                        None => break,
+                    };
+                    let mut closed = false;
+                    for line in split_output(output, timestamps) {
+                        let json = serde_json::to_string(&line).unwrap_or_default();
+                        if session.text(json).await.is_err() {
+                            closed = true;
+                            break;
+                        }
+                    }
+                    if closed {
+                        break;
+                    }
+                }
+
+                Some(msg) = msg_stream.next() => {
+                    match msg {
+                        Ok(AggregatedMessage::Ping(msg)) => {
+                            let _ = session.pong(&msg).await;
+                        }
+                        Ok(AggregatedMessage::Close(_)) | Err(_) => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(res)
+}
@@ -0,0 +1,311 @@
+use actix_web::{web, HttpResponse, Responder};
+use bollard::container::ListContainersOptions;
+use bollard::models::{EndpointIpamConfig, EndpointSettings, Ipam, IpamConfig, Network};
+use bollard::network::{
+    ConnectNetworkOptions, CreateNetworkOptions, DisconnectNetworkOptions, InspectNetworkOptions,
+    ListNetworksOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{container_name, is_valid_name, DockerClient};
+
+#[derive(Serialize)]
+pub struct Subnet {
+    pub subnet: Option<String>,
+    pub gateway: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NetworkMember {
+    pub name: String,
+    pub ipv4_address: Option<String>,
+    pub ipv6_address: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct NetworkInfo {
+    pub id: String,
+    pub name: String,
+    pub driver: String,
+    pub scope: String,
+    pub internal: bool,
+    pub attachable: bool,
+    pub subnets: Vec<Subnet>,
+    pub containers: Vec<NetworkMember>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateNetworkRequest {
+    pub name: String,
+    pub driver: Option<String>,
+    pub subnet: Option<String>,
+    pub gateway: Option<String>,
+    #[serde(default)]
+    pub internal: bool,
+    #[serde(default)]
+    pub attachable: bool,
+    #[serde(default)]
+    pub enable_ipv6: bool,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct NetworkAttachment {
+    pub container: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub ipv4_address: Option<String>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn network_info(n: Network, containers: Vec<NetworkMember>) -> NetworkInfo {
+    NetworkInfo {
+        id: n.id.unwrap_or_default(),
+        name: n.name.unwrap_or_default(),
+        driver: n.driver.unwrap_or_default(),
+        scope: n.scope.unwrap_or_default(),
+        internal: n.internal.unwrap_or(false),
+        attachable: n.attachable.unwrap_or(false),
+        subnets: n
+            .ipam
+            .and_then(|i| i.config)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|c| Subnet {
+                subnet: c.subnet,
+                gateway: c.gateway,
+            })
+            .collect(),
+        containers,
+    }
+}
+
+fn strip_prefix_len(address: Option<String>) -> Option<String> {
+    address
+        .filter(|a| !a.is_empty())
+        .map(|a| a.split('/').next().unwrap_or_default().to_string())
+}
+
+pub async fn list_networks(client: web::Data<DockerClient>) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let networks = match docker
+        .list_networks(None::<ListNetworksOptions<String>>)
+        .await
+    {
+        Ok(n) => n,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Docker error: {}", e)),
+    };
+
+    let containers = docker
+        .list_containers(Some(ListContainersOptions::<String> {
+            all: true,
+            ..Default::default()
+        }))
+        .await
+        .unwrap_or_default();
+
+    let mut members: HashMap<String, Vec<NetworkMember>> = HashMap::new();
+    for c in containers {
+        let name = container_name(c.names);
+        let endpoints = c
+            .network_settings
+            .and_then(|n| n.networks)
+            .unwrap_or_default();
+        for (network, endpoint) in endpoints {
+            let key = endpoint.network_id.unwrap_or(network);
+            members.entry(key).or_default().push(NetworkMember {
+                name: name.clone(),
+                ipv4_address: endpoint.ip_address.filter(|a| !a.is_empty()),
+                ipv6_address: endpoint.global_ipv6_address.filter(|a| !a.is_empty()),
+            });
+        }
+    }
+
+    let info: Vec<NetworkInfo> = networks
+        .into_iter()
+        .map(|n| {
+            let attached =
+                n.id.as_ref()
+                    .and_then(|id| members.remove(id))
+                    .unwrap_or_default();
+            network_info(n, attached)
+        })
+        .collect();
+
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn inspect_network(
+    id: web::Path<String>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    match docker
+        .inspect_network(&id.into_inner(), None::<InspectNetworkOptions<String>>)
+        .await
+    {
+        Ok(mut n) => {
+            let containers = n
+                .containers
+                .take()
+                .unwrap_or_default()
+                .into_values()
+                .map(|c| NetworkMember {
+                    name: c.name.unwrap_or_default(),
+                    ipv4_address: strip_prefix_len(c.ipv4_address),
+                    ipv6_address: strip_prefix_len(c.ipv6_address),
+                })
+                .collect();
+            HttpResponse::Ok().json(network_info(n, containers))
+        }
+        Err(e) => HttpResponse::NotFound().json(format!("Network not found: {}", e)),
+    }
+}
+
+pub async fn create_network(
+    body: web::Json<CreateNetworkRequest>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    if !is_valid_name(&body.name) {
+        return HttpResponse::BadRequest().json("Invalid network name");
+    }
+    if body.gateway.is_some() && body.subnet.is_none() {
+        return HttpResponse::BadRequest().json("A gateway requires a subnet");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let body = body.into_inner();
+    let ipam = match body.subnet {
+        Some(subnet) => Ipam {
+            config: Some(vec![IpamConfig {
+                subnet: Some(subnet),
+                gateway: body.gateway,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        },
+        None => Ipam::default(),
+    };
+
+    let options = CreateNetworkOptions {
+        name: body.name.clone(),
+        driver: body.driver.unwrap_or_else(|| "bridge".to_string()),
+        internal: body.internal,
+        attachable: body.attachable,
+        enable_ipv6: body.enable_ipv6,
+        ipam,
+        labels: body.labels,
+        ..Default::default()
+    };
+
+    match docker.create_network(options).await {
+        Ok(res) => HttpResponse::Ok().json(res.id),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to create network: {}", e))
+        }
+    }
+}
+
+pub async fn remove_network(
+    id: web::Path<String>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let id = id.into_inner();
+
+    match docker.remove_network(&id).await {
+        Ok(_) => HttpResponse::Ok().json(format!("Network {} removed", id)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to remove network: {}", e))
+        }
+    }
+}
+
+pub async fn connect_container(
+    id: web::Path<String>,
+    body: web::Json<NetworkAttachment>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let network = id.into_inner();
+    let body = body.into_inner();
+
+    let endpoint_config = EndpointSettings {
+        aliases: (!body.aliases.is_empty()).then_some(body.aliases),
+        ipam_config: body.ipv4_address.map(|ip| EndpointIpamConfig {
+            ipv4_address: Some(ip),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    match docker
+        .connect_network(
+            &network,
+            ConnectNetworkOptions {
+                container: body.container.clone(),
+                endpoint_config,
+            },
+        )
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(format!(
+            "Container {} connected to {}",
+            body.container, network
+        )),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to connect container: {}", e))
+        }
+    }
+}
+
+pub async fn disconnect_container(
+    id: web::Path<String>,
+    body: web::Json<NetworkAttachment>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let network = id.into_inner();
+
+    match docker
+        .disconnect_network(
+            &network,
+            DisconnectNetworkOptions {
+                container: body.container.clone(),
+                force: body.force,
+            },
+        )
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().json(format!(
+            "Container {} disconnected from {}",
+            body.container, network
+        )),
+        Err(e) => HttpResponse::InternalServerError()
+            .json(format!("Failed to disconnect container: {}", e)),
+    }
+}
@@ -0,0 +1,479 @@
+use actix_web::{web, HttpResponse, Responder};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bollard::auth::DockerCredentials;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+const REGISTRIES_FILE: &str = "wadm-registries.json";
+const REGISTRY_KEY_FILE: &str = "wadm-registries.key";
+const DOCKER_HUB: &str = "docker.io";
+const NONCE_LEN: usize = 12;
+const DAEMON_CONFIG: &str = "/etc/docker/daemon.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+struct StoredCredential {
+    username: String,
+    // Base64 of the AES-256-GCM nonce followed by the ciphertext.
+    password: String,
+    updated: String,
+}
+
+#[derive(Serialize)]
+pub struct RegistryInfo {
+    pub registry: String,
+    pub username: String,
+    pub updated: String,
+}
+
+#[derive(Deserialize)]
+pub struct RegistryLogin {
+    pub registry: String,
+    pub username: String,
+    pub password: String,
+    #[serde(default)]
+    pub verify: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RegistryTest {
+    pub registry: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+pub struct RegistryStore {
+    cipher: Option<Aes256Gcm>,
+    entries: Mutex<BTreeMap<String, StoredCredential>>,
+    http: reqwest::Client,
+}
+
+fn write_private(path: &str, content: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(content)
+}
+
+fn load_key() -> Result<Aes256Gcm, String> {
+    match fs::read(REGISTRY_KEY_FILE) {
+        Ok(key) => Aes256Gcm::new_from_slice(&key)
+            .map_err(|_| format!("{} does not hold a valid key", REGISTRY_KEY_FILE)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = Aes256Gcm::generate_key(OsRng);
+            write_private(REGISTRY_KEY_FILE, &key)
+                .map_err(|e| format!("Failed to create {}: {}", REGISTRY_KEY_FILE, e))?;
+            Ok(Aes256Gcm::new(&key))
+        }
+        Err(e) => Err(format!("Failed to read {}: {}", REGISTRY_KEY_FILE, e)),
+    }
+}
+
+// Docker Hub is known under several hostnames; they all share one entry.
+pub fn normalize_registry(registry: &str) -> String {
+    let host = registry
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    match host.as_str() {
+        "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+            DOCKER_HUB.to_string()
+        }
+        _ => host,
+    }
+}
+
+fn is_valid_registry(registry: &str) -> bool {
+    !registry.is_empty()
+        && registry
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".-:[]".contains(c))
+}
+
+// Follows the docker CLI: the first path component names a registry only if
+// it looks like a host, otherwise the image lives on Docker Hub.
+pub fn registry_of(image: &str) -> String {
+    match image.split_once('/') {
+        Some((first, _)) if first.contains(['.', ':']) || first == "localhost" => {
+            normalize_registry(first)
+        }
+        _ => DOCKER_HUB.to_string(),
+    }
+}
+
+fn server_address(registry: &str) -> String {
+    if registry == DOCKER_HUB {
+        "https://index.docker.io/v1/".to_string()
+    } else {
+        registry.to_string()
+    }
+}
+
+fn api_host(registry: &str) -> &str {
+    if registry == DOCKER_HUB {
+        "registry-1.docker.io"
+    } else {
+        registry
+    }
+}
+
+fn is_loopback(registry: &str) -> bool {
+    let host = registry.rsplit_once(':').map_or(registry, |(h, _)| h);
+    host == "localhost" || host.starts_with("127.") || host == "[::1]"
+}
+
+// Like the Docker daemon, plain HTTP is only used for loopback registries and
+// the ones listed under `insecure-registries` in its daemon.json.
+fn is_insecure(registry: &str) -> bool {
+    if is_loopback(registry) {
+        return true;
+    }
+    let Ok(content) = fs::read_to_string(DAEMON_CONFIG) else {
+        return false;
+    };
+    let config: serde_json::Value = serde_json::from_str(&content).unwrap_or_default();
+    config["insecure-registries"]
+        .as_array()
+        .is_some_and(|list| {
+            list.iter()
+                .filter_map(|r| r.as_str())
+                .any(|r| normalize_registry(r) == registry)
+        })
+}
+
+// Splits `Bearer realm="...",service="..."` into its scheme and parameters.
+// Values are quoted and may themselves contain commas.
+fn parse_challenge(header: &str) -> (String, BTreeMap<String, String>) {
+    let (scheme, rest) = header.trim().split_once(' ').unwrap_or((header, ""));
+    let mut params = BTreeMap::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut quoted = false;
+
+    for c in rest.chars() {
+        match c {
+            '"' if in_value => quoted = !quoted,
+            '=' if !in_value => in_value = true,
+            ',' if !quoted => {
+                params.insert(key.trim().to_lowercase(), value.clone());
+                key.clear();
+                value.clear();
+                in_value = false;
+            }
+            _ if in_value => value.push(c),
+            _ => key.push(c),
+        }
+    }
+    if !key.trim().is_empty() {
+        params.insert(key.trim().to_lowercase(), value);
+    }
+
+    (scheme.to_lowercase(), params)
+}
+
+impl RegistryStore {
+    pub fn load() -> Self {
+        let cipher = load_key()
+            .map_err(|e| log::error!("Registry credentials are unavailable: {}", e))
+            .ok();
+        let entries = match fs::read_to_string(REGISTRIES_FILE) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => BTreeMap::new(),
+        };
+
+        RegistryStore {
+            cipher,
+            entries: Mutex::new(entries),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let entries = self.entries.lock().unwrap();
+        let content = serde_json::to_string_pretty(&*entries).map_err(|e| e.to_string())?;
+        write_private(REGISTRIES_FILE, content.as_bytes())
+            .map_err(|e| format!("Failed to save registries: {}", e))
+    }
+
+    fn cipher(&self) -> Result<&Aes256Gcm, String> {
+        self.cipher
+            .as_ref()
+            .ok_or_else(|| "Registry credential key is unavailable".to_string())
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "Failed to encrypt credentials".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(STANDARD.encode(sealed))
+    }
+
+    fn decrypt(&self, sealed: &str) -> Result<String, String> {
+        let sealed = STANDARD
+            .decode(sealed)
+            .map_err(|_| "Stored credentials are corrupt".to_string())?;
+        if sealed.len() < NONCE_LEN {
+            return Err("Stored credentials are corrupt".to_string());
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher()?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt stored credentials".to_string())?;
+        String::from_utf8(plaintext).map_err(|_| "Stored credentials are corrupt".to_string())
+    }
+
+    fn login(&self, registry: &str) -> Option<(String, String)> {
+        let entry = self.entries.lock().unwrap().get(registry).cloned()?;
+        match self.decrypt(&entry.password) {
+            Ok(password) => Some((entry.username, password)),
+            Err(e) => {
+                log::warn!("Ignoring credentials for {}: {}", registry, e);
+                None
+            }
+        }
+    }
+
+    pub fn credentials_for(&self, image: &str) -> Option<DockerCredentials> {
+        let registry = registry_of(image);
+        let (username, password) = self.login(&registry)?;
+        Some(DockerCredentials {
+            username: Some(username),
+            password: Some(password),
+            serveraddress: Some(server_address(&registry)),
+            ..Default::default()
+        })
+    }
+
+    // A docker CLI config.json with every stored login added to the `auths`
+    // of `base`, for tools such as compose that read credentials from a file
+    // rather than taking them through the API.
+    pub fn docker_config(&self, base: Option<&str>) -> Option<String> {
+        let registries: Vec<String> = self.entries.lock().unwrap().keys().cloned().collect();
+        let logins: Vec<(String, String)> = registries
+            .iter()
+            .filter_map(|registry| {
+                let (username, password) = self.login(registry)?;
+                let auth = STANDARD.encode(format!("{}:{}", username, password));
+                Some((server_address(registry), auth))
+            })
+            .collect();
+        if logins.is_empty() {
+            return None;
+        }
+
+        let mut config: serde_json::Value = base
+            .and_then(|b| serde_json::from_str(b).ok())
+            .filter(|c: &serde_json::Value| c.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        if !config["auths"].is_object() {
+            config["auths"] = serde_json::json!({});
+        }
+        for (server, auth) in logins {
+            config["auths"][server] = serde_json::json!({ "auth": auth });
+        }
+        Some(config.to_string())
+    }
+
+    // Performs the registry v2 handshake: an anonymous probe of /v2/, then the
+    // Basic or Bearer flow named in the challenge with the given credentials.
+    // Registries that allow anonymous access still get an authenticated
+    // request, since only that one says anything about the credentials.
+    async fn check_login(
+        &self,
+        registry: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<(), String> {
+        let host = api_host(registry);
+        let insecure = is_insecure(registry);
+        let schemes: &[&str] = if insecure {
+            &["https", "http"]
+        } else {
+            &["https"]
+        };
+
+        let mut last_error = String::new();
+        for scheme in schemes {
+            let base = format!("{}://{}/v2/", scheme, host);
+            let probe = match self.http.get(&base).send().await {
+                Ok(r) => r,
+                Err(e) => {
+                    last_error = e.to_string();
+                    continue;
+                }
+            };
+
+            let challenge = if probe.status().is_success() {
+                "Basic".to_string()
+            } else if probe.status() == reqwest::StatusCode::UNAUTHORIZED {
+                probe
+                    .headers()
+                    .get(reqwest::header::WWW_AUTHENTICATE)
+                    .and_then(|h| h.to_str().ok())
+                    .unwrap_or("Basic")
+                    .to_string()
+            } else {
+                return Err(format!("Registry responded with {}", probe.status()));
+            };
+            let (auth_scheme, params) = parse_challenge(&challenge);
+
+            let request = match auth_scheme.as_str() {
+                "basic" => self.http.get(&base),
+                "bearer" => {
+                    let realm = params
+                        .get("realm")
+                        .ok_or("Registry sent a bearer challenge without a realm")?;
+                    // The credentials go to the realm, which may be another host.
+                    if !realm.starts_with("https://") && !insecure {
+                        return Err(format!(
+                            "Refusing to send credentials to a token service without HTTPS: {}",
+                            realm
+                        ));
+                    }
+                    let query: Vec<(&str, &String)> = params
+                        .get("service")
+                        .map(|s| ("service", s))
+                        .into_iter()
+                        .collect();
+                    self.http.get(realm).query(&query)
+                }
+                other => return Err(format!("Unsupported authentication scheme: {}", other)),
+            };
+
+            return match request.basic_auth(username, Some(password)).send().await {
+                Ok(r) if r.status().is_success() => Ok(()),
+                Ok(r)
+                    if r.status() == reqwest::StatusCode::UNAUTHORIZED
+                        || r.status() == reqwest::StatusCode::FORBIDDEN =>
+                {
+                    Err("Invalid username or password".to_string())
+                }
+                Ok(r) => Err(format!("Registry responded with {}", r.status())),
+                Err(e) => Err(format!("Failed to contact registry: {}", e)),
+            };
+        }
+
+        Err(format!(
+            "Registry {} is unreachable: {}",
+            registry, last_error
+        ))
+    }
+}
+
+pub async fn list_registries(store: web::Data<RegistryStore>) -> impl Responder {
+    let entries = store.entries.lock().unwrap();
+    let info: Vec<RegistryInfo> = entries
+        .iter()
+        .map(|(registry, entry)| RegistryInfo {
+            registry: registry.clone(),
+            username: entry.username.clone(),
+            updated: entry.updated.clone(),
+        })
+        .collect();
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn save_registry(
+    body: web::Json<RegistryLogin>,
+    store: web::Data<RegistryStore>,
+) -> impl Responder {
+    let registry = normalize_registry(&body.registry);
+    if !is_valid_registry(&registry) {
+        return HttpResponse::BadRequest().json("Invalid registry address");
+    }
+    if body.username.is_empty() || body.password.is_empty() {
+        return HttpResponse::BadRequest().json("Username and password are required");
+    }
+
+    if body.verify {
+        if let Err(e) = store
+            .check_login(&registry, &body.username, &body.password)
+            .await
+        {
+            return HttpResponse::BadRequest().json(format!("Login to {} failed: {}", registry, e));
+        }
+    }
+
+    let password = match store.encrypt(&body.password) {
+        Ok(p) => p,
+        Err(e) => return HttpResponse::InternalServerError().json(e),
+    };
+
+    store.entries.lock().unwrap().insert(
+        registry.clone(),
+        StoredCredential {
+            username: body.username.clone(),
+            password,
+            updated: Local::now().to_rfc3339(),
+        },
+    );
+
+    if let Err(e) = store.save() {
+        return HttpResponse::InternalServerError().json(e);
+    }
+    HttpResponse::Ok().json(format!("Credentials for {} saved", registry))
+}
+
+pub async fn remove_registry(
+    registry: web::Path<String>,
+    store: web::Data<RegistryStore>,
+) -> impl Responder {
+    let registry = normalize_registry(&registry.into_inner());
+    if store.entries.lock().unwrap().remove(&registry).is_none() {
+        return HttpResponse::NotFound().json("No credentials stored for this registry");
+    }
+
+    if let Err(e) = store.save() {
+        return HttpResponse::InternalServerError().json(e);
+    }
+    HttpResponse::Ok().json(format!("Credentials for {} removed", registry))
+}
+
+// Tests the given credentials, or the stored ones when none are supplied.
+pub async fn test_registry(
+    body: web::Json<RegistryTest>,
+    store: web::Data<RegistryStore>,
+) -> impl Responder {
+    let registry = normalize_registry(&body.registry);
+    if !is_valid_registry(&registry) {
+        return HttpResponse::BadRequest().json("Invalid registry address");
+    }
+
+    let (username, password) = match (&body.username, &body.password) {
+        (Some(u), Some(p)) => (u.clone(), p.clone()),
+        _ => match store.login(&registry) {
+            Some(login) => login,
+            None => {
+                return HttpResponse::NotFound().json("No credentials stored for this registry")
+            }
+        },
+    };
+
+    match store.check_login(&registry, &username, &password).await {
+        Ok(()) => HttpResponse::Ok().json(format!("Login to {} succeeded", registry)),
+        Err(e) => HttpResponse::BadRequest().json(format!("Login to {} failed: {}", registry, e)),
+    }
+}
@@ -0,0 +1,186 @@
+use bollard::{Docker, API_DEFAULT_VERSION};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CONNECT_TIMEOUT_SECS: u64 = 120;
+
+pub trait ContainerRuntime: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // Candidate API sockets, most preferred first.
+    fn sockets(&self) -> Vec<PathBuf>;
+
+    fn start(&self) -> Result<(), String>;
+
+    fn binary(&self) -> &'static str {
+        self.name()
+    }
+
+    fn is_installed(&self) -> bool {
+        Command::new("which")
+            .arg(self.binary())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn socket(&self) -> Option<PathBuf> {
+        self.sockets().into_iter().find(|s| s.exists())
+    }
+
+    // Set when the daemon is reached over the network rather than a socket.
+    fn remote_host(&self) -> Option<String> {
+        None
+    }
+
+    fn version(&self) -> String {
+        Command::new(self.binary())
+            .arg("--version")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default()
+    }
+
+    fn socket_or_default(&self) -> Result<PathBuf, String> {
+        self.socket()
+            .or_else(|| self.sockets().into_iter().next())
+            .ok_or_else(|| format!("No API socket known for {}", self.name()))
+    }
+
+    // The socket does not need to exist yet: bollard connects lazily, so a
+    // client built before the service starts works once it is up.
+    fn connect(&self) -> Result<Docker, String> {
+        connect_unix(&self.socket_or_default()?)
+    }
+}
+
+fn connect_unix(socket: &Path) -> Result<Docker, String> {
+    Docker::connect_with_unix(
+        &socket.to_string_lossy(),
+        CONNECT_TIMEOUT_SECS,
+        API_DEFAULT_VERSION,
+    )
+    .map_err(|e| format!("Failed to connect to {}: {}", socket.display(), e))
+}
+
+pub struct DockerRuntime;
+
+pub struct PodmanRuntime;
+
+fn current_uid() -> u32 {
+    std::fs::metadata("/proc/self")
+        .map(|m| m.uid())
+        .unwrap_or(0)
+}
+
+fn env_socket(var: &str) -> Option<PathBuf> {
+    std::env::var(var)
+        .ok()
+        .and_then(|v| v.strip_prefix("unix://").map(PathBuf::from))
+}
+
+fn env_remote(var: &str) -> Option<String> {
+    std::env::var(var)
+        .ok()
+        .filter(|v| !v.is_empty() && !v.starts_with("unix://"))
+}
+
+fn run_systemctl(args: &[&str], sudo: bool) -> Result<(), String> {
+    let mut command = if sudo {
+        let mut c = Command::new("sudo");
+        c.arg("systemctl");
+        c
+    } else {
+        Command::new("systemctl")
+    };
+
+    match command.args(args).output() {
+        Ok(o) if o.status.success() => Ok(()),
+        Ok(o) => Err(String::from_utf8_lossy(&o.stderr).trim().to_string()),
+        Err(e) => Err(format!("Failed to execute systemctl: {}", e)),
+    }
+}
+
+impl ContainerRuntime for DockerRuntime {
+    fn name(&self) -> &'static str {
+        "docker"
+    }
+
+    fn sockets(&self) -> Vec<PathBuf> {
+        let mut sockets: Vec<PathBuf> = env_socket("DOCKER_HOST").into_iter().collect();
+        sockets.push(PathBuf::from("/var/run/docker.sock"));
+        sockets.push(PathBuf::from(format!(
+            "/run/user/{}/docker.sock",
+            current_uid()
+        )));
+        sockets
+    }
+
+    fn start(&self) -> Result<(), String> {
+        run_systemctl(&["start", "docker"], true)
+    }
+
+    fn remote_host(&self) -> Option<String> {
+        env_remote("DOCKER_HOST")
+    }
+
+    // bollard's local defaults read DOCKER_HOST, which covers tcp:// and
+    // http:// daemons as well as sockets.
+    fn connect(&self) -> Result<Docker, String> {
+        match self.remote_host() {
+            Some(host) => Docker::connect_with_local_defaults()
+                .map_err(|e| format!("Failed to connect to {}: {}", host, e)),
+            None => connect_unix(&self.socket_or_default()?),
+        }
+    }
+}
+
+// Rootful Podman serves its Docker-compatible API on /run/podman, rootless
+// Podman under the user's runtime directory. The one matching the user WADM
+// runs as is tried first.
+impl ContainerRuntime for PodmanRuntime {
+    fn name(&self) -> &'static str {
+        "podman"
+    }
+
+    fn sockets(&self) -> Vec<PathBuf> {
+        let uid = current_uid();
+        let rootful = PathBuf::from("/run/podman/podman.sock");
+        let rootless = PathBuf::from(format!("/run/user/{}/podman/podman.sock", uid));
+
+        let mut sockets: Vec<PathBuf> = env_socket("CONTAINER_HOST").into_iter().collect();
+        if uid == 0 {
+            sockets.extend([rootful, rootless]);
+        } else {
+            sockets.extend([rootless, rootful]);
+        }
+        sockets
+    }
+
+    fn start(&self) -> Result<(), String> {
+        if current_uid() == 0 {
+            run_systemctl(&["start", "podman.socket"], false)
+        } else {
+            run_systemctl(&["--user", "start", "podman.socket"], false)
+        }
+    }
+}
+
+// Prefers a runtime with a remote host or a live API socket, then one that is
+// merely installed, and falls back to Docker so status can report it missing.
+pub fn detect() -> Box<dyn ContainerRuntime> {
+    let runtimes: Vec<Box<dyn ContainerRuntime>> =
+        vec![Box::new(DockerRuntime), Box::new(PodmanRuntime)];
+
+    let index = runtimes
+        .iter()
+        .position(|r| r.remote_host().is_some() || r.socket().is_some())
+        .or_else(|| runtimes.iter().position(|r| r.is_installed()))
+        .unwrap_or(0);
+
+    runtimes
+        .into_iter()
+        .nth(index)
+        .expect("runtime index is in range")
+}
@@ -0,0 +1,421 @@
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use actix_ws::AggregatedMessage;
+use bollard::container::{ListContainersOptions, StatsOptions};
+use bollard::models::ContainerStatsResponse;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use super::DockerClient;
+use crate::api::auth::verify_token;
+
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(5);
+const PUSH_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Clone)]
+pub struct ContainerStats {
+    pub id: String,
+    pub name: String,
+    pub cpu_usage: f64,
+    pub memory_usage: u64,
+    pub memory_limit: u64,
+    pub network_rx: u64,
+    pub network_tx: u64,
+    pub block_read: u64,
+    pub block_write: u64,
+    pub pids: u64,
+}
+
+#[derive(Default)]
+pub struct StatsCollector {
+    stats: Mutex<HashMap<String, ContainerStats>>,
+}
+
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    pub token: String,
+}
+
+impl StatsCollector {
+    pub fn snapshot(&self) -> Vec<ContainerStats> {
+        let mut stats: Vec<ContainerStats> = self.stats.lock().unwrap().values().cloned().collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        stats
+    }
+
+    fn find(&self, id: &str) -> Option<ContainerStats> {
+        self.stats
+            .lock()
+            .unwrap()
+            .values()
+            .find(|s| s.id.starts_with(id) || s.name == id)
+            .cloned()
+    }
+}
+
+// Mirrors `docker stats`: memory excludes the inactive page cache, and CPU is
+// the usage delta against the previous sample scaled by the online CPUs.
+pub fn compute_stats(id: &str, stats: &ContainerStatsResponse) -> ContainerStats {
+    let cpu_total = |s: &Option<bollard::models::ContainerCpuStats>| {
+        s.as_ref()
+            .and_then(|c| c.cpu_usage.as_ref())
+            .and_then(|u| u.total_usage)
+            .unwrap_or(0)
+    };
+    let system_total = |s: &Option<bollard::models::ContainerCpuStats>| {
+        s.as_ref().and_then(|c| c.system_cpu_usage).unwrap_or(0)
+    };
+
+    let online_cpus = stats
+        .cpu_stats
+        .as_ref()
+        .and_then(|c| c.online_cpus)
+        .unwrap_or(1);
+    let cpu_delta = cpu_total(&stats.cpu_stats) as f64 - cpu_total(&stats.precpu_stats) as f64;
+    let system_delta =
+        system_total(&stats.cpu_stats) as f64 - system_total(&stats.precpu_stats) as f64;
+
+    // Without a previous sample the deltas would be totals since boot.
+    let mut cpu_usage = 0.0;
+    if system_total(&stats.precpu_stats) > 0 && system_delta > 0.0 && cpu_delta > 0.0 {
+        cpu_usage = (cpu_delta / system_delta) * (online_cpus as f64) * 100.0;
+    }
+
+    let memory = stats.memory_stats.as_ref();
+    let cache = memory
+        .and_then(|m| m.stats.as_ref())
+        .and_then(|s| s.get("inactive_file").or(s.get("total_inactive_file")))
+        .copied()
+        .unwrap_or(0);
+    let memory_usage = memory
+        .and_then(|m| m.usage)
+        .unwrap_or(0)
+        .saturating_sub(cache);
+
+    let (network_rx, network_tx) = stats
+        .networks
+        .as_ref()
+        .map(|n| {
+            n.values().fold((0, 0), |(rx, tx), i| {
+                (rx + i.rx_bytes.unwrap_or(0), tx + i.tx_bytes.unwrap_or(0))
+            })
+        })
+        .unwrap_or((0, 0));
+
+    let (block_read, block_write) = stats
+        .blkio_stats
+        .as_ref()
+        .and_then(|b| b.io_service_bytes_recursive.as_ref())
+        .map(|entries| {
+            entries.iter().fold((0, 0), |(read, write), e| {
+                let value = e.value.unwrap_or(0);
+                match e.op.as_deref().map(|op| op.to_lowercase()).as_deref() {
+                    Some("read") => (read + value, write),
+                    Some("write") => (read, write + value),
+                    _ => (read, write),
+                }
+            })
+        })
+        .unwrap_or((0, 0));
+
+    ContainerStats {
+        id: id.to_string(),
+        name: stats
+            .name
+            .as_deref()
+            .unwrap_or_default()
+            .trim_start_matches('/')
+            .to_string(),
+        cpu_usage,
+        memory_usage,
+        memory_limit: memory.and_then(|m| m.limit).unwrap_or(0),
+        network_rx,
+        network_tx,
+        block_read,
+        block_write,
+        pids: stats
+            .pids_stats
+            .as_ref()
+            .and_then(|p| p.current)
+            .unwrap_or(0),
+    }
+}
+
+// Keeps one streaming stats request open per running container. New
+// containers are picked up on every discovery pass, and entries for
+// containers that stopped are dropped when their stream ends.
+pub fn start_stats_collector(
+    client: &web::Data<DockerClient>,
+    collector: &web::Data<StatsCollector>,
+) {
+    let client = client.clone();
+    let collector = collector.clone();
+
+    tokio::spawn(async move {
+        let mut tasks: HashMap<String, JoinHandle<()>> = HashMap::new();
+
+        loop {
+            let Some(docker) = client.live().await else {
+                tokio::time::sleep(DISCOVERY_INTERVAL).await;
+                continue;
+            };
+            let mut filters = HashMap::new();
+            filters.insert("status".to_string(), vec!["running".to_string()]);
+            let running: Vec<String> = docker
+                .list_containers(Some(ListContainersOptions::<String> {
+                    filters,
+                    ..Default::default()
+                }))
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|c| c.id)
+                .collect();
+
+            tasks.retain(|id, task| {
+                let keep = running.contains(id) && !task.is_finished();
+                if !keep {
+                    task.abort();
+                    collector.stats.lock().unwrap().remove(id);
+                }
+                keep
+            });
+
+            for id in running {
+                if tasks.contains_key(&id) {
+                    continue;
+                }
+                let docker = docker.clone();
+                let collector = collector.clone();
+                let container_id = id.clone();
+                let task = tokio::spawn(async move {
+                    let mut stream = docker.stats(
+                        &container_id,
+                        Some(StatsOptions {
+                            stream: true,
+                            one_shot: false,
+                        }),
+                    );
+                    while let Some(Ok(stats)) = stream.next().await {
+                        let entry = compute_stats(&container_id, &stats);
+                        collector
+                            .stats
+                            .lock()
+                            .unwrap()
+                            .insert(container_id.clone(), entry);
+                    }
+                    collector.stats.lock().unwrap().remove(&container_id);
+                });
+                tasks.insert(id, task);
+            }
+
+            tokio::time::sleep(DISCOVERY_INTERVAL).await;
+        }
+    });
+}
+
+pub async fn list_stats(collector: web::Data<StatsCollector>) -> impl Responder {
+    HttpResponse::Ok().json(collector.snapshot())
+}
+
+pub async fn get_container_stats(
+    id: web::Path<String>,
+    client: web::Data<DockerClient>,
+    collector: web::Data<StatsCollector>,
+) -> impl Responder {
+    let id = id.into_inner();
+    if let Some(stats) = collector.find(&id) {
+        return HttpResponse::Ok().json(stats);
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let options = Some(StatsOptions {
+        stream: false,
+        one_shot: false,
+    });
+
+    match docker.stats(&id, options).next().await {
+        Some(Ok(stats)) => {
+            let full_id = stats.id.clone().unwrap_or(id);
+            HttpResponse::Ok().json(compute_stats(&full_id, &stats))
+        }
+        Some(Err(e)) => {
+            HttpResponse::InternalServerError().json(format!("Failed to fetch stats: {}", e))
+        }
+        None => HttpResponse::NotFound().json("No stats found"),
+    }
+}
+
+pub async fn ws_stats(
+    req: HttpRequest,
+    stream: web::Payload,
+    query: web::Query<StatsQuery>,
+    collector: web::Data<StatsCollector>,
+) -> Result<HttpResponse, Error> {
+    if !verify_token(&query.token) {
+        return Ok(HttpResponse::Unauthorized().body("Invalid token"));
+    }
+
+    let (res, mut session, msg_stream) = actix_ws::handle(&req, stream)?;
+    let mut msg_stream = msg_stream.aggregate_continuations();
+
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(PUSH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let json = serde_json::to_string(&collector.snapshot()).unwrap_or_default();
+                    if session.text(json).await.is_err() {
+                        break;
+                    }
+                }
+
+                Some(msg) = msg_stream.next() => {
+                    match msg {
+                        Ok(AggregatedMessage::Ping(msg)) => {
+                            let _ = session.pong(&msg).await;
+                        }
+                        Ok(AggregatedMessage::Close(_)) | Err(_) => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let _ = session.close(None).await;
+    });
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(value: serde_json::Value) -> ContainerStatsResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn stats_match_docker_stats() {
+        // (response, cpu %, memory, rx, tx, block read, block write, pids)
+        let cases = [
+            (
+                // cgroup v2: two CPUs, a quarter of the system delta.
+                serde_json::json!({
+                    "name": "/web",
+                    "cpu_stats": {
+                        "cpu_usage": { "total_usage": 400 },
+                        "system_cpu_usage": 2000,
+                        "online_cpus": 2
+                    },
+                    "precpu_stats": {
+                        "cpu_usage": { "total_usage": 200 },
+                        "system_cpu_usage": 1200
+                    },
+                    "memory_stats": {
+                        "usage": 10_000,
+                        "limit": 50_000,
+                        "stats": { "inactive_file": 4000 }
+                    },
+                    "networks": {
+                        "eth0": { "rx_bytes": 100, "tx_bytes": 10 },
+                        "eth1": { "rx_bytes": 50, "tx_bytes": 5 }
+                    },
+                    "blkio_stats": {
+                        "io_service_bytes_recursive": [
+                            { "major": 8, "minor": 0, "op": "read", "value": 300 },
+                            { "major": 8, "minor": 0, "op": "write", "value": 70 }
+                        ]
+                    },
+                    "pids_stats": { "current": 7 }
+                }),
+                50.0,
+                6000,
+                150,
+                15,
+                300,
+                70,
+                7,
+            ),
+            (
+                // cgroup v1 names the cache total_inactive_file and ops are
+                // capitalized.
+                serde_json::json!({
+                    "name": "/db",
+                    "memory_stats": {
+                        "usage": 8000,
+                        "stats": { "total_inactive_file": 9000 }
+                    },
+                    "blkio_stats": {
+                        "io_service_bytes_recursive": [
+                            { "major": 8, "minor": 0, "op": "Read", "value": 5 },
+                            { "major": 8, "minor": 0, "op": "Write", "value": 6 },
+                            { "major": 8, "minor": 0, "op": "Total", "value": 11 }
+                        ]
+                    }
+                }),
+                0.0,
+                0,
+                0,
+                0,
+                5,
+                6,
+                0,
+            ),
+            (
+                // The first sample has no previous CPU reading.
+                serde_json::json!({
+                    "cpu_stats": {
+                        "cpu_usage": { "total_usage": 400 },
+                        "system_cpu_usage": 2000,
+                        "online_cpus": 4
+                    },
+                    "precpu_stats": {}
+                }),
+                0.0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            ),
+        ];
+
+        for (response, cpu, memory, rx, tx, read, write, pids) in cases {
+            let stats = compute_stats("abc", &sample(response.clone()));
+            assert!(
+                (stats.cpu_usage - cpu).abs() < 1e-9,
+                "{} cpu {}",
+                response,
+                stats.cpu_usage
+            );
+            assert_eq!(
+                (
+                    stats.memory_usage,
+                    stats.network_rx,
+                    stats.network_tx,
+                    stats.block_read,
+                    stats.block_write,
+                    stats.pids
+                ),
+                (memory, rx, tx, read, write, pids),
+                "{}",
+                response
+            );
+        }
+
+        let stats = compute_stats("abc", &sample(serde_json::json!({ "name": "/web" })));
+        assert_eq!(stats.name, "web");
+        assert_eq!(stats.id, "abc");
+    }
+}
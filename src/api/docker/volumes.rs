@@ -0,0 +1,190 @@
+use actix_web::{web, HttpResponse, Responder};
+use bollard::container::ListContainersOptions;
+use bollard::models::{MountPointTypeEnum, Volume};
+use bollard::volume::{CreateVolumeOptions, ListVolumesOptions, RemoveVolumeOptions};
+use bollard::Docker;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{container_name, is_valid_name, DockerClient};
+
+#[derive(Serialize)]
+pub struct VolumeInfo {
+    pub name: String,
+    pub driver: String,
+    pub mountpoint: String,
+    pub created: Option<String>,
+    pub scope: Option<String>,
+    pub size: Option<i64>,
+    pub labels: HashMap<String, String>,
+    pub options: HashMap<String, String>,
+    pub containers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateVolumeRequest {
+    pub name: String,
+    pub driver: Option<String>,
+    #[serde(default)]
+    pub driver_opts: HashMap<String, String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveVolumeQuery {
+    #[serde(default)]
+    pub force: bool,
+}
+
+async fn volume_users(docker: &Docker) -> HashMap<String, Vec<String>> {
+    let containers = docker
+        .list_containers(Some(ListContainersOptions::<String> {
+            all: true,
+            ..Default::default()
+        }))
+        .await
+        .unwrap_or_default();
+
+    let mut users: HashMap<String, Vec<String>> = HashMap::new();
+    for c in containers {
+        let name = container_name(c.names);
+        for mount in c.mounts.unwrap_or_default() {
+            if mount.typ != Some(MountPointTypeEnum::VOLUME) {
+                continue;
+            }
+            if let Some(volume) = mount.name {
+                users.entry(volume).or_default().push(name.clone());
+            }
+        }
+    }
+    users
+}
+
+// Volume sizes are only reported by the disk usage endpoint, which is
+// comparatively slow, so a failure there just leaves sizes unset.
+async fn volume_sizes(docker: &Docker) -> HashMap<String, i64> {
+    docker
+        .df(None)
+        .await
+        .ok()
+        .and_then(|df| df.volumes)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| v.usage_data.map(|u| (v.name, u.size)))
+        .filter(|(_, size)| *size >= 0)
+        .collect()
+}
+
+fn volume_info(v: Volume, size: Option<i64>, containers: Vec<String>) -> VolumeInfo {
+    VolumeInfo {
+        name: v.name,
+        driver: v.driver,
+        mountpoint: v.mountpoint,
+        created: v.created_at,
+        scope: v.scope.map(|s| s.to_string()),
+        size,
+        labels: v.labels,
+        options: v.options,
+        containers,
+    }
+}
+
+pub async fn list_volumes(client: web::Data<DockerClient>) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let volumes = match docker
+        .list_volumes(None::<ListVolumesOptions<String>>)
+        .await
+    {
+        Ok(v) => v.volumes.unwrap_or_default(),
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Docker error: {}", e)),
+    };
+    let mut users = volume_users(&docker).await;
+    let sizes = volume_sizes(&docker).await;
+
+    let info: Vec<VolumeInfo> = volumes
+        .into_iter()
+        .map(|v| {
+            let size = sizes.get(&v.name).copied();
+            let containers = users.remove(&v.name).unwrap_or_default();
+            volume_info(v, size, containers)
+        })
+        .collect();
+
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn inspect_volume(
+    name: web::Path<String>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let volume = match docker.inspect_volume(&name.into_inner()).await {
+        Ok(v) => v,
+        Err(e) => return HttpResponse::NotFound().json(format!("Volume not found: {}", e)),
+    };
+    let containers = volume_users(&docker)
+        .await
+        .remove(&volume.name)
+        .unwrap_or_default();
+    let size = volume_sizes(&docker).await.get(&volume.name).copied();
+
+    HttpResponse::Ok().json(volume_info(volume, size, containers))
+}
+
+pub async fn create_volume(
+    body: web::Json<CreateVolumeRequest>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    if !is_valid_name(&body.name) {
+        return HttpResponse::BadRequest().json("Invalid volume name");
+    }
+
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+
+    let body = body.into_inner();
+    let options = CreateVolumeOptions {
+        name: body.name,
+        driver: body.driver.unwrap_or_else(|| "local".to_string()),
+        driver_opts: body.driver_opts,
+        labels: body.labels,
+    };
+
+    match docker.create_volume(options).await {
+        Ok(v) => HttpResponse::Ok().json(volume_info(v, None, Vec::new())),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to create volume: {}", e))
+        }
+    }
+}
+
+pub async fn remove_volume(
+    name: web::Path<String>,
+    query: web::Query<RemoveVolumeQuery>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let docker = match client.get() {
+        Ok(d) => d,
+        Err(res) => return res,
+    };
+    let name = name.into_inner();
+
+    let options = RemoveVolumeOptions { force: query.force };
+    match docker.remove_volume(&name, Some(options)).await {
+        Ok(_) => HttpResponse::Ok().json(format!("Volume {} removed", name)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to remove volume: {}", e))
+        }
+    }
+}
@@ -1,12 +1,30 @@
 use actix_web::{web, HttpResponse, Responder};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::io::Write;
+use std::net::IpAddr;
+use std::os::unix::fs::OpenOptionsExt;
 use std::process::Command;
 
+pub mod blocked;
+pub mod firewalld;
+pub mod nftables;
+pub mod policy;
+pub mod rollback;
+pub mod ruleset;
+pub mod ufw;
+
+use crate::api::alerts::AlertLog;
+use policy::{AppProfile, Policy};
+use rollback::{PendingChange, SafeApply, SafeQuery};
+
 #[derive(Serialize, Deserialize)]
 pub struct FirewallStatus {
+    pub backend: String,
     pub active: bool,
-    pub rules: Vec<String>,
+    pub rules: Vec<FirewallRule>,
     pub installed: bool,
+    pub policy: Option<Policy>,
+    pub pending: Option<PendingChange>,
 }
 
 #[derive(Deserialize)]
@@ -14,62 +32,407 @@ pub struct FirewallAction {
     pub action: String, 
 }
 
-#[derive(Deserialize)]
-pub struct FirewallRuleData {
-    pub rule: String, 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+    Reject,
+    Limit,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    In,
+    Out,
+    Forward,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    #[default]
+    Any,
+    Tcp,
+    Udp,
+}
+
+// A single ufw rule. `source` and `destination` are addresses or CIDRs and
+// mean "any" when unset; ports use ufw syntax (`22`, `80,443`, `6000:6010`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FirewallRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<u32>,
+    pub action: RuleAction,
+    #[serde(default)]
+    pub direction: Direction,
+    #[serde(default)]
+    pub protocol: Protocol,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "port_string"
+    )]
+    pub port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "port_string"
+    )]
+    pub source_port: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub v6: bool,
+}
+
+// Hand written rulesets often give a single port as a number.
+fn port_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Port {
+        Number(u16),
+        Text(String),
+    }
+
+    Ok(
+        Option::<Port>::deserialize(deserializer)?.map(|port| match port {
+            Port::Number(n) => n.to_string(),
+            Port::Text(s) => s,
+        }),
+    )
+}
+
+impl RuleAction {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "allow" => Some(RuleAction::Allow),
+            "deny" => Some(RuleAction::Deny),
+            "reject" => Some(RuleAction::Reject),
+            "limit" => Some(RuleAction::Limit),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleAction::Allow => "allow",
+            RuleAction::Deny => "deny",
+            RuleAction::Reject => "reject",
+            RuleAction::Limit => "limit",
+        }
+    }
 }
 
-pub async fn get_status() -> impl Responder {
-    
-    let check = Command::new("which").arg("ufw").output();
-    let installed = match check {
-        Ok(o) => o.status.success(),
-        Err(_) => false,
+impl Protocol {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "tcp" => Some(Protocol::Tcp),
+            "udp" => Some(Protocol::Udp),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Any => "any",
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+        }
+    }
+}
+
+pub fn is_valid_address(address: &str) -> bool {
+    let (ip, prefix) = match address.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (address, None),
+    };
+    let Ok(ip) = ip.parse::<IpAddr>() else {
+        return false;
     };
+    let max = if ip.is_ipv4() { 32 } else { 128 };
+    prefix.is_none_or(|p| p.parse::<u8>().is_ok_and(|p| p <= max))
+}
+
+// ufw accepts up to 15 comma separated ports, where a range counts as two.
+pub fn is_valid_port(port: &str) -> bool {
+    let parse = |p: &str| p.parse::<u16>().ok().filter(|p| *p > 0);
+    let mut count = 0;
+    for item in port.split(',') {
+        match item.split_once(':') {
+            Some((start, end)) => match (parse(start), parse(end)) {
+                (Some(start), Some(end)) if start < end => count += 2,
+                _ => return false,
+            },
+            None if parse(item).is_some() => count += 1,
+            None => return false,
+        }
+    }
+    count <= 15
+}
+
+pub fn is_port_list(port: &str) -> bool {
+    port.contains([',', ':'])
+}
+
+fn is_valid_interface(interface: &str) -> bool {
+    !interface.is_empty()
+        && interface.len() <= 15
+        && interface
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c))
+}
+
+pub fn is_valid_app(app: &str) -> bool {
+    !app.trim().is_empty()
+        && app
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " ._-+".contains(c))
+}
+
+pub fn is_valid_comment(comment: &str) -> bool {
+    comment.len() <= 128
+        && !comment
+            .chars()
+            .any(|c| c.is_control() || c == '\'' || c == '"')
+}
+
+impl FirewallRule {
+    pub fn validate(&self) -> Result<(), String> {
+        for address in [&self.source, &self.destination].into_iter().flatten() {
+            if !is_valid_address(address) {
+                return Err(format!("Invalid address: {}", address));
+            }
+        }
+        for port in [&self.port, &self.source_port].into_iter().flatten() {
+            if !is_valid_port(port) {
+                return Err(format!("Invalid port: {}", port));
+            }
+            if is_port_list(port) && self.protocol == Protocol::Any {
+                return Err("Port lists and ranges require tcp or udp".to_string());
+            }
+        }
+        if let Some(app) = &self.app {
+            if !is_valid_app(app) {
+                return Err("Invalid application name".to_string());
+            }
+            if self.port.is_some() || self.protocol != Protocol::Any {
+                return Err("An application rule cannot also set a port or protocol".to_string());
+            }
+        }
+        if let Some(interface) = self.interface.as_deref().filter(|i| !is_valid_interface(i)) {
+            return Err(format!("Invalid interface name: {}", interface));
+        }
+        if !self.comment.as_deref().is_none_or(is_valid_comment) {
+            return Err("Comments cannot contain quotes or control characters".to_string());
+        }
+        Ok(())
+    }
+}
+
+// The ruleset as it was before a change. `ruleset` is in whatever form the
+// backend can load back, and is only meaningful to the backend that took it.
+pub struct Snapshot {
+    pub active: bool,
+    pub ruleset: String,
+}
+
+pub trait FirewallBackend {
+    fn name(&self) -> &'static str;
+
+    fn binary(&self) -> &'static str {
+        self.name()
+    }
+
+    fn is_installed(&self) -> bool {
+        Command::new("which")
+            .arg(self.binary())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn is_active(&self) -> bool;
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String>;
+
+    fn rules(&self) -> Result<Vec<FirewallRule>, String>;
+
+    // Rejects valid rules the backend has no way to express.
+    fn supports(&self, _rule: &FirewallRule) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn add_rule(&self, rule: &FirewallRule) -> Result<(), String>;
 
-    if !installed {
+    // Adds the rule ahead of the existing ones so a broader rule cannot
+    // shadow it. Backends that order rules by action just add it.
+    fn insert_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        self.add_rule(rule)
+    }
+
+    fn delete_rule(&self, rule: &FirewallRule) -> Result<(), String>;
+
+    // Numbers are the ones reported by `rules()` and only stay valid until
+    // the next change.
+    fn delete_rule_number(&self, number: u32) -> Result<(), String>;
+
+    fn snapshot(&self) -> Result<Snapshot, String>;
+
+    // Puts back the rules, default policies and enabled state of a snapshot.
+    fn restore(&self, snapshot: &Snapshot) -> Result<(), String>;
+
+    fn policy(&self) -> Result<Policy, String>;
+
+    fn set_policy(&self, direction: Direction, action: RuleAction) -> Result<(), String>;
+
+    fn logging_levels(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn set_logging(&self, _level: &str) -> Result<(), String> {
+        Err(format!("{} has no logging level", self.name()))
+    }
+
+    fn profiles(&self) -> Result<Vec<AppProfile>, String> {
+        Ok(Vec::new())
+    }
+
+    fn save_profile(&self, _profile: &AppProfile) -> Result<(), String> {
+        Err(format!("{} has no application profiles", self.name()))
+    }
+
+    fn delete_profile(&self, _name: &str) -> Result<(), String> {
+        Err(format!("{} has no application profiles", self.name()))
+    }
+}
+
+// Every firewall tool is run through here: the arguments are handed over as
+// a vector and never pass through a shell.
+fn sudo_command(program: &str, args: &[String]) -> Command {
+    let mut command = Command::new("sudo");
+    command.arg(program).args(args);
+    command
+}
+
+pub fn run(program: &str, args: &[String]) -> Result<String, String> {
+    let output = sudo_command(program, args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Err(if stderr.is_empty() { stdout } else { stderr })
+    }
+}
+
+// Writes a root owned file through `install`, for files WADM cannot write
+// itself.
+pub fn install_file(path: &str, content: &str, mode: &str) -> Result<(), String> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let temp = std::env::temp_dir().join(format!("wadm-{}-{:016x}", name, rand::random::<u64>()));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&temp)
+        .and_then(|mut f| f.write_all(content.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    let result = run(
+        "install",
+        &[
+            "-m".to_string(),
+            mode.to_string(),
+            temp.to_string_lossy().to_string(),
+            path.to_string(),
+        ],
+    );
+    let _ = std::fs::remove_file(&temp);
+    result.map(|_| ())
+}
+
+fn backends() -> Vec<Box<dyn FirewallBackend>> {
+    vec![
+        Box::new(ufw::Ufw),
+        Box::new(firewalld::Firewalld),
+        Box::new(nftables::Nftables),
+    ]
+}
+
+pub fn backend_named(name: &str) -> Option<Box<dyn FirewallBackend>> {
+    backends().into_iter().find(|b| b.name() == name)
+}
+
+// A running firewalld owns the ruleset even if ufw is also installed, so it
+// wins. Otherwise ufw, firewalld and raw nftables are tried in that order,
+// falling back to ufw so status can report it missing.
+pub fn detect() -> Box<dyn FirewallBackend> {
+    let firewalld = firewalld::Firewalld;
+    if firewalld.is_installed() && firewalld.is_active() {
+        return Box::new(firewalld);
+    }
+
+    let backends = backends();
+    let index = backends.iter().position(|b| b.is_installed()).unwrap_or(0);
+    backends
+        .into_iter()
+        .nth(index)
+        .expect("backend index is in range")
+}
+
+pub async fn get_status(safe: web::Data<SafeApply>) -> impl Responder {
+    let backend = detect();
+    if !backend.is_installed() {
         return HttpResponse::Ok().json(FirewallStatus {
+            backend: backend.name().to_string(),
             active: false,
             rules: vec![],
             installed: false,
+            policy: None,
+            pending: None,
         });
     }
 
-    let output = Command::new("sudo")
-        .arg("ufw")
-        .arg("status")
-        .arg("numbered")
-        .output();
-
-    match output {
-        Ok(o) => {
-            let stdout = String::from_utf8_lossy(&o.stdout);
-            let active = stdout.contains("Status: active");
-
-            let rules: Vec<String> = stdout
-                .lines()
-                .filter(|line| line.contains("[")) 
-                .map(|line| line.to_string())
-                .collect();
-
-            HttpResponse::Ok().json(FirewallStatus {
-                active,
-                rules,
-                installed: true,
-            })
-        }
-        Err(_) => HttpResponse::InternalServerError().json("Failed to execute ufw command"),
+    match backend.rules() {
+        Ok(rules) => HttpResponse::Ok().json(FirewallStatus {
+            backend: backend.name().to_string(),
+            active: backend.is_active(),
+            rules,
+            installed: true,
+            policy: backend.policy().ok(),
+            pending: safe.pending(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().json(format!(
+            "Failed to read {} rules: {}",
+            backend.name(),
+            e
+        )),
     }
 }
 
-pub async fn install_ufw() -> impl Responder {
+// Installs the firewall each distribution family ships by default.
+pub async fn install_firewall() -> impl Responder {
     use crate::api::pkgmgr::{detect_manager, ManagerType};
 
     let manager = detect_manager();
-    let (cmd, args) = match manager {
-        ManagerType::Apt => ("apt-get", vec!["install", "-y", "ufw"]),
-        ManagerType::Dnf => ("dnf", vec!["install", "-y", "ufw"]),
-        ManagerType::Pacman => ("pacman", vec!["-S", "--noconfirm", "ufw"]),
+    let (cmd, args, package) = match manager {
+        ManagerType::Apt => ("apt-get", vec!["install", "-y", "ufw"], "ufw"),
+        ManagerType::Dnf => ("dnf", vec!["install", "-y", "firewalld"], "firewalld"),
+        ManagerType::Pacman => ("pacman", vec!["-S", "--noconfirm", "ufw"], "ufw"),
         ManagerType::Unknown => {
             return HttpResponse::InternalServerError().json("Unsupported package manager")
         }
@@ -80,11 +443,11 @@ pub async fn install_ufw() -> impl Responder {
     match output {
         Ok(o) => {
             if o.status.success() {
-                HttpResponse::Ok().json("UFW installed successfully")
+                HttpResponse::Ok().json(format!("{} installed successfully", package))
             } else {
                 let stderr = String::from_utf8_lossy(&o.stderr);
                 HttpResponse::InternalServerError()
-                    .json(format!("Failed to install UFW: {}", stderr))
+                    .json(format!("Failed to install {}: {}", package, stderr))
             }
         }
         Err(e) => HttpResponse::InternalServerError()
@@ -92,90 +455,172 @@ pub async fn install_ufw() -> impl Responder {
     }
 }
 
-pub async fn set_status(body: web::Json<FirewallAction>) -> impl Responder {
-    let arg = if body.action == "enable" {
-        "enable"
-    } else {
-        "disable"
-    };
+pub async fn set_status(
+    body: web::Json<FirewallAction>,
+    query: web::Query<SafeQuery>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let enable = body.action == "enable";
+    let risk = enable.then(rollback::enable_risk);
 
-    
-    let status = if arg == "enable" {
-        Command::new("sh")
-            .arg("-c")
-            .arg("yes | sudo ufw enable")
-            .status()
-    } else {
-        Command::new("sudo").arg("ufw").arg("disable").status()
+    let backend = detect();
+    let change = || {
+        let arg = if enable { "enable" } else { "disable" };
+        backend
+            .set_enabled(enable)
+            .map(|_| format!("Firewall {}d", arg))
+            .map_err(|e| format!("Command failed: {}", e))
     };
-
-    match status {
-        Ok(s) => {
-            if s.success() {
-                HttpResponse::Ok().json(format!("Firewall {}d", arg))
-            } else {
-                HttpResponse::InternalServerError().json("Command failed")
-            }
-        }
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
-    }
+    rollback::apply(&safe, &alerts, &query, backend.as_ref(), risk, change)
 }
 
-pub async fn add_rule(body: web::Json<FirewallRuleData>) -> impl Responder {
-    
-    
-    
+pub async fn add_rule(
+    body: web::Json<FirewallRule>,
+    query: web::Query<SafeQuery>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let backend = detect();
+    if let Err(e) = body.validate().and_then(|_| backend.supports(&body)) {
+        return HttpResponse::BadRequest().json(e);
+    }
 
-    
-    let args: Vec<&str> = body.rule.split_whitespace().collect();
+    let risk = rollback::add_risk(&body);
+    let change = || {
+        backend
+            .add_rule(&body)
+            .map(|_| "Rule added".to_string())
+            .map_err(|e| format!("Failed to add rule: {}", e))
+    };
+    rollback::apply(&safe, &alerts, &query, backend.as_ref(), risk, change)
+}
 
-    if args.is_empty() {
-        return HttpResponse::BadRequest().json("Rule cannot be empty");
+pub async fn delete_rule(
+    body: web::Json<FirewallRule>,
+    query: web::Query<SafeQuery>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let backend = detect();
+    if let Err(e) = body.validate().and_then(|_| backend.supports(&body)) {
+        return HttpResponse::BadRequest().json(e);
     }
 
-    let status = Command::new("sudo").arg("ufw").args(args).status();
+    let risk = rollback::delete_risk(&body);
+    let change = || {
+        backend
+            .delete_rule(&body)
+            .map(|_| "Rule deleted".to_string())
+            .map_err(|e| format!("Failed to delete rule: {}", e))
+    };
+    rollback::apply(&safe, &alerts, &query, backend.as_ref(), risk, change)
+}
 
-    match status {
-        Ok(s) => {
-            if s.success() {
-                HttpResponse::Ok().json("Rule added")
-            } else {
-                HttpResponse::InternalServerError().json("Failed to add rule")
-            }
-        }
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+pub async fn delete_rule_number(
+    number: web::Path<u32>,
+    query: web::Query<SafeQuery>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let number = number.into_inner();
+    if number == 0 {
+        return HttpResponse::BadRequest().json("Rule numbers start at 1");
     }
+
+    let backend = detect();
+    let risk = backend
+        .rules()
+        .ok()
+        .and_then(|rules| rules.into_iter().find(|r| r.number == Some(number)))
+        .and_then(|rule| rollback::delete_risk(&rule));
+    let change = || {
+        backend
+            .delete_rule_number(number)
+            .map(|_| format!("Rule {} deleted", number))
+            .map_err(|e| format!("Failed to delete rule: {}", e))
+    };
+    rollback::apply(&safe, &alerts, &query, backend.as_ref(), risk, change)
 }
 
-pub async fn delete_rule(body: web::Json<FirewallRuleData>) -> impl Responder {
-    
-    
-    
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let args: Vec<&str> = body.rule.split_whitespace().collect();
-    if args.is_empty() {
-        return HttpResponse::BadRequest().json("Rule cannot be empty");
+    const PAYLOADS: &[&str] = &[
+        "22; reboot",
+        "22 && id",
+        "$(reboot)",
+        "`reboot`",
+        "22 | sh",
+        "eth0\nreboot",
+        "x' ; rm -rf / #",
+    ];
+
+    fn rule() -> FirewallRule {
+        FirewallRule {
+            number: None,
+            action: RuleAction::Allow,
+            direction: Direction::In,
+            protocol: Protocol::Tcp,
+            port: Some("22".to_string()),
+            source: None,
+            source_port: None,
+            destination: None,
+            interface: None,
+            app: None,
+            comment: None,
+            v6: false,
+        }
     }
 
-    let mut command = Command::new("sudo");
-    command.arg("ufw").arg("delete");
-    command.args(args);
-
-    
-    
-    let status = Command::new("sh")
-        .arg("-c")
-        .arg(format!("yes | sudo ufw delete {}", body.rule))
-        .status();
-
-    match status {
-        Ok(s) => {
-            if s.success() {
-                HttpResponse::Ok().json("Rule deleted")
-            } else {
-                HttpResponse::InternalServerError().json("Failed to delete rule")
+    #[test]
+    fn shell_metacharacters_are_rejected_in_every_field() {
+        for payload in PAYLOADS {
+            let payload = Some(payload.to_string());
+            let cases = [
+                FirewallRule {
+                    port: payload.clone(),
+                    ..rule()
+                },
+                FirewallRule {
+                    source: payload.clone(),
+                    ..rule()
+                },
+                FirewallRule {
+                    source_port: payload.clone(),
+                    ..rule()
+                },
+                FirewallRule {
+                    destination: payload.clone(),
+                    ..rule()
+                },
+                FirewallRule {
+                    interface: payload.clone(),
+                    ..rule()
+                },
+                FirewallRule {
+                    port: None,
+                    protocol: Protocol::Any,
+                    app: payload.clone(),
+                    ..rule()
+                },
+            ];
+            for case in cases {
+                assert!(case.validate().is_err(), "accepted {:?}", case);
             }
         }
-        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+
+    #[test]
+    fn tools_are_invoked_without_a_shell() {
+        let comment = "a comment; with `$(metacharacters)`".to_string();
+        let args = vec!["comment".to_string(), comment.clone()];
+
+        let command = sudo_command("ufw", &args);
+        let argv: Vec<_> = command.get_args().collect();
+
+        assert_eq!(command.get_program(), "sudo");
+        assert_eq!(argv, ["ufw", "comment", comment.as_str()]);
     }
 }
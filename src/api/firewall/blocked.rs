@@ -0,0 +1,309 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+use super::rollback::{self, SafeApply, SafeQuery};
+use super::{detect, is_valid_address, run, Direction, FirewallRule, Protocol, RuleAction};
+use crate::api::alerts::AlertLog;
+
+const LOG_FILE: &str = "/var/log/ufw.log";
+const MARKERS: [&str; 2] = ["[UFW BLOCK]", "[UFW LIMIT BLOCK]"];
+const DEFAULT_LINES: usize = 2000;
+const MAX_LINES: usize = 20000;
+const RECENT: usize = 200;
+const TOP: usize = 10;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct BlockedEvent {
+    pub time: String,
+    pub source: String,
+    pub destination: Option<String>,
+    pub port: Option<u16>,
+    pub protocol: Option<String>,
+    pub interface: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BlockedCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+pub struct BlockedReport {
+    // Where the events were read from: "journal" or the log file path.
+    pub origin: String,
+    pub total: usize,
+    pub events: Vec<BlockedEvent>,
+    pub top_sources: Vec<BlockedCount>,
+    pub top_ports: Vec<BlockedCount>,
+}
+
+#[derive(Deserialize)]
+pub struct BlockedQuery {
+    pub lines: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct DenySource {
+    pub source: String,
+}
+
+// Parses a kernel log line written by ufw's logging, e.g.
+// `2026-10-18T10:00:00+0000 host kernel: [UFW BLOCK] IN=eth0 OUT= MAC=...
+// SRC=203.0.113.7 DST=192.0.2.10 LEN=44 ... PROTO=TCP SPT=51234 DPT=22 ...`
+// from `journalctl -o short-iso` or the classic `Oct 18 10:00:00 host ...`
+// syslog format.
+fn parse_event(line: &str) -> Option<BlockedEvent> {
+    let marker = MARKERS.iter().find_map(|m| line.find(m).map(|i| (i, m)));
+    let (start, marker) = marker?;
+
+    let mut words = line.split_whitespace();
+    let first = words.next()?;
+    let time = if first.starts_with(|c: char| c.is_ascii_digit()) {
+        first.to_string()
+    } else {
+        format!("{} {} {}", first, words.next()?, words.next()?)
+    };
+
+    let fields: HashMap<&str, &str> = line[start + marker.len()..]
+        .split_whitespace()
+        .filter_map(|w| w.split_once('='))
+        .collect();
+    let field = |key: &str| {
+        fields
+            .get(key)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    };
+
+    Some(BlockedEvent {
+        time,
+        source: field("SRC")?,
+        destination: field("DST"),
+        port: fields.get("DPT").and_then(|p| p.parse().ok()),
+        protocol: field("PROTO"),
+        interface: field("IN"),
+    })
+}
+
+// The kernel ring buffer in the journal is preferred; hosts that only log to
+// syslog have the same lines in ufw.log. Both are usually readable by root
+// and the adm group only, so they are read through sudo like the rest of the
+// firewall.
+fn read_lines(lines: usize) -> Result<(String, Vec<String>), String> {
+    let mut args = [
+        "-k",
+        "-o",
+        "short-iso",
+        "--no-pager",
+        "--grep",
+        "UFW (LIMIT )?BLOCK",
+    ]
+    .map(String::from)
+    .to_vec();
+    args.push(format!("--lines={}", lines));
+    let journal = match run("journalctl", &args) {
+        Ok(output) => Ok(output),
+        // journalctl exits non-zero when nothing matches the pattern.
+        Err(e) if e.is_empty() || e.contains("No entries") => Ok(String::new()),
+        Err(e) => Err(e),
+    };
+    if let Ok(output) = &journal {
+        if output.lines().any(|l| parse_event(l).is_some()) {
+            return Ok((
+                "journal".to_string(),
+                output.lines().map(String::from).collect(),
+            ));
+        }
+    }
+
+    if Path::new(LOG_FILE).exists() {
+        let args = ["-n".to_string(), lines.to_string(), LOG_FILE.to_string()];
+        return match run("tail", &args) {
+            Ok(output) => Ok((
+                LOG_FILE.to_string(),
+                output.lines().map(String::from).collect(),
+            )),
+            Err(e) => Err(format!("Failed to read {}: {}", LOG_FILE, e)),
+        };
+    }
+
+    // Nothing has been blocked and logged yet.
+    journal
+        .map(|_| ("journal".to_string(), Vec::new()))
+        .map_err(|e| format!("Failed to read the journal: {}", e))
+}
+
+fn top<K: ToString>(values: impl Iterator<Item = K>) -> Vec<BlockedCount> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value.to_string()).or_default() += 1;
+    }
+    let mut counts: Vec<BlockedCount> = counts
+        .into_iter()
+        .map(|(value, count)| BlockedCount { value, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts.truncate(TOP);
+    counts
+}
+
+fn report(origin: String, lines: &[String]) -> BlockedReport {
+    let events: Vec<BlockedEvent> = lines.iter().filter_map(|l| parse_event(l)).collect();
+    let top_sources = top(events.iter().map(|e| &e.source));
+    let top_ports = top(events.iter().filter_map(|e| {
+        e.port.map(|port| match &e.protocol {
+            Some(protocol) => format!("{}/{}", port, protocol.to_lowercase()),
+            None => port.to_string(),
+        })
+    }));
+
+    let total = events.len();
+    let events = events.into_iter().rev().take(RECENT).collect();
+    BlockedReport {
+        origin,
+        total,
+        events,
+        top_sources,
+        top_ports,
+    }
+}
+
+pub async fn list_blocked(query: web::Query<BlockedQuery>) -> impl Responder {
+    let lines = query.lines.unwrap_or(DEFAULT_LINES).clamp(1, MAX_LINES);
+    match read_lines(lines) {
+        Ok((origin, lines)) => HttpResponse::Ok().json(report(origin, &lines)),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
+
+// Addresses the request may have come from. Behind a reverse proxy the peer
+// is the proxy, so the forwarded address is included too.
+fn client_addresses(req: &HttpRequest) -> Vec<IpAddr> {
+    let info = req.connection_info();
+    [
+        req.peer_addr().map(|a| a.ip().to_string()),
+        info.realip_remote_addr().map(String::from),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(|a| a.parse::<IpAddr>().ok())
+    .map(|ip| ip.to_canonical())
+    .collect()
+}
+
+pub async fn deny_source(
+    req: HttpRequest,
+    body: web::Json<DenySource>,
+    query: web::Query<SafeQuery>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let source = body.source.trim();
+    if !is_valid_address(source) {
+        return HttpResponse::BadRequest().json(format!("Invalid address: {}", source));
+    }
+
+    let backend = detect();
+    let mut rule = FirewallRule {
+        number: None,
+        action: RuleAction::Deny,
+        direction: Direction::In,
+        protocol: Protocol::Any,
+        port: None,
+        source: Some(source.to_string()),
+        source_port: None,
+        destination: None,
+        interface: None,
+        app: None,
+        comment: Some("Blocked from the WADM traffic log".to_string()),
+        v6: false,
+    };
+    if backend.supports(&rule).is_err() {
+        rule.comment = None;
+    }
+    if let Err(e) = rule.validate().and_then(|_| backend.supports(&rule)) {
+        return HttpResponse::BadRequest().json(e);
+    }
+
+    // Ranges are guarded as well, since they may contain the admin's address.
+    let own = source
+        .parse::<IpAddr>()
+        .is_ok_and(|ip| client_addresses(&req).contains(&ip.to_canonical()));
+    let risk = if own {
+        Some(format!("{} is the address you are connected from", source))
+    } else if source.contains('/') {
+        Some(format!(
+            "{} may include the address you are connected from",
+            source
+        ))
+    } else {
+        None
+    };
+
+    let change = || {
+        backend
+            .insert_rule(&rule)
+            .map(|_| format!("Denied traffic from {}", source))
+            .map_err(|e| format!("Failed to deny {}: {}", source, e))
+    };
+    rollback::apply(&safe, &alerts, &query, backend.as_ref(), risk, change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_entries_are_parsed_from_journal_and_syslog() {
+        let journal = "2026-10-18T10:00:00+0000 web1 kernel: [UFW BLOCK] IN=eth0 OUT= \
+            MAC=52:54:00:12:34:56 SRC=203.0.113.7 DST=192.0.2.10 LEN=44 TOS=0x00 PREC=0x00 \
+            TTL=241 ID=54321 PROTO=TCP SPT=51234 DPT=22 WINDOW=1024 RES=0x00 SYN URGP=0";
+        assert_eq!(
+            parse_event(journal),
+            Some(BlockedEvent {
+                time: "2026-10-18T10:00:00+0000".to_string(),
+                source: "203.0.113.7".to_string(),
+                destination: Some("192.0.2.10".to_string()),
+                port: Some(22),
+                protocol: Some("TCP".to_string()),
+                interface: Some("eth0".to_string()),
+            })
+        );
+
+        let syslog = "Oct 18 10:00:01 web1 kernel: [12345.678901] [UFW BLOCK] IN=eth0 OUT= \
+            SRC=2001:db8::1 DST=2001:db8::2 LEN=72 PROTO=ICMPv6 TYPE=128 CODE=0";
+        let event = parse_event(syslog).unwrap();
+        assert_eq!(event.time, "Oct 18 10:00:01");
+        assert_eq!(event.source, "2001:db8::1");
+        assert_eq!(event.port, None);
+
+        assert!(
+            parse_event("Oct 18 10:00:02 web1 kernel: [UFW ALLOW] IN=eth0 SRC=1.2.3.4").is_none()
+        );
+    }
+
+    #[test]
+    fn top_lists_are_counted_over_all_events() {
+        let lines: Vec<String> = ["203.0.113.7", "198.51.100.2", "203.0.113.7"]
+            .iter()
+            .map(|src| {
+                format!(
+                    "Oct 18 10:00:00 web1 kernel: [UFW BLOCK] IN=eth0 SRC={} PROTO=TCP DPT=22",
+                    src
+                )
+            })
+            .collect();
+
+        let report = report(LOG_FILE.to_string(), &lines);
+        assert_eq!(report.total, 3);
+        assert_eq!(report.top_sources[0].value, "203.0.113.7");
+        assert_eq!(report.top_sources[0].count, 2);
+        assert_eq!(report.top_ports[0].value, "22/tcp");
+        assert_eq!(report.top_ports[0].count, 3);
+        assert_eq!(report.events[0].source, "203.0.113.7");
+    }
+}
@@ -0,0 +1,784 @@
+use actix_web::{HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use super::policy::{parse_profile_port, AppProfile, Policy};
+use super::{
+    install_file, run, Direction, FirewallBackend, FirewallRule, Protocol, RuleAction, Snapshot,
+};
+
+const LIMIT_RATE: &str = "6/m";
+const LOGGING_LEVELS: [&str; 5] = ["off", "all", "unicast", "broadcast", "multicast"];
+// Services shipped with firewalld, and the ones defined locally, which take
+// precedence.
+const SYSTEM_SERVICES: &str = "/usr/lib/firewalld/services";
+const CUSTOM_SERVICES: &str = "/etc/firewalld/services";
+
+pub struct Firewalld;
+
+#[derive(Serialize, Default)]
+pub struct Zone {
+    pub name: String,
+    pub default: bool,
+    pub active: bool,
+    pub target: String,
+    pub interfaces: Vec<String>,
+    pub sources: Vec<String>,
+    pub services: Vec<String>,
+    pub ports: Vec<String>,
+    pub rich_rules: Vec<String>,
+}
+
+// How a rule is stored in the zone. Plain allows of a service or port use
+// firewalld's own lists so they show up where admins expect them; anything
+// with addresses or another action becomes a rich rule.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+enum Entry {
+    Service(String),
+    Port(String),
+    Rich(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct ZoneState {
+    zone: String,
+    target: String,
+    entries: Vec<Entry>,
+}
+
+fn firewall_cmd(args: &[String]) -> Result<String, String> {
+    run("firewall-cmd", args)
+}
+
+fn default_zone() -> Result<String, String> {
+    firewall_cmd(&["--get-default-zone".to_string()])
+}
+
+fn list(zone: &str, what: &str) -> Result<String, String> {
+    firewall_cmd(&[format!("--zone={}", zone), format!("--list-{}", what)])
+}
+
+// The target only exists in the permanent configuration, so setting it takes
+// a reload.
+fn get_target(zone: &str) -> Result<String, String> {
+    firewall_cmd(&[
+        "--permanent".to_string(),
+        format!("--zone={}", zone),
+        "--get-target".to_string(),
+    ])
+}
+
+fn set_target(zone: &str, target: &str) -> Result<(), String> {
+    firewall_cmd(&[
+        "--permanent".to_string(),
+        format!("--zone={}", zone),
+        format!("--set-target={}", target),
+    ])?;
+    reload()
+}
+
+fn reload() -> Result<(), String> {
+    firewall_cmd(&["--reload".to_string()]).map(|_| ())
+}
+
+// Changes are made to both the runtime and the permanent configuration so
+// they take effect now and survive a reload.
+fn apply(zone: &str, operation: &str, entry: &Entry) -> Result<(), String> {
+    let (kind, value) = match entry {
+        Entry::Service(s) => ("service", s),
+        Entry::Port(p) => ("port", p),
+        Entry::Rich(r) => ("rich-rule", r),
+    };
+    let change = format!("--{}-{}={}", operation, kind, value);
+    let zone = format!("--zone={}", zone);
+
+    firewall_cmd(&[zone.clone(), change.clone()])?;
+    firewall_cmd(&["--permanent".to_string(), zone, change]).map(|_| ())
+}
+
+fn protocol_name(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Udp => "udp",
+        _ => "tcp",
+    }
+}
+
+fn port_rule(port: &str) -> Option<FirewallRule> {
+    let (port, protocol) = port.split_once('/')?;
+    Some(FirewallRule {
+        port: Some(port.replace('-', ":")),
+        protocol: Protocol::parse(protocol)?,
+        ..allow_rule()
+    })
+}
+
+fn allow_rule() -> FirewallRule {
+    FirewallRule {
+        number: None,
+        action: RuleAction::Allow,
+        direction: Direction::In,
+        protocol: Protocol::Any,
+        port: None,
+        source: None,
+        source_port: None,
+        destination: None,
+        interface: None,
+        app: None,
+        comment: None,
+        v6: false,
+    }
+}
+
+// Splits a rich rule into `key` and `key="value"` tokens, keeping quoted
+// values that contain spaces together.
+fn tokenize(rule: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in rule.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_rich_rule(line: &str) -> Option<FirewallRule> {
+    let mut rule = allow_rule();
+    let mut action = None;
+    let mut context = String::new();
+
+    for token in tokenize(line) {
+        match token.split_once('=') {
+            Some((key, value)) => {
+                let value = value.trim_matches('"').to_string();
+                match (context.as_str(), key) {
+                    ("rule", "family") => rule.v6 = value == "ipv6",
+                    ("source", "address") => rule.source = Some(value),
+                    ("destination", "address") => rule.destination = Some(value),
+                    ("port", "port") => rule.port = Some(value.replace('-', ":")),
+                    ("source-port", "port") => rule.source_port = Some(value.replace('-', ":")),
+                    ("port", "protocol") | ("source-port", "protocol") | ("protocol", "value") => {
+                        rule.protocol = Protocol::parse(&value)?
+                    }
+                    ("service", "name") => rule.app = Some(value),
+                    _ => {}
+                }
+            }
+            None => match token.as_str() {
+                "accept" => action = Some(RuleAction::Allow),
+                "drop" => action = Some(RuleAction::Deny),
+                "reject" => action = Some(RuleAction::Reject),
+                "limit" if action == Some(RuleAction::Allow) => action = Some(RuleAction::Limit),
+                // Negated matches cannot be expressed in the rule model.
+                "NOT" | "not" => return None,
+                _ => context = token,
+            },
+        }
+    }
+
+    rule.action = action?;
+    Some(rule)
+}
+
+fn rich_rule(rule: &FirewallRule) -> String {
+    let mut parts = vec!["rule".to_string()];
+
+    let family = [&rule.source, &rule.destination]
+        .into_iter()
+        .flatten()
+        .next()
+        .map(|a| if a.contains(':') { "ipv6" } else { "ipv4" });
+    if let Some(family) = family {
+        parts.push(format!("family=\"{}\"", family));
+    }
+    if let Some(source) = &rule.source {
+        parts.push(format!("source address=\"{}\"", source));
+    }
+    if let Some(destination) = &rule.destination {
+        parts.push(format!("destination address=\"{}\"", destination));
+    }
+
+    let protocol = protocol_name(rule.protocol);
+    if let Some(app) = &rule.app {
+        parts.push(format!("service name=\"{}\"", app));
+    } else if let Some(port) = &rule.port {
+        parts.push(format!(
+            "port port=\"{}\" protocol=\"{}\"",
+            port.replace(':', "-"),
+            protocol
+        ));
+    }
+    if let Some(port) = &rule.source_port {
+        parts.push(format!(
+            "source-port port=\"{}\" protocol=\"{}\"",
+            port.replace(':', "-"),
+            protocol
+        ));
+    }
+    if rule.port.is_none() && rule.source_port.is_none() && rule.protocol != Protocol::Any {
+        parts.push(format!("protocol value=\"{}\"", protocol));
+    }
+
+    parts.push(match rule.action {
+        RuleAction::Allow => "accept".to_string(),
+        RuleAction::Deny => "drop".to_string(),
+        RuleAction::Reject => "reject".to_string(),
+        RuleAction::Limit => format!("accept limit value=\"{}\"", LIMIT_RATE),
+    });
+    parts.join(" ")
+}
+
+fn entry_for(rule: &FirewallRule) -> Entry {
+    let plain = rule.action == RuleAction::Allow
+        && rule.source.is_none()
+        && rule.destination.is_none()
+        && rule.source_port.is_none();
+
+    match (&rule.app, &rule.port) {
+        (Some(app), _) if plain => Entry::Service(app.clone()),
+        (None, Some(port)) if plain => Entry::Port(format!(
+            "{}/{}",
+            port.replace(':', "-"),
+            protocol_name(rule.protocol)
+        )),
+        _ => Entry::Rich(rich_rule(rule)),
+    }
+}
+
+// Rules of a zone, numbered in the order services, ports and rich rules are
+// listed by firewalld.
+fn parse_entries(services: &str, ports: &str, rich_rules: &str) -> Vec<(FirewallRule, Entry)> {
+    let mut entries = Vec::new();
+
+    for service in services.split_whitespace() {
+        let rule = FirewallRule {
+            app: Some(service.to_string()),
+            ..allow_rule()
+        };
+        entries.push((rule, Entry::Service(service.to_string())));
+    }
+    for port in ports.split_whitespace() {
+        if let Some(rule) = port_rule(port) {
+            entries.push((rule, Entry::Port(port.to_string())));
+        }
+    }
+    for line in rich_rules.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // Rules outside the model are still listed so numbering matches
+        // the zone, but only as an opaque comment.
+        let rule = parse_rich_rule(line).unwrap_or_else(|| FirewallRule {
+            comment: Some(line.to_string()),
+            ..allow_rule()
+        });
+        entries.push((rule, Entry::Rich(line.to_string())));
+    }
+
+    for (i, (rule, _)) in entries.iter_mut().enumerate() {
+        rule.number = Some(i as u32 + 1);
+    }
+    entries
+}
+
+impl Firewalld {
+    fn entries(&self, zone: &str) -> Result<Vec<(FirewallRule, Entry)>, String> {
+        Ok(parse_entries(
+            &list(zone, "services")?,
+            &list(zone, "ports")?,
+            &list(zone, "rich-rules")?,
+        ))
+    }
+}
+
+impl FirewallBackend for Firewalld {
+    fn name(&self) -> &'static str {
+        "firewalld"
+    }
+
+    fn binary(&self) -> &'static str {
+        "firewall-cmd"
+    }
+
+    fn is_active(&self) -> bool {
+        firewall_cmd(&["--state".to_string()]).is_ok_and(|s| s == "running")
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let action = if enabled { "enable" } else { "disable" };
+        run(
+            "systemctl",
+            &[
+                action.to_string(),
+                "--now".to_string(),
+                "firewalld".to_string(),
+            ],
+        )
+        .map(|_| ())
+    }
+
+    fn rules(&self) -> Result<Vec<FirewallRule>, String> {
+        if !self.is_active() {
+            return Ok(Vec::new());
+        }
+        let zone = default_zone()?;
+        Ok(self
+            .entries(&zone)?
+            .into_iter()
+            .map(|(rule, _)| rule)
+            .collect())
+    }
+
+    fn supports(&self, rule: &FirewallRule) -> Result<(), String> {
+        if rule.direction != Direction::In {
+            return Err("firewalld zones only filter incoming traffic".to_string());
+        }
+        if rule.interface.is_some() {
+            return Err("firewalld binds interfaces to zones instead of rules".to_string());
+        }
+        if rule.comment.is_some() {
+            return Err("firewalld rules cannot carry a comment".to_string());
+        }
+        for port in [&rule.port, &rule.source_port].into_iter().flatten() {
+            if port.contains(',') {
+                return Err("firewalld takes one port or range per rule".to_string());
+            }
+            if rule.protocol == Protocol::Any {
+                return Err("firewalld ports require tcp or udp".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn add_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        apply(&default_zone()?, "add", &entry_for(rule))
+    }
+
+    fn delete_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        apply(&default_zone()?, "remove", &entry_for(rule))
+    }
+
+    fn delete_rule_number(&self, number: u32) -> Result<(), String> {
+        let zone = default_zone()?;
+        let (_, entry) = self
+            .entries(&zone)?
+            .into_iter()
+            .find(|(rule, _)| rule.number == Some(number))
+            .ok_or_else(|| format!("Rule {} does not exist", number))?;
+        apply(&zone, "remove", &entry)
+    }
+
+    // firewall-cmd needs the daemon running, so a stopped firewalld has no
+    // zone contents to save; stopping it again is all a restore needs.
+    fn snapshot(&self) -> Result<Snapshot, String> {
+        if !self.is_active() {
+            return Ok(Snapshot {
+                active: false,
+                ruleset: String::new(),
+            });
+        }
+        let zone = default_zone()?;
+        let state = ZoneState {
+            target: get_target(&zone)?,
+            entries: self
+                .entries(&zone)?
+                .into_iter()
+                .map(|(_, entry)| entry)
+                .collect(),
+            zone,
+        };
+        Ok(Snapshot {
+            active: true,
+            ruleset: serde_json::to_string(&state).map_err(|e| e.to_string())?,
+        })
+    }
+
+    fn restore(&self, snapshot: &Snapshot) -> Result<(), String> {
+        if !snapshot.active {
+            return self.set_enabled(false);
+        }
+        if !self.is_active() {
+            self.set_enabled(true)?;
+        }
+
+        let state: ZoneState =
+            serde_json::from_str(&snapshot.ruleset).map_err(|e| e.to_string())?;
+        let current: Vec<Entry> = self
+            .entries(&state.zone)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+
+        for entry in current.iter().filter(|e| !state.entries.contains(e)) {
+            apply(&state.zone, "remove", entry)?;
+        }
+        for entry in state.entries.iter().filter(|e| !current.contains(e)) {
+            apply(&state.zone, "add", entry)?;
+        }
+        if get_target(&state.zone)? != state.target {
+            set_target(&state.zone, &state.target)?;
+        }
+        Ok(())
+    }
+
+    // Zones only filter incoming traffic; their target is what happens to
+    // packets no rule accepts. The `default` target rejects them.
+    fn policy(&self) -> Result<Policy, String> {
+        let incoming = match get_target(&default_zone()?)?.as_str() {
+            "ACCEPT" => RuleAction::Allow,
+            "DROP" => RuleAction::Deny,
+            _ => RuleAction::Reject,
+        };
+        Ok(Policy {
+            incoming: Some(incoming),
+            outgoing: None,
+            routed: None,
+            logging: firewall_cmd(&["--get-log-denied".to_string()]).ok(),
+            logging_levels: LOGGING_LEVELS.iter().map(|l| l.to_string()).collect(),
+        })
+    }
+
+    fn set_policy(&self, direction: Direction, action: RuleAction) -> Result<(), String> {
+        if direction != Direction::In {
+            return Err("firewalld zones only set a policy for incoming traffic".to_string());
+        }
+        let target = match action {
+            RuleAction::Allow => "ACCEPT",
+            RuleAction::Deny => "DROP",
+            _ => "%%REJECT%%",
+        };
+        set_target(&default_zone()?, target)
+    }
+
+    fn logging_levels(&self) -> &'static [&'static str] {
+        &LOGGING_LEVELS
+    }
+
+    fn set_logging(&self, level: &str) -> Result<(), String> {
+        firewall_cmd(&[format!("--set-log-denied={}", level)]).map(|_| ())
+    }
+
+    fn profiles(&self) -> Result<Vec<AppProfile>, String> {
+        let mut profiles = read_services(SYSTEM_SERVICES, false);
+        for custom in read_services(CUSTOM_SERVICES, true) {
+            profiles.retain(|p| p.name != custom.name);
+            profiles.push(custom);
+        }
+        Ok(profiles)
+    }
+
+    fn save_profile(&self, profile: &AppProfile) -> Result<(), String> {
+        if !is_valid_service_name(&profile.name) {
+            return Err(
+                "firewalld service names may only contain letters, digits, - and _".to_string(),
+            );
+        }
+        install_file(&service_path(&profile.name), &service_xml(profile), "0644")?;
+        if self.is_active() {
+            reload()?;
+        }
+        Ok(())
+    }
+
+    fn delete_profile(&self, name: &str) -> Result<(), String> {
+        run("rm", &["-f".to_string(), service_path(name)])?;
+        if self.is_active() {
+            reload()?;
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_service_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn service_path(name: &str) -> String {
+    format!("{}/{}.xml", CUSTOM_SERVICES, name)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn xml_text(xml: &str, tag: &str) -> String {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    xml.split_once(&open)
+        .and_then(|(_, rest)| rest.split_once(&close))
+        .map(|(text, _)| xml_unescape(&text.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .unwrap_or_default()
+}
+
+fn xml_attr(element: &str, name: &str) -> Option<String> {
+    let (_, rest) = element.split_once(&format!("{}=\"", name))?;
+    rest.split_once('"').map(|(value, _)| xml_unescape(value))
+}
+
+// Service definitions are small XML files, e.g.
+// `<service><short>SSH</short><port protocol="tcp" port="22"/></service>`.
+fn parse_service(name: &str, xml: &str, custom: bool) -> AppProfile {
+    let ports = xml
+        .split("<port ")
+        .skip(1)
+        .filter_map(|element| {
+            let element = element.split('>').next()?;
+            let port = xml_attr(element, "port")?.replace('-', ":");
+            Some(match xml_attr(element, "protocol") {
+                Some(protocol) => format!("{}/{}", port, protocol),
+                None => port,
+            })
+        })
+        .collect();
+
+    AppProfile {
+        name: name.to_string(),
+        title: xml_text(xml, "short"),
+        description: xml_text(xml, "description"),
+        ports,
+        custom,
+    }
+}
+
+fn read_services(dir: &str, custom: bool) -> Vec<AppProfile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "xml"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.to_string();
+            let xml = fs::read_to_string(&path).ok()?;
+            Some(parse_service(&name, &xml, custom))
+        })
+        .collect()
+}
+
+// A port without a protocol is opened for both tcp and udp, as ufw does.
+fn service_xml(profile: &AppProfile) -> String {
+    let mut ports = Vec::new();
+    for (spec, protocol) in profile.ports.iter().filter_map(|p| parse_profile_port(p)) {
+        let protocols = match protocol {
+            Protocol::Any => vec!["tcp", "udp"],
+            p => vec![p.as_str()],
+        };
+        for port in spec.split(',') {
+            for protocol in &protocols {
+                ports.push(format!(
+                    "  <port protocol=\"{}\" port=\"{}\"/>\n",
+                    protocol,
+                    port.replace(':', "-")
+                ));
+            }
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<service>\n  <short>{}</short>\n  <description>{}</description>\n{}</service>\n",
+        xml_escape(if profile.title.is_empty() { &profile.name } else { &profile.title }),
+        xml_escape(&profile.description),
+        ports.concat()
+    )
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+// Parses `firewall-cmd --list-all-zones`: an unindented header per zone such
+// as `public (default, active)`, followed by indented `key: values` lines and
+// tab-indented rich rules.
+fn parse_zones(output: &str) -> Vec<Zone> {
+    let mut zones: Vec<Zone> = Vec::new();
+    let mut in_rich_rules = false;
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            let (name, flags) = line.split_once(' ').unwrap_or((line, ""));
+            zones.push(Zone {
+                name: name.to_string(),
+                default: flags.contains("default"),
+                active: flags.contains("active"),
+                ..Default::default()
+            });
+            in_rich_rules = false;
+            continue;
+        }
+        let Some(zone) = zones.last_mut() else {
+            continue;
+        };
+
+        match line.trim().split_once(':') {
+            Some((key, value)) if !line.starts_with('\t') => {
+                in_rich_rules = key == "rich rules";
+                match key {
+                    "target" => zone.target = value.trim().to_string(),
+                    "interfaces" => zone.interfaces = split_list(value),
+                    "sources" => zone.sources = split_list(value),
+                    "services" => zone.services = split_list(value),
+                    "ports" => zone.ports = split_list(value),
+                    _ => {}
+                }
+            }
+            _ if in_rich_rules => zone.rich_rules.push(line.trim().to_string()),
+            _ => {}
+        }
+    }
+    zones
+}
+
+pub async fn list_zones() -> impl Responder {
+    if !Firewalld.is_installed() {
+        return HttpResponse::NotFound().json("Zones are only available with firewalld");
+    }
+
+    match firewall_cmd(&["--list-all-zones".to_string()]) {
+        Ok(output) => HttpResponse::Ok().json(parse_zones(&output)),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to list zones: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zone_entries_are_numbered_in_listing_order() {
+        let rich = "rule family=\"ipv4\" source address=\"10.0.0.0/8\" port port=\"5432\" protocol=\"tcp\" accept\n\
+            rule family=\"ipv6\" source address=\"2001:db8::/32\" service name=\"ssh\" drop\n\
+            rule source NOT address=\"192.0.2.1\" reject\n";
+        let entries = parse_entries("dhcpv6-client ssh", "8080/tcp 6000-6010/udp", rich);
+        let rules: Vec<&FirewallRule> = entries.iter().map(|(rule, _)| rule).collect();
+
+        assert_eq!(rules.len(), 7);
+        assert!(rules
+            .iter()
+            .enumerate()
+            .all(|(i, r)| r.number == Some(i as u32 + 1)));
+        assert_eq!(rules[1].app.as_deref(), Some("ssh"));
+        assert_eq!(rules[3].port.as_deref(), Some("6000:6010"));
+        assert_eq!(rules[3].protocol, Protocol::Udp);
+
+        assert_eq!(rules[4].source.as_deref(), Some("10.0.0.0/8"));
+        assert_eq!(rules[4].port.as_deref(), Some("5432"));
+        assert_eq!(rules[4].protocol, Protocol::Tcp);
+        assert_eq!(rules[5].action, RuleAction::Deny);
+        assert_eq!(rules[5].app.as_deref(), Some("ssh"));
+        assert!(rules[5].v6);
+
+        // Negated matches are kept as opaque entries so numbering still lines up.
+        assert_eq!(
+            rules[6].comment.as_deref(),
+            Some(rich.lines().nth(2).unwrap())
+        );
+        assert!(entries[6].1 == Entry::Rich(rich.lines().nth(2).unwrap().to_string()));
+    }
+
+    #[test]
+    fn rich_rules_round_trip_through_the_parser() {
+        let rules = [
+            FirewallRule {
+                action: RuleAction::Limit,
+                protocol: Protocol::Tcp,
+                port: Some("22".to_string()),
+                source: Some("192.0.2.0/24".to_string()),
+                ..allow_rule()
+            },
+            FirewallRule {
+                action: RuleAction::Reject,
+                protocol: Protocol::Udp,
+                port: Some("6000:6010".to_string()),
+                source_port: Some("53".to_string()),
+                destination: Some("2001:db8::1".to_string()),
+                v6: true,
+                ..allow_rule()
+            },
+            FirewallRule {
+                action: RuleAction::Deny,
+                protocol: Protocol::Tcp,
+                ..allow_rule()
+            },
+            FirewallRule {
+                app: Some("https".to_string()),
+                source: Some("198.51.100.7".to_string()),
+                ..allow_rule()
+            },
+        ];
+
+        assert_eq!(
+            tokenize("rule log prefix=\"ssh in\" level=\"info\" accept"),
+            [
+                "rule",
+                "log",
+                "prefix=\"ssh in\"",
+                "level=\"info\"",
+                "accept"
+            ]
+        );
+        for rule in rules {
+            let text = rich_rule(&rule);
+            assert_eq!(parse_rich_rule(&text).as_ref(), Some(&rule), "{}", text);
+        }
+    }
+
+    #[test]
+    fn zones_are_parsed_from_list_all_zones() {
+        let output = "block\n  target: %%REJECT%%\n  interfaces: \n  services: \n\n\
+            public (default, active)\n  target: default\n  icmp-block-inversion: no\n  \
+            interfaces: eth0 eth1\n  sources: 10.0.0.0/8\n  services: dhcpv6-client ssh\n  \
+            ports: 8080/tcp\n  forward: yes\n  rich rules: \n\
+            \trule family=\"ipv4\" source address=\"10.0.0.5\" accept\n\
+            \trule family=\"ipv6\" source address=\"2001:db8::1\" drop\n";
+
+        let zones = parse_zones(output);
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].target, "%%REJECT%%");
+        assert!(!zones[0].default && !zones[0].active);
+
+        let public = &zones[1];
+        assert!(public.default && public.active);
+        assert_eq!(public.interfaces, ["eth0", "eth1"]);
+        assert_eq!(public.sources, ["10.0.0.0/8"]);
+        assert_eq!(public.services, ["dhcpv6-client", "ssh"]);
+        assert_eq!(public.ports, ["8080/tcp"]);
+        assert_eq!(public.rich_rules.len(), 2);
+        assert_eq!(
+            public.rich_rules[1],
+            "rule family=\"ipv6\" source address=\"2001:db8::1\" drop"
+        );
+    }
+}
@@ -0,0 +1,736 @@
+use actix_web::{HttpResponse, Responder};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use super::policy::Policy;
+use super::{
+    install_file, run, Direction, FirewallBackend, FirewallRule, Protocol, RuleAction, Snapshot,
+};
+
+// WADM keeps its rules in a table of its own so the rest of the ruleset is
+// left to whoever manages it. The table is saved to /etc/nftables.d/wadm.nft,
+// which no distribution loads by default: the admin has to include it from
+// the boot ruleset (/etc/nftables.conf or /etc/sysconfig/nftables.conf) for
+// the rules to survive a reboot.
+const FAMILY: &str = "inet";
+const TABLE: &str = "wadm";
+const CHAINS: [&str; 3] = ["input", "output", "forward"];
+const SAVE_DIR: &str = "/etc/nftables.d";
+const SAVE_FILE: &str = "/etc/nftables.d/wadm.nft";
+const DISABLED_FILE: &str = "/etc/nftables.d/wadm.nft.disabled";
+
+pub struct Nftables;
+
+#[derive(Serialize)]
+pub struct NftChain {
+    pub name: String,
+    pub kind: Option<String>,
+    pub hook: Option<String>,
+    pub priority: Option<i64>,
+    pub policy: Option<String>,
+    pub rules: usize,
+}
+
+#[derive(Serialize)]
+pub struct NftSet {
+    pub name: String,
+    pub kind: String,
+    pub elements: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct NftTable {
+    pub family: String,
+    pub name: String,
+    pub managed: bool,
+    pub chains: Vec<NftChain>,
+    pub sets: Vec<NftSet>,
+}
+
+fn nft(args: &[&str]) -> Result<String, String> {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    run("nft", &args)
+}
+
+fn chain_for(direction: Direction) -> &'static str {
+    match direction {
+        Direction::In => "input",
+        Direction::Out => "output",
+        Direction::Forward => "forward",
+    }
+}
+
+fn ensure_table() -> Result<(), String> {
+    nft(&["add", "table", FAMILY, TABLE])?;
+    for chain in CHAINS {
+        let spec = format!(
+            "{{ type filter hook {} priority 0 ; policy accept ; }}",
+            chain
+        );
+        nft(&["add", "chain", FAMILY, TABLE, chain, &spec])?;
+    }
+    Ok(())
+}
+
+// The saved form declares and deletes the table before redefining it, so
+// loading it with `nft -f` replaces the table instead of appending to it.
+fn script() -> Result<String, String> {
+    let dump = nft(&["list", "table", FAMILY, TABLE])?;
+    Ok(format!(
+        "table {family} {table}\ndelete table {family} {table}\n{}\n",
+        dump,
+        family = FAMILY,
+        table = TABLE
+    ))
+}
+
+fn write(path: &str, content: &str) -> Result<(), String> {
+    let args = ["-d", "-m", "0755", SAVE_DIR].map(String::from);
+    run("install", &args)?;
+    install_file(path, content, "0644")
+}
+
+fn remove(path: &str) {
+    let args = ["-f", path].map(String::from);
+    if let Err(e) = run("rm", &args) {
+        log::warn!("Failed to remove {}: {}", path, e);
+    }
+}
+
+fn save(path: &str) -> Result<(), String> {
+    write(path, &script()?)
+}
+
+fn nft_ports(port: &str) -> String {
+    let port = port.replace(':', "-");
+    if port.contains(',') {
+        format!("{{ {} }}", port.replace(',', ", "))
+    } else {
+        port
+    }
+}
+
+fn model_ports(value: &str) -> String {
+    value
+        .trim_matches(|c| c == '{' || c == '}')
+        .split(',')
+        .map(|p| p.trim().replace('-', ":"))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn expression(rule: &FirewallRule) -> Vec<String> {
+    let mut expr: Vec<String> = Vec::new();
+
+    if let Some(interface) = &rule.interface {
+        let key = if rule.direction == Direction::Out {
+            "oifname"
+        } else {
+            "iifname"
+        };
+        expr.extend([key.to_string(), format!("\"{}\"", interface)]);
+    }
+    for (address, key) in [(&rule.source, "saddr"), (&rule.destination, "daddr")] {
+        if let Some(address) = address {
+            let family = if address.contains(':') { "ip6" } else { "ip" };
+            expr.extend([family.to_string(), key.to_string(), address.clone()]);
+        }
+    }
+
+    let has_ports = rule.port.is_some() || rule.source_port.is_some();
+    let protocol = match rule.protocol {
+        Protocol::Tcp => "tcp",
+        Protocol::Udp => "udp",
+        Protocol::Any => "th",
+    };
+    if rule.protocol == Protocol::Any && has_ports {
+        expr.extend(["meta", "l4proto", "{ tcp, udp }"].map(String::from));
+    } else if rule.protocol != Protocol::Any && !has_ports {
+        expr.extend([
+            "meta".to_string(),
+            "l4proto".to_string(),
+            protocol.to_string(),
+        ]);
+    }
+    for (port, key) in [(&rule.source_port, "sport"), (&rule.port, "dport")] {
+        if let Some(port) = port {
+            expr.extend([protocol.to_string(), key.to_string(), nft_ports(port)]);
+        }
+    }
+
+    expr.push(
+        match rule.action {
+            RuleAction::Allow | RuleAction::Limit => "accept",
+            RuleAction::Deny => "drop",
+            RuleAction::Reject => "reject",
+        }
+        .to_string(),
+    );
+    if let Some(comment) = rule.comment.as_ref().filter(|c| !c.is_empty()) {
+        expr.extend(["comment".to_string(), format!("\"{}\"", comment)]);
+    }
+    expr
+}
+
+// Splits rule text on whitespace, keeping `{ a, b }` sets as one token.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut set: Option<String> = None;
+
+    for word in text.split_whitespace() {
+        match set.as_mut() {
+            Some(s) => {
+                s.push_str(word);
+                if word.ends_with('}') {
+                    tokens.extend(set.take());
+                }
+            }
+            None if word == "{" => set = Some(word.to_string()),
+            None => tokens.push(word.to_string()),
+        }
+    }
+    tokens
+}
+
+// Parses a rule line of `nft -a list table`, e.g.
+// `iifname "eth0" ip saddr 10.0.0.0/8 tcp dport { 22, 80 } accept comment "x" # handle 5`.
+fn parse_rule(line: &str, direction: Direction) -> Option<FirewallRule> {
+    let (body, handle) = line.rsplit_once("# handle ")?;
+    let (body, comment) = match body.split_once(" comment \"") {
+        Some((body, comment)) => (body, Some(comment.trim_end().trim_end_matches('"'))),
+        None => (body, None),
+    };
+
+    let mut rule = FirewallRule {
+        number: Some(handle.trim().parse().ok()?),
+        action: RuleAction::Allow,
+        direction,
+        protocol: Protocol::Any,
+        port: None,
+        source: None,
+        source_port: None,
+        destination: None,
+        interface: None,
+        app: None,
+        comment: comment.map(|c| c.to_string()),
+        v6: false,
+    };
+    let mut action = None;
+
+    let tokens = tokenize(body);
+    let mut tokens = tokens.iter().map(|t| t.as_str());
+    while let Some(token) = tokens.next() {
+        match token {
+            "iifname" | "oifname" => {
+                rule.interface = tokens.next().map(|i| i.trim_matches('"').to_string())
+            }
+            "ip" | "ip6" => {
+                rule.v6 = token == "ip6";
+                match (tokens.next(), tokens.next()) {
+                    (Some("saddr"), Some(a)) => rule.source = Some(a.to_string()),
+                    (Some("daddr"), Some(a)) => rule.destination = Some(a.to_string()),
+                    _ => return None,
+                }
+            }
+            "tcp" | "udp" | "th" => {
+                rule.protocol = Protocol::parse(token).unwrap_or(Protocol::Any);
+                match (tokens.next(), tokens.next()) {
+                    (Some("dport"), Some(p)) => rule.port = Some(model_ports(p)),
+                    (Some("sport"), Some(p)) => rule.source_port = Some(model_ports(p)),
+                    _ => return None,
+                }
+            }
+            "meta" => {
+                if tokens.next() != Some("l4proto") {
+                    return None;
+                }
+                rule.protocol = tokens
+                    .next()
+                    .and_then(Protocol::parse)
+                    .unwrap_or(Protocol::Any);
+            }
+            "accept" => action = Some(RuleAction::Allow),
+            "drop" => action = Some(RuleAction::Deny),
+            "reject" => action = Some(RuleAction::Reject),
+            _ => {}
+        }
+    }
+
+    rule.action = action?;
+    Some(rule)
+}
+
+// Reads the policy of each base chain from `nft list table`, where it sits on
+// the chain's `type filter hook input priority filter; policy drop;` line.
+fn chain_policies(listing: &str) -> Vec<(String, RuleAction)> {
+    let mut policies = Vec::new();
+    let mut chain = None;
+
+    for line in listing.lines().map(|l| l.trim()) {
+        if let Some(name) = line.strip_prefix("chain ") {
+            chain = name.split_whitespace().next().map(String::from);
+        } else if let (Some(name), Some((_, policy))) = (&chain, line.split_once("policy ")) {
+            let action = match policy.trim_end_matches(';').trim() {
+                "accept" => RuleAction::Allow,
+                "drop" => RuleAction::Deny,
+                _ => continue,
+            };
+            policies.push((name.clone(), action));
+        }
+    }
+    policies
+}
+
+// Collects the rules of WADM's base chains from `nft -a list table`.
+fn parse_listing(output: &str) -> Vec<(&'static str, FirewallRule)> {
+    let mut entries = Vec::new();
+    let mut chain = None;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("chain ") {
+            let name = name.trim_end_matches('{').split_whitespace().next();
+            chain = CHAINS.into_iter().find(|c| Some(*c) == name);
+            continue;
+        }
+        let direction = match chain {
+            Some("output") => Direction::Out,
+            Some("forward") => Direction::Forward,
+            Some(_) => Direction::In,
+            None => continue,
+        };
+        if line.starts_with("type ") {
+            continue;
+        }
+        if let Some(rule) = parse_rule(line, direction) {
+            entries.push((chain_for(direction), rule));
+        }
+    }
+    entries
+}
+
+impl Nftables {
+    fn entries(&self) -> Result<Vec<(&'static str, FirewallRule)>, String> {
+        let output = nft(&["-a", "list", "table", FAMILY, TABLE])?;
+        Ok(parse_listing(&output))
+    }
+
+    fn delete_handle(&self, chain: &str, handle: u32) -> Result<(), String> {
+        nft(&[
+            "delete",
+            "rule",
+            FAMILY,
+            TABLE,
+            chain,
+            "handle",
+            &handle.to_string(),
+        ])?;
+        save(SAVE_FILE)
+    }
+}
+
+impl FirewallBackend for Nftables {
+    fn name(&self) -> &'static str {
+        "nftables"
+    }
+
+    fn binary(&self) -> &'static str {
+        "nft"
+    }
+
+    fn is_active(&self) -> bool {
+        nft(&["list", "table", FAMILY, TABLE]).is_ok()
+    }
+
+    // Disabling removes the table from the running ruleset but keeps its
+    // rules on disk, so enabling again brings them back.
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        if enabled {
+            if Path::new(DISABLED_FILE).exists() {
+                nft(&["-f", DISABLED_FILE])?;
+                remove(DISABLED_FILE);
+            }
+            ensure_table()?;
+            save(SAVE_FILE)
+        } else {
+            if !self.is_active() {
+                return Ok(());
+            }
+            save(DISABLED_FILE)?;
+            nft(&["delete", "table", FAMILY, TABLE])?;
+            remove(SAVE_FILE);
+            Ok(())
+        }
+    }
+
+    fn rules(&self) -> Result<Vec<FirewallRule>, String> {
+        if !self.is_active() {
+            return Ok(Vec::new());
+        }
+        Ok(self.entries()?.into_iter().map(|(_, rule)| rule).collect())
+    }
+
+    fn supports(&self, rule: &FirewallRule) -> Result<(), String> {
+        if rule.app.is_some() {
+            return Err("nftables has no application profiles".to_string());
+        }
+        if rule.action == RuleAction::Limit {
+            return Err("Rate limited rules are not supported with nftables".to_string());
+        }
+        Ok(())
+    }
+
+    fn add_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        if !self.is_active() {
+            return Err("Enable the firewall before adding rules".to_string());
+        }
+        let mut args = vec!["add", "rule", FAMILY, TABLE, chain_for(rule.direction)];
+        let expr = expression(rule);
+        args.extend(expr.iter().map(|s| s.as_str()));
+        nft(&args)?;
+        save(SAVE_FILE)
+    }
+
+    fn insert_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        if !self.is_active() {
+            return Err("Enable the firewall before adding rules".to_string());
+        }
+        let mut args = vec!["insert", "rule", FAMILY, TABLE, chain_for(rule.direction)];
+        let expr = expression(rule);
+        args.extend(expr.iter().map(|s| s.as_str()));
+        nft(&args)?;
+        save(SAVE_FILE)
+    }
+
+    // nftables deletes by handle, so the rule is looked up by its fields.
+    fn delete_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        let wanted = FirewallRule {
+            number: None,
+            comment: None,
+            v6: false,
+            ..rule.clone()
+        };
+        let (chain, found) = self
+            .entries()?
+            .into_iter()
+            .find(|(_, r)| {
+                FirewallRule {
+                    number: None,
+                    comment: None,
+                    v6: false,
+                    ..r.clone()
+                } == wanted
+            })
+            .ok_or("No matching rule found")?;
+        self.delete_handle(chain, found.number.unwrap_or_default())
+    }
+
+    fn delete_rule_number(&self, number: u32) -> Result<(), String> {
+        let (chain, _) = self
+            .entries()?
+            .into_iter()
+            .find(|(_, r)| r.number == Some(number))
+            .ok_or_else(|| format!("Rule {} does not exist", number))?;
+        self.delete_handle(chain, number)
+    }
+
+    fn snapshot(&self) -> Result<Snapshot, String> {
+        let active = self.is_active();
+        Ok(Snapshot {
+            active,
+            ruleset: if active { script()? } else { String::new() },
+        })
+    }
+
+    fn restore(&self, snapshot: &Snapshot) -> Result<(), String> {
+        if !snapshot.active {
+            return self.set_enabled(false);
+        }
+        write(SAVE_FILE, &snapshot.ruleset)?;
+        nft(&["-f", SAVE_FILE])?;
+        remove(DISABLED_FILE);
+        Ok(())
+    }
+
+    // Only the chains of WADM's own table are reported; other tables on the
+    // host may still drop what these accept.
+    fn policy(&self) -> Result<Policy, String> {
+        let policies = chain_policies(&nft(&["list", "table", FAMILY, TABLE])?);
+        let policy = |direction| {
+            policies
+                .iter()
+                .find(|(chain, _)| chain == chain_for(direction))
+                .map(|(_, action)| *action)
+        };
+        Ok(Policy {
+            incoming: policy(Direction::In),
+            outgoing: policy(Direction::Out),
+            routed: policy(Direction::Forward),
+            ..Default::default()
+        })
+    }
+
+    fn set_policy(&self, direction: Direction, action: RuleAction) -> Result<(), String> {
+        let policy = match action {
+            RuleAction::Allow => "accept",
+            RuleAction::Deny => "drop",
+            _ => return Err("nftables chains can only accept or drop by default".to_string()),
+        };
+        if !self.is_active() {
+            return Err("Enable the firewall before changing its policy".to_string());
+        }
+        let spec = format!("{{ policy {} ; }}", policy);
+        nft(&["chain", FAMILY, TABLE, chain_for(direction), &spec])?;
+        save(SAVE_FILE)
+    }
+}
+
+fn set_element(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Object(o) => match (o.get("prefix"), o.get("range")) {
+            (Some(p), _) => format!(
+                "{}/{}",
+                p["addr"].as_str().unwrap_or_default(),
+                p["len"].as_u64().unwrap_or_default()
+            ),
+            (_, Some(Value::Array(r))) if r.len() == 2 => {
+                format!("{}-{}", set_element(&r[0]), set_element(&r[1]))
+            }
+            _ => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+// Summarises `nft -j list ruleset`, whose top-level array mixes table, chain,
+// rule and set objects that refer to their table by family and name.
+fn summarize(ruleset: &Value) -> Vec<NftTable> {
+    let items = ruleset["nftables"].as_array().cloned().unwrap_or_default();
+    let mut tables: Vec<NftTable> = Vec::new();
+    let find = |tables: &mut Vec<NftTable>, o: &Value| -> Option<usize> {
+        tables
+            .iter()
+            .position(|t| t.family == o["family"] && t.name == o["table"])
+    };
+
+    for item in &items {
+        if let Some(t) = item.get("table") {
+            let family = t["family"].as_str().unwrap_or_default().to_string();
+            let name = t["name"].as_str().unwrap_or_default().to_string();
+            tables.push(NftTable {
+                managed: family == FAMILY && name == TABLE,
+                family,
+                name,
+                chains: Vec::new(),
+                sets: Vec::new(),
+            });
+        } else if let Some(c) = item.get("chain") {
+            if let Some(i) = find(&mut tables, c) {
+                tables[i].chains.push(NftChain {
+                    name: c["name"].as_str().unwrap_or_default().to_string(),
+                    kind: c["type"].as_str().map(String::from),
+                    hook: c["hook"].as_str().map(String::from),
+                    priority: c["prio"].as_i64(),
+                    policy: c["policy"].as_str().map(String::from),
+                    rules: 0,
+                });
+            }
+        } else if let Some(r) = item.get("rule") {
+            if let Some(i) = find(&mut tables, r) {
+                if let Some(chain) = tables[i].chains.iter_mut().find(|c| c.name == r["chain"]) {
+                    chain.rules += 1;
+                }
+            }
+        } else if let Some(s) = item.get("set") {
+            if let Some(i) = find(&mut tables, s) {
+                tables[i].sets.push(NftSet {
+                    name: s["name"].as_str().unwrap_or_default().to_string(),
+                    kind: match &s["type"] {
+                        Value::String(t) => t.clone(),
+                        other => other.to_string(),
+                    },
+                    elements: s["elem"]
+                        .as_array()
+                        .map(|e| e.iter().map(set_element).collect())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+    }
+    tables
+}
+
+pub async fn list_ruleset() -> impl Responder {
+    if !Nftables.is_installed() {
+        return HttpResponse::NotFound().json("nftables is not installed");
+    }
+
+    let output = match nft(&["-j", "list", "ruleset"]) {
+        Ok(o) => o,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to list ruleset: {}", e))
+        }
+    };
+    match serde_json::from_str::<Value>(&output) {
+        Ok(ruleset) => HttpResponse::Ok().json(summarize(&ruleset)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to parse ruleset: {}", e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "table inet wadm { # handle 12
+	chain input { # handle 1
+		type filter hook input priority filter; policy drop;
+		iifname \"eth0\" ip saddr 10.0.0.0/8 tcp dport { 22, 80 } accept comment \"ssh, web\" # handle 5
+		meta l4proto { tcp, udp } th dport 6000-6010 drop # handle 6
+		ip6 saddr 2001:db8::/32 meta l4proto udp reject # handle 7
+	}
+	chain output { # handle 2
+		type filter hook output priority filter; policy accept;
+		oifname \"wg0\" udp sport 51820 accept # handle 8
+	}
+	chain forward { # handle 3
+		type filter hook forward priority filter; policy accept;
+	}
+}
+";
+
+    fn rule(direction: Direction, action: RuleAction) -> FirewallRule {
+        FirewallRule {
+            number: None,
+            action,
+            direction,
+            protocol: Protocol::Any,
+            port: None,
+            source: None,
+            source_port: None,
+            destination: None,
+            interface: None,
+            app: None,
+            comment: None,
+            v6: false,
+        }
+    }
+
+    #[test]
+    fn rules_are_parsed_from_the_table_listing() {
+        let entries = parse_listing(LISTING);
+        let chains: Vec<&str> = entries.iter().map(|(chain, _)| *chain).collect();
+        assert_eq!(chains, ["input", "input", "input", "output"]);
+
+        let rules: Vec<FirewallRule> = entries.into_iter().map(|(_, rule)| rule).collect();
+        assert_eq!(
+            rules[0],
+            FirewallRule {
+                number: Some(5),
+                protocol: Protocol::Tcp,
+                port: Some("22,80".to_string()),
+                source: Some("10.0.0.0/8".to_string()),
+                interface: Some("eth0".to_string()),
+                comment: Some("ssh, web".to_string()),
+                ..rule(Direction::In, RuleAction::Allow)
+            }
+        );
+        assert_eq!(
+            rules[1],
+            FirewallRule {
+                number: Some(6),
+                port: Some("6000:6010".to_string()),
+                ..rule(Direction::In, RuleAction::Deny)
+            }
+        );
+        assert_eq!(
+            rules[2],
+            FirewallRule {
+                number: Some(7),
+                protocol: Protocol::Udp,
+                source: Some("2001:db8::/32".to_string()),
+                v6: true,
+                ..rule(Direction::In, RuleAction::Reject)
+            }
+        );
+        assert_eq!(rules[3].direction, Direction::Out);
+        assert_eq!(rules[3].source_port.as_deref(), Some("51820"));
+        assert_eq!(rules[3].interface.as_deref(), Some("wg0"));
+
+        assert_eq!(
+            chain_policies(LISTING),
+            [
+                ("input".to_string(), RuleAction::Deny),
+                ("output".to_string(), RuleAction::Allow),
+                ("forward".to_string(), RuleAction::Allow),
+            ]
+        );
+    }
+
+    #[test]
+    fn expressions_round_trip_through_the_parser() {
+        let rules = [
+            FirewallRule {
+                protocol: Protocol::Tcp,
+                port: Some("80,443".to_string()),
+                source: Some("192.0.2.0/24".to_string()),
+                interface: Some("eth0".to_string()),
+                comment: Some("web".to_string()),
+                ..rule(Direction::In, RuleAction::Allow)
+            },
+            FirewallRule {
+                port: Some("6000:6010".to_string()),
+                destination: Some("2001:db8::1".to_string()),
+                v6: true,
+                ..rule(Direction::Forward, RuleAction::Deny)
+            },
+            FirewallRule {
+                protocol: Protocol::Udp,
+                interface: Some("wg0".to_string()),
+                ..rule(Direction::Out, RuleAction::Reject)
+            },
+        ];
+
+        for original in rules {
+            let line = format!("{} # handle 9", expression(&original).join(" "));
+            let parsed = parse_rule(&line, original.direction).unwrap();
+            assert_eq!(
+                parsed,
+                FirewallRule {
+                    number: Some(9),
+                    ..original
+                },
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn the_ruleset_is_summarized_per_table() {
+        let ruleset = serde_json::json!({ "nftables": [
+            { "metainfo": { "json_schema_version": 1 } },
+            { "table": { "family": "inet", "name": "wadm", "handle": 12 } },
+            { "table": { "family": "ip", "name": "nat", "handle": 3 } },
+            { "chain": { "family": "inet", "table": "wadm", "name": "input", "type": "filter",
+                         "hook": "input", "prio": 0, "policy": "drop" } },
+            { "rule": { "family": "inet", "table": "wadm", "chain": "input", "handle": 5 } },
+            { "rule": { "family": "inet", "table": "wadm", "chain": "input", "handle": 6 } },
+            { "set": { "family": "ip", "table": "nat", "name": "blocked", "type": "ipv4_addr",
+                       "elem": ["192.0.2.1"] } },
+        ]});
+
+        let tables = summarize(&ruleset);
+        assert_eq!(tables.len(), 2);
+        assert!(tables[0].managed && !tables[1].managed);
+        assert_eq!(tables[0].chains[0].policy.as_deref(), Some("drop"));
+        assert_eq!(tables[0].chains[0].rules, 2);
+        assert_eq!(tables[1].sets[0].name, "blocked");
+        assert_eq!(tables[1].sets[0].elements.len(), 1);
+    }
+}
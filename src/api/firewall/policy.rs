@@ -0,0 +1,182 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use super::rollback::{self, SafeApply, SafeQuery};
+use super::{detect, is_port_list, is_valid_app, is_valid_comment, is_valid_port};
+use super::{Direction, Protocol, RuleAction};
+use crate::api::alerts::AlertLog;
+
+// The default action for traffic no rule matches, per direction, and the
+// logging level. Directions a backend does not filter are left unset.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Policy {
+    pub incoming: Option<RuleAction>,
+    pub outgoing: Option<RuleAction>,
+    pub routed: Option<RuleAction>,
+    pub logging: Option<String>,
+    pub logging_levels: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PolicyChange {
+    pub direction: Direction,
+    pub action: RuleAction,
+}
+
+#[derive(Deserialize)]
+pub struct LoggingChange {
+    pub level: String,
+}
+
+// A named set of ports rules can refer to by name: a ufw application profile
+// or a firewalld service. Ports use ufw syntax with an optional protocol,
+// e.g. `22/tcp`, `80,443/tcp` or `60000:61000/udp`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AppProfile {
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub ports: Vec<String>,
+    #[serde(default)]
+    pub custom: bool,
+}
+
+pub fn parse_profile_port(spec: &str) -> Option<(String, Protocol)> {
+    let (port, protocol) = match spec.split_once('/') {
+        Some((port, protocol)) => (port, Protocol::parse(protocol)?),
+        None => (spec, Protocol::Any),
+    };
+    if !is_valid_port(port) || (is_port_list(port) && protocol == Protocol::Any) {
+        return None;
+    }
+    Some((port.to_string(), protocol))
+}
+
+impl AppProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if !is_valid_app(&self.name) {
+            return Err("Invalid profile name".to_string());
+        }
+        if !is_valid_comment(&self.title) || !is_valid_comment(&self.description) {
+            return Err(
+                "Titles and descriptions cannot contain quotes or control characters".to_string(),
+            );
+        }
+        if self.ports.is_empty() {
+            return Err("A profile needs at least one port".to_string());
+        }
+        if let Some(port) = self.ports.iter().find(|p| parse_profile_port(p).is_none()) {
+            return Err(format!("Invalid port: {}", port));
+        }
+        Ok(())
+    }
+}
+
+pub async fn set_policy(
+    body: web::Json<PolicyChange>,
+    query: web::Query<SafeQuery>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    if body.action == RuleAction::Limit {
+        return HttpResponse::BadRequest().json("Limit cannot be used as a default policy");
+    }
+
+    let risk = (body.direction == Direction::In && body.action != RuleAction::Allow)
+        .then(|| "Refusing incoming traffic by default may block WADM or SSH".to_string());
+
+    let backend = detect();
+    let change = || {
+        backend
+            .set_policy(body.direction, body.action)
+            .map(|_| "Default policy updated".to_string())
+            .map_err(|e| format!("Failed to set default policy: {}", e))
+    };
+    rollback::apply(&safe, &alerts, &query, backend.as_ref(), risk, change)
+}
+
+pub async fn set_logging(body: web::Json<LoggingChange>) -> impl Responder {
+    let backend = detect();
+    let levels = backend.logging_levels();
+    if levels.is_empty() {
+        return HttpResponse::BadRequest().json(format!("{} has no logging level", backend.name()));
+    }
+    if !levels.contains(&body.level.as_str()) {
+        return HttpResponse::BadRequest().json(format!(
+            "Logging level must be one of: {}",
+            levels.join(", ")
+        ));
+    }
+
+    match backend.set_logging(&body.level) {
+        Ok(()) => HttpResponse::Ok().json(format!("Logging set to {}", body.level)),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to set logging: {}", e)),
+    }
+}
+
+pub async fn list_profiles() -> impl Responder {
+    match detect().profiles() {
+        Ok(mut profiles) => {
+            profiles.sort_by_key(|p| p.name.to_lowercase());
+            HttpResponse::Ok().json(profiles)
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to list profiles: {}", e))
+        }
+    }
+}
+
+pub async fn save_profile(body: web::Json<AppProfile>) -> impl Responder {
+    if let Err(e) = body.validate() {
+        return HttpResponse::BadRequest().json(e);
+    }
+
+    let backend = detect();
+    let existing = match backend.profiles() {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to list profiles: {}", e))
+        }
+    };
+    if existing.iter().any(|p| p.name == body.name && !p.custom) {
+        return HttpResponse::Conflict().json(format!(
+            "{} is a built-in profile and cannot be changed",
+            body.name
+        ));
+    }
+
+    match backend.save_profile(&body) {
+        Ok(()) => HttpResponse::Ok().json(format!("Profile {} saved", body.name)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to save profile: {}", e))
+        }
+    }
+}
+
+pub async fn delete_profile(name: web::Path<String>) -> impl Responder {
+    let backend = detect();
+    let existing = match backend.profiles() {
+        Ok(p) => p,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to list profiles: {}", e))
+        }
+    };
+    match existing.iter().find(|p| p.name == *name) {
+        None => return HttpResponse::NotFound().json(format!("Profile {} not found", name)),
+        Some(p) if !p.custom => {
+            return HttpResponse::BadRequest().json("Only profiles created in WADM can be removed")
+        }
+        Some(_) => {}
+    }
+
+    match backend.delete_profile(&name) {
+        Ok(()) => HttpResponse::Ok().json(format!("Profile {} removed", name)),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to remove profile: {}", e))
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{
+    backend_named, Direction, FirewallBackend, FirewallRule, Protocol, RuleAction, Snapshot,
+};
+use crate::api::alerts::AlertLog;
+
+pub const DEFAULT_TIMEOUT: u64 = 30;
+const MIN_TIMEOUT: u64 = 5;
+const MAX_TIMEOUT: u64 = 600;
+
+#[derive(Deserialize)]
+pub struct SafeQuery {
+    pub timeout: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PendingChange {
+    pub id: String,
+    pub description: String,
+    // Set when the change was put on a timer because it could cut off access.
+    pub reason: Option<String>,
+    pub timeout: u64,
+    pub expires_at: String,
+}
+
+struct Pending {
+    change: PendingChange,
+    backend: &'static str,
+    snapshot: Snapshot,
+}
+
+// At most one change waits for confirmation at a time; further changes are
+// refused until it is confirmed or rolled back, since rolling back restores
+// the whole ruleset.
+#[derive(Default)]
+pub struct SafeApply {
+    pending: Mutex<Option<Pending>>,
+}
+
+impl SafeApply {
+    pub fn pending(&self) -> Option<PendingChange> {
+        self.pending
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|p| p.change.clone())
+    }
+
+    fn take(&self, id: &str) -> Option<Pending> {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.as_ref().is_some_and(|p| p.change.id == id) {
+            pending.take()
+        } else {
+            None
+        }
+    }
+}
+
+pub fn ssh_ports() -> Vec<u16> {
+    let mut files = vec![PathBuf::from("/etc/ssh/sshd_config")];
+    if let Ok(dir) = fs::read_dir("/etc/ssh/sshd_config.d") {
+        files.extend(
+            dir.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "conf")),
+        );
+    }
+
+    let mut ports: Vec<u16> = files
+        .iter()
+        .filter_map(|f| fs::read_to_string(f).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .filter_map(|line| {
+                    let mut words = line.split_whitespace();
+                    match (words.next(), words.next()) {
+                        (Some(key), Some(value)) if key.eq_ignore_ascii_case("port") => {
+                            value.parse().ok()
+                        }
+                        _ => None,
+                    }
+                })
+                .collect::<Vec<u16>>()
+        })
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+
+    if ports.is_empty() {
+        vec![22]
+    } else {
+        ports
+    }
+}
+
+// Ports an admin needs to reach the host: WADM itself and sshd.
+fn protected_ports() -> Vec<(&'static str, u16)> {
+    let mut ports = vec![("WADM", crate::PORT)];
+    ports.extend(ssh_ports().into_iter().map(|p| ("SSH", p)));
+    ports
+}
+
+fn covers(spec: &str, port: u16) -> bool {
+    spec.split(',').any(|item| match item.split_once(':') {
+        Some((start, end)) => matches!(
+            (start.parse::<u16>(), end.parse::<u16>()),
+            (Ok(start), Ok(end)) if (start..=end).contains(&port)
+        ),
+        None => item.parse() == Ok(port),
+    })
+}
+
+// Names the protected service whose traffic a rule matches, if any. Rules
+// limited to a source or interface are counted too, since the admin may be
+// connecting from anywhere.
+fn reaches(rule: &FirewallRule) -> Option<String> {
+    if rule.direction != Direction::In {
+        return None;
+    }
+    if let Some(app) = &rule.app {
+        return app
+            .to_lowercase()
+            .contains("ssh")
+            .then(|| format!("SSH ({})", app));
+    }
+    if rule.protocol == Protocol::Udp {
+        return None;
+    }
+
+    protected_ports()
+        .into_iter()
+        .find(|(_, port)| rule.port.as_deref().is_none_or(|spec| covers(spec, *port)))
+        .map(|(name, port)| format!("{} (port {})", name, port))
+}
+
+pub fn add_risk(rule: &FirewallRule) -> Option<String> {
+    if !matches!(rule.action, RuleAction::Deny | RuleAction::Reject) {
+        return None;
+    }
+    reaches(rule).map(|target| format!("The rule may block {}", target))
+}
+
+pub fn delete_risk(rule: &FirewallRule) -> Option<String> {
+    if !matches!(rule.action, RuleAction::Allow | RuleAction::Limit) {
+        return None;
+    }
+    reaches(rule).map(|target| format!("Removing the rule may block {}", target))
+}
+
+pub fn enable_risk() -> String {
+    "Enabling the firewall may block WADM or SSH".to_string()
+}
+
+fn revert(pending: Pending, alerts: &AlertLog, cause: &str) -> Result<(), String> {
+    let backend = backend_named(pending.backend)
+        .ok_or_else(|| format!("Unknown firewall backend {}", pending.backend))?;
+
+    match backend.restore(&pending.snapshot) {
+        Ok(()) => {
+            alerts.push(
+                "firewall",
+                pending.backend,
+                "warning",
+                format!("Rolled back \"{}\": {}", pending.change.description, cause),
+            );
+            Ok(())
+        }
+        Err(e) => {
+            alerts.push(
+                "firewall",
+                pending.backend,
+                "critical",
+                format!(
+                    "Failed to roll back \"{}\": {}",
+                    pending.change.description, e
+                ),
+            );
+            Err(e)
+        }
+    }
+}
+
+// Runs `change` against the firewall. When a timeout is given, or `risk`
+// says the change could lock the admin out, the ruleset is snapshotted first
+// and restored unless the change is confirmed within the timeout. The
+// response closes the connection so the confirmation has to arrive over a new
+// one, which proves the host is still reachable.
+pub fn apply(
+    safe: &web::Data<SafeApply>,
+    alerts: &web::Data<AlertLog>,
+    query: &SafeQuery,
+    backend: &dyn FirewallBackend,
+    risk: Option<String>,
+    change: impl FnOnce() -> Result<String, String>,
+) -> HttpResponse {
+    let timeout = query.timeout.or(risk.as_ref().map(|_| DEFAULT_TIMEOUT));
+    if timeout.is_some_and(|t| !(MIN_TIMEOUT..=MAX_TIMEOUT).contains(&t)) {
+        return HttpResponse::BadRequest().json(format!(
+            "Timeout must be between {} and {} seconds",
+            MIN_TIMEOUT, MAX_TIMEOUT
+        ));
+    }
+
+    let mut pending = safe.pending.lock().unwrap();
+    if pending.is_some() {
+        return HttpResponse::Conflict().json("Another firewall change is awaiting confirmation");
+    }
+
+    let Some(timeout) = timeout else {
+        return match change() {
+            Ok(message) => HttpResponse::Ok().json(message),
+            Err(e) => HttpResponse::InternalServerError().json(e),
+        };
+    };
+
+    let snapshot = match backend.snapshot() {
+        Ok(s) => s,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to snapshot the firewall: {}", e))
+        }
+    };
+
+    let description = match change() {
+        Ok(message) => message,
+        Err(e) => {
+            // A failed change may still have been applied in part.
+            if let Err(restore) = backend.restore(&snapshot) {
+                log::error!("Failed to restore firewall after error: {}", restore);
+            }
+            return HttpResponse::InternalServerError().json(e);
+        }
+    };
+
+    let change = PendingChange {
+        id: format!("{:016x}", rand::random::<u64>()),
+        description,
+        reason: risk,
+        timeout,
+        expires_at: (Utc::now() + chrono::Duration::seconds(timeout as i64)).to_rfc3339(),
+    };
+    *pending = Some(Pending {
+        change: change.clone(),
+        backend: backend.name(),
+        snapshot,
+    });
+    drop(pending);
+
+    let (safe, alerts, id) = (safe.clone(), alerts.clone(), change.id.clone());
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(timeout)).await;
+        if let Some(expired) = safe.take(&id) {
+            let cause = format!("not confirmed within {} seconds", timeout);
+            let _ = revert(expired, &alerts, &cause);
+        }
+    });
+
+    HttpResponse::Accepted().force_close().json(change)
+}
+
+pub async fn confirm_change(id: web::Path<String>, safe: web::Data<SafeApply>) -> impl Responder {
+    match safe.take(&id) {
+        Some(pending) => {
+            HttpResponse::Ok().json(format!("Confirmed: {}", pending.change.description))
+        }
+        None => HttpResponse::NotFound()
+            .json("No change with that id is awaiting confirmation; it may have been rolled back"),
+    }
+}
+
+pub async fn rollback_change(
+    id: web::Path<String>,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let Some(pending) = safe.take(&id) else {
+        return HttpResponse::NotFound().json("No change with that id is awaiting confirmation");
+    };
+
+    match revert(pending, &alerts, "rolled back on request") {
+        Ok(()) => HttpResponse::Ok().json("Change rolled back"),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to roll back: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(action: RuleAction, port: Option<&str>) -> FirewallRule {
+        FirewallRule {
+            number: None,
+            action,
+            direction: Direction::In,
+            protocol: Protocol::Tcp,
+            port: port.map(String::from),
+            source: None,
+            source_port: None,
+            destination: None,
+            interface: None,
+            app: None,
+            comment: None,
+            v6: false,
+        }
+    }
+
+    #[test]
+    fn changes_that_can_block_wadm_are_guarded() {
+        assert!(add_risk(&rule(RuleAction::Deny, Some("8000:9000"))).is_some());
+        assert!(add_risk(&rule(RuleAction::Reject, Some("80,8168"))).is_some());
+        assert!(add_risk(&rule(RuleAction::Deny, None)).is_some());
+        assert!(delete_risk(&rule(RuleAction::Allow, Some("8168"))).is_some());
+
+        assert!(add_risk(&rule(RuleAction::Allow, Some("8168"))).is_none());
+        assert!(delete_risk(&rule(RuleAction::Deny, Some("8168"))).is_none());
+        assert!(add_risk(&FirewallRule {
+            direction: Direction::Out,
+            ..rule(RuleAction::Deny, None)
+        })
+        .is_none());
+    }
+}
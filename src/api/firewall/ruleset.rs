@@ -0,0 +1,736 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+
+use super::policy::AppProfile;
+use super::rollback::{self, SafeApply, SafeQuery};
+use super::{detect, Direction, FirewallBackend, FirewallRule, Protocol, RuleAction};
+use crate::api::alerts::AlertLog;
+
+const TEMPLATES_FILE: &str = "wadm-firewall-templates.json";
+const VERSION: u32 = 1;
+
+// A firewall configuration in a form that can be moved between hosts, and
+// between backends as far as they can express it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Ruleset {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub policy: DefaultPolicy,
+    #[serde(default)]
+    pub rules: Vec<FirewallRule>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<AppProfile>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct DefaultPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incoming: Option<RuleAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outgoing: Option<RuleAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routed: Option<RuleAction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logging: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PolicyUpdate {
+    pub direction: Direction,
+    pub from: Option<RuleAction>,
+    pub to: RuleAction,
+}
+
+// What importing a ruleset would change on this host. `skipped` lists the
+// parts the local backend cannot express.
+#[derive(Serialize, Default)]
+pub struct RulesetDiff {
+    pub enable: Option<bool>,
+    pub policy: Vec<PolicyUpdate>,
+    pub logging: Option<String>,
+    pub add: Vec<FirewallRule>,
+    pub remove: Vec<FirewallRule>,
+    pub profiles: Vec<AppProfile>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct FirewallTemplates {
+    pub templates: BTreeMap<String, Ruleset>,
+}
+
+#[derive(Serialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub builtin: bool,
+}
+
+#[derive(Deserialize)]
+pub struct FormatQuery {
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub replace: bool,
+    pub timeout: Option<u64>,
+}
+
+fn default_version() -> u32 {
+    VERSION
+}
+
+pub fn load_templates() -> FirewallTemplates {
+    match fs::read_to_string(TEMPLATES_FILE) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => FirewallTemplates::default(),
+    }
+}
+
+fn save_templates(store: &FirewallTemplates) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(TEMPLATES_FILE, content)
+        .map_err(|e| format!("Failed to save firewall templates: {}", e))
+}
+
+fn is_valid_template_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn allow(port: &str, source: Option<&str>, comment: &str) -> FirewallRule {
+    FirewallRule {
+        number: None,
+        action: RuleAction::Allow,
+        direction: Direction::In,
+        protocol: Protocol::Tcp,
+        port: Some(port.to_string()),
+        source: source.map(String::from),
+        source_port: None,
+        destination: None,
+        interface: None,
+        app: None,
+        comment: Some(comment.to_string()),
+        v6: false,
+    }
+}
+
+// Every template keeps SSH and WADM reachable so stamping one onto a remote
+// host does not cut off the session that applied it. SSH is allowed on the
+// ports sshd is configured with on this host.
+fn base_template(description: &str, ssh_ports: &[u16], mut rules: Vec<FirewallRule>) -> Ruleset {
+    let ssh = ssh_ports
+        .iter()
+        .map(|port| allow(&port.to_string(), None, "SSH"));
+    let wadm = allow(&crate::PORT.to_string(), None, "WADM");
+    rules.splice(0..0, ssh.chain([wadm]));
+
+    Ruleset {
+        version: VERSION,
+        description: Some(description.to_string()),
+        enabled: Some(true),
+        policy: DefaultPolicy {
+            incoming: Some(RuleAction::Deny),
+            outgoing: Some(RuleAction::Allow),
+            routed: Some(RuleAction::Deny),
+            logging: None,
+        },
+        rules,
+        profiles: Vec::new(),
+    }
+}
+
+fn builtin_templates() -> BTreeMap<String, Ruleset> {
+    templates_for(&rollback::ssh_ports())
+}
+
+fn templates_for(ssh_ports: &[u16]) -> BTreeMap<String, Ruleset> {
+    const PRIVATE_NETWORKS: [&str; 3] = ["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16"];
+
+    let database_rules = [("5432", "PostgreSQL"), ("3306", "MySQL")]
+        .iter()
+        .flat_map(|(port, name)| {
+            PRIVATE_NETWORKS
+                .iter()
+                .map(move |net| allow(port, Some(net), &format!("{} from private networks", name)))
+        })
+        .collect();
+
+    BTreeMap::from([
+        (
+            "base".to_string(),
+            base_template(
+                "Deny incoming traffic except SSH and WADM",
+                ssh_ports,
+                Vec::new(),
+            ),
+        ),
+        (
+            "web-server".to_string(),
+            base_template(
+                "Base policy plus HTTP and HTTPS",
+                ssh_ports,
+                vec![allow("80", None, "HTTP"), allow("443", None, "HTTPS")],
+            ),
+        ),
+        (
+            "database-host".to_string(),
+            base_template(
+                "Base policy plus PostgreSQL and MySQL from private networks",
+                ssh_ports,
+                database_rules,
+            ),
+        ),
+    ])
+}
+
+// Rules compare equal when they match the same traffic, whatever their
+// position, comment or the address family ufw listed them under.
+fn rule_key(rule: &FirewallRule) -> FirewallRule {
+    FirewallRule {
+        number: None,
+        comment: None,
+        v6: false,
+        ..rule.clone()
+    }
+}
+
+fn same_rule(a: &FirewallRule, b: &FirewallRule) -> bool {
+    rule_key(a) == rule_key(b)
+}
+
+fn portable(rule: FirewallRule) -> FirewallRule {
+    FirewallRule {
+        number: None,
+        ..rule
+    }
+}
+
+// Rules the backend could not express itself, like unparsed firewalld rich
+// rules, are left out. ufw lists an IPv6 twin of every rule without an
+// address; adding the rule creates both, so only one is kept.
+fn current_rules(backend: &dyn FirewallBackend) -> Result<Vec<FirewallRule>, String> {
+    let rules: Vec<FirewallRule> = backend
+        .rules()?
+        .into_iter()
+        .filter(|r| r.validate().is_ok() && backend.supports(r).is_ok())
+        .map(portable)
+        .collect();
+
+    let mut kept: Vec<FirewallRule> = Vec::new();
+    for rule in rules {
+        if !kept.iter().any(|k| same_rule(k, &rule)) {
+            kept.push(rule);
+        }
+    }
+    Ok(kept)
+}
+
+fn export(backend: &dyn FirewallBackend) -> Result<Ruleset, String> {
+    let policy = backend.policy().unwrap_or_default();
+    Ok(Ruleset {
+        version: VERSION,
+        description: None,
+        enabled: Some(backend.is_active()),
+        policy: DefaultPolicy {
+            incoming: policy.incoming,
+            outgoing: policy.outgoing,
+            routed: policy.routed,
+            logging: policy.logging,
+        },
+        rules: current_rules(backend)?,
+        profiles: backend
+            .profiles()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.custom)
+            .collect(),
+    })
+}
+
+fn serialize(ruleset: &Ruleset, format: Option<&str>) -> Result<(String, &'static str), String> {
+    match format.unwrap_or("json") {
+        "json" => serde_json::to_string_pretty(ruleset)
+            .map(|s| (s, "application/json"))
+            .map_err(|e| e.to_string()),
+        "yaml" | "yml" => serde_yaml::to_string(ruleset)
+            .map(|s| (s, "application/yaml"))
+            .map_err(|e| e.to_string()),
+        other => Err(format!("Unknown format: {}", other)),
+    }
+}
+
+pub fn parse(body: &[u8]) -> Result<Ruleset, String> {
+    let text = std::str::from_utf8(body).map_err(|_| "Ruleset must be UTF-8 text".to_string())?;
+    let ruleset: Ruleset = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?
+    } else {
+        serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML: {}", e))?
+    };
+
+    if ruleset.version > VERSION {
+        return Err(format!(
+            "Ruleset version {} is newer than this WADM supports",
+            ruleset.version
+        ));
+    }
+    Ok(ruleset)
+}
+
+fn describe(rule: &FirewallRule) -> String {
+    let target = rule
+        .app
+        .clone()
+        .or(rule.port.clone())
+        .unwrap_or_else(|| "any".to_string());
+    let source = rule.source.as_deref().unwrap_or("anywhere");
+    format!(
+        "{} {} {}/{} from {}",
+        rule.action.as_str(),
+        match rule.direction {
+            Direction::In => "in",
+            Direction::Out => "out",
+            Direction::Forward => "forward",
+        },
+        target,
+        rule.protocol.as_str(),
+        source
+    )
+}
+
+fn diff(
+    backend: &dyn FirewallBackend,
+    ruleset: &Ruleset,
+    replace: bool,
+) -> Result<RulesetDiff, String> {
+    let mut diff = RulesetDiff::default();
+    let existing = current_rules(backend)?;
+
+    for rule in ruleset.rules.iter().cloned().map(portable) {
+        if let Err(e) = rule.validate() {
+            diff.skipped.push(format!("{}: {}", describe(&rule), e));
+            continue;
+        }
+        // Comments are dropped rather than the rule on backends without them.
+        let uncommented = FirewallRule {
+            comment: None,
+            ..rule.clone()
+        };
+        let rule = match backend.supports(&rule) {
+            Ok(()) => rule,
+            Err(_) if backend.supports(&uncommented).is_ok() => uncommented,
+            Err(e) => {
+                diff.skipped.push(format!("{}: {}", describe(&rule), e));
+                continue;
+            }
+        };
+        let known = existing
+            .iter()
+            .chain(&diff.add)
+            .any(|r| same_rule(r, &rule));
+        if !known {
+            diff.add.push(rule);
+        }
+    }
+    if replace {
+        diff.remove = existing
+            .into_iter()
+            .filter(|r| !ruleset.rules.iter().any(|w| same_rule(r, w)))
+            .collect();
+    }
+
+    let current = backend.policy().unwrap_or_default();
+    let policies = [
+        (
+            Direction::In,
+            "incoming",
+            ruleset.policy.incoming,
+            current.incoming,
+        ),
+        (
+            Direction::Out,
+            "outgoing",
+            ruleset.policy.outgoing,
+            current.outgoing,
+        ),
+        (
+            Direction::Forward,
+            "routed",
+            ruleset.policy.routed,
+            current.routed,
+        ),
+    ];
+    for (direction, label, wanted, from) in policies {
+        match (wanted, from) {
+            (None, _) => {}
+            (Some(_), None) => diff.skipped.push(format!(
+                "{} does not manage the {} policy",
+                backend.name(),
+                label
+            )),
+            (Some(to), Some(from)) if to != from => diff.policy.push(PolicyUpdate {
+                direction,
+                from: Some(from),
+                to,
+            }),
+            _ => {}
+        }
+    }
+
+    if let Some(level) = &ruleset.policy.logging {
+        if !backend.logging_levels().contains(&level.as_str()) {
+            diff.skipped
+                .push(format!("{} has no logging level {}", backend.name(), level));
+        } else if current.logging.as_ref() != Some(level) {
+            diff.logging = Some(level.clone());
+        }
+    }
+
+    if !ruleset.profiles.is_empty() {
+        let profiles = backend.profiles().unwrap_or_default();
+        for profile in &ruleset.profiles {
+            let profile = AppProfile {
+                custom: true,
+                ..profile.clone()
+            };
+            match profiles.iter().find(|p| p.name == profile.name) {
+                Some(p) if *p == profile => {}
+                Some(p) if !p.custom => diff.skipped.push(format!(
+                    "Profile {} is built in on this host and was kept",
+                    p.name
+                )),
+                _ => match profile.validate() {
+                    Ok(()) => diff.profiles.push(profile),
+                    Err(e) => diff
+                        .skipped
+                        .push(format!("Profile {}: {}", profile.name, e)),
+                },
+            }
+        }
+    }
+
+    diff.enable = ruleset.enabled.filter(|e| *e != backend.is_active());
+    Ok(diff)
+}
+
+fn lockout_risk(diff: &RulesetDiff) -> Option<String> {
+    diff.add
+        .iter()
+        .find_map(rollback::add_risk)
+        .or_else(|| diff.remove.iter().find_map(rollback::delete_risk))
+        .or_else(|| {
+            diff.policy
+                .iter()
+                .any(|p| p.direction == Direction::In && p.to != RuleAction::Allow)
+                .then(|| "Refusing incoming traffic by default may block WADM or SSH".to_string())
+        })
+        .or_else(|| (diff.enable == Some(true)).then(rollback::enable_risk))
+}
+
+// Enabling happens first so backends that need an active firewall can take
+// rules, and disabling happens last.
+fn apply_diff(backend: &dyn FirewallBackend, diff: &RulesetDiff) -> Result<(), String> {
+    if diff.enable == Some(true) {
+        backend.set_enabled(true)?;
+    }
+    for profile in &diff.profiles {
+        backend.save_profile(profile)?;
+    }
+    for rule in &diff.remove {
+        backend.delete_rule(rule)?;
+    }
+    for rule in &diff.add {
+        backend.add_rule(rule)?;
+    }
+    for update in &diff.policy {
+        backend.set_policy(update.direction, update.to)?;
+    }
+    if let Some(level) = &diff.logging {
+        backend.set_logging(level)?;
+    }
+    if diff.enable == Some(false) {
+        backend.set_enabled(false)?;
+    }
+    Ok(())
+}
+
+pub async fn export_ruleset(query: web::Query<FormatQuery>) -> impl Responder {
+    let backend = detect();
+    let ruleset = match export(backend.as_ref()) {
+        Ok(r) => r,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .json(format!("Failed to export firewall: {}", e))
+        }
+    };
+
+    match serialize(&ruleset, query.format.as_deref()) {
+        Ok((body, content_type)) => HttpResponse::Ok()
+            .content_type(content_type)
+            .insert_header((
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"firewall.{}\"",
+                    query.format.as_deref().unwrap_or("json")
+                ),
+            ))
+            .body(body),
+        Err(e) => HttpResponse::BadRequest().json(e),
+    }
+}
+
+pub async fn preview_import(query: web::Query<ImportQuery>, body: web::Bytes) -> impl Responder {
+    let ruleset = match parse(&body) {
+        Ok(r) => r,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+
+    match diff(detect().as_ref(), &ruleset, query.replace) {
+        Ok(diff) => HttpResponse::Ok().json(diff),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Failed to compare: {}", e)),
+    }
+}
+
+pub async fn import_ruleset(
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    safe: web::Data<SafeApply>,
+    alerts: web::Data<AlertLog>,
+) -> impl Responder {
+    let ruleset = match parse(&body) {
+        Ok(r) => r,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+
+    let backend = detect();
+    let diff = match diff(backend.as_ref(), &ruleset, query.replace) {
+        Ok(d) => d,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(format!("Failed to compare: {}", e))
+        }
+    };
+
+    let risk = lockout_risk(&diff);
+    let change = || {
+        apply_diff(backend.as_ref(), &diff)
+            .map(|_| {
+                format!(
+                    "Ruleset imported: {} rules added, {} removed",
+                    diff.add.len(),
+                    diff.remove.len()
+                )
+            })
+            .map_err(|e| format!("Failed to import ruleset: {}", e))
+    };
+    let safe_query = SafeQuery {
+        timeout: query.timeout,
+    };
+    rollback::apply(&safe, &alerts, &safe_query, backend.as_ref(), risk, change)
+}
+
+pub async fn list_templates(data: web::Data<Mutex<FirewallTemplates>>) -> impl Responder {
+    let store = data.lock().unwrap();
+    let builtin = builtin_templates();
+
+    let info: Vec<TemplateInfo> = builtin
+        .iter()
+        .map(|(name, t)| (name, t, true))
+        .chain(store.templates.iter().map(|(name, t)| (name, t, false)))
+        .map(|(name, template, builtin)| TemplateInfo {
+            name: name.clone(),
+            description: template.description.clone(),
+            builtin,
+        })
+        .collect();
+
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn get_template(
+    name: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<Mutex<FirewallTemplates>>,
+) -> impl Responder {
+    let template = builtin_templates()
+        .remove(name.as_str())
+        .or_else(|| data.lock().unwrap().templates.get(name.as_str()).cloned());
+    let Some(template) = template else {
+        return HttpResponse::NotFound().json(format!("Template {} not found", name));
+    };
+
+    match serialize(&template, query.format.as_deref()) {
+        Ok((body, content_type)) => HttpResponse::Ok().content_type(content_type).body(body),
+        Err(e) => HttpResponse::BadRequest().json(e),
+    }
+}
+
+pub async fn save_template(
+    name: web::Path<String>,
+    body: web::Bytes,
+    data: web::Data<Mutex<FirewallTemplates>>,
+) -> impl Responder {
+    if !is_valid_template_name(&name) {
+        return HttpResponse::BadRequest()
+            .json("Template names may only contain letters, digits, - and _");
+    }
+    if builtin_templates().contains_key(name.as_str()) {
+        return HttpResponse::Conflict().json(format!("{} is a built-in template", name));
+    }
+    let template = match parse(&body) {
+        Ok(t) => t,
+        Err(e) => return HttpResponse::BadRequest().json(e),
+    };
+    if let Some((rule, e)) = template
+        .rules
+        .iter()
+        .find_map(|r| r.validate().err().map(|e| (r, e)))
+    {
+        return HttpResponse::BadRequest().json(format!("{}: {}", describe(rule), e));
+    }
+
+    let mut store = data.lock().unwrap();
+    store.templates.insert(name.to_string(), template);
+    match save_templates(&store) {
+        Ok(()) => HttpResponse::Ok().json(format!("Template {} saved", name)),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
+
+pub async fn delete_template(
+    name: web::Path<String>,
+    data: web::Data<Mutex<FirewallTemplates>>,
+) -> impl Responder {
+    let mut store = data.lock().unwrap();
+    if store.templates.remove(name.as_str()).is_none() {
+        return HttpResponse::NotFound().json(format!("Template {} not found", name));
+    }
+    match save_templates(&store) {
+        Ok(()) => HttpResponse::Ok().json(format!("Template {} deleted", name)),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::firewall::policy::Policy;
+    use crate::api::firewall::Snapshot;
+
+    // A ufw-like backend whose rules are listed with an IPv6 twin.
+    struct Fake(Vec<FirewallRule>);
+
+    impl FirewallBackend for Fake {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+        fn is_active(&self) -> bool {
+            true
+        }
+        fn set_enabled(&self, _: bool) -> Result<(), String> {
+            Ok(())
+        }
+        fn rules(&self) -> Result<Vec<FirewallRule>, String> {
+            Ok(self.0.clone())
+        }
+        fn add_rule(&self, _: &FirewallRule) -> Result<(), String> {
+            Ok(())
+        }
+        fn delete_rule(&self, _: &FirewallRule) -> Result<(), String> {
+            Ok(())
+        }
+        fn delete_rule_number(&self, _: u32) -> Result<(), String> {
+            Ok(())
+        }
+        fn snapshot(&self) -> Result<Snapshot, String> {
+            Err("unused".to_string())
+        }
+        fn restore(&self, _: &Snapshot) -> Result<(), String> {
+            Ok(())
+        }
+        fn policy(&self) -> Result<Policy, String> {
+            Ok(Policy {
+                incoming: Some(RuleAction::Allow),
+                ..Default::default()
+            })
+        }
+        fn set_policy(&self, _: Direction, _: RuleAction) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn templates_round_trip_through_yaml_and_json() {
+        for template in templates_for(&[22]).values() {
+            let yaml = serialize(template, Some("yaml")).unwrap().0;
+            let json = serialize(template, Some("json")).unwrap().0;
+            for text in [yaml, json] {
+                let parsed = parse(text.as_bytes()).unwrap();
+                assert_eq!(parsed.rules, template.rules);
+                assert_eq!(parsed.policy.incoming, template.policy.incoming);
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_ports_are_accepted() {
+        let parsed = parse(b"rules:\n- action: deny\n  protocol: tcp\n  port: 25\n").unwrap();
+        assert_eq!(parsed.rules[0].port.as_deref(), Some("25"));
+        assert_eq!(parsed.version, VERSION);
+    }
+
+    #[test]
+    fn diff_only_lists_what_changes() {
+        let ssh = allow("22", None, "SSH");
+        let old = allow("8080", None, "old");
+        let backend = Fake(vec![
+            FirewallRule {
+                number: Some(1),
+                ..ssh.clone()
+            },
+            FirewallRule {
+                number: Some(2),
+                ..old.clone()
+            },
+            FirewallRule {
+                number: Some(3),
+                v6: true,
+                ..ssh.clone()
+            },
+        ]);
+        let template = &templates_for(&[22])["web-server"];
+
+        let merged = diff(&backend, template, false).unwrap();
+        assert_eq!(merged.add.len(), template.rules.len() - 1);
+        assert!(merged.remove.is_empty());
+        assert_eq!(merged.policy.len(), 1);
+        assert!(merged.skipped.iter().any(|s| s.contains("outgoing")));
+
+        let replaced = diff(&backend, template, true).unwrap();
+        assert_eq!(replaced.remove, vec![old]);
+    }
+
+    #[test]
+    fn templates_allow_ssh_on_the_configured_ports() {
+        let templates = templates_for(&[2222, 22022]);
+        let ports: Vec<&str> = templates["base"]
+            .rules
+            .iter()
+            .filter(|r| r.comment.as_deref() == Some("SSH"))
+            .filter_map(|r| r.port.as_deref())
+            .collect();
+        assert_eq!(ports, ["2222", "22022"]);
+    }
+}
@@ -0,0 +1,541 @@
+use std::fs;
+
+use super::policy::{AppProfile, Policy};
+use super::{
+    install_file, is_valid_address, is_valid_port, run, Direction, FirewallBackend, FirewallRule,
+    Protocol, RuleAction, Snapshot,
+};
+
+// Rules added through the ufw command and the default policies are kept in
+// these files, which ufw loads on enable and reload.
+const STATE_FILES: [(&str, &str); 3] = [
+    ("/etc/ufw/user.rules", "0640"),
+    ("/etc/ufw/user6.rules", "0640"),
+    (DEFAULTS_FILE, "0644"),
+];
+const DEFAULTS_FILE: &str = "/etc/default/ufw";
+const CONFIG_FILE: &str = "/etc/ufw/ufw.conf";
+const PROFILE_DIR: &str = "/etc/ufw/applications.d";
+const CUSTOM_PROFILES: &str = "/etc/ufw/applications.d/wadm";
+const LOGGING_LEVELS: [&str; 5] = ["off", "low", "medium", "high", "full"];
+
+pub struct Ufw;
+
+fn ufw(args: &[String]) -> Result<String, String> {
+    run("ufw", args)
+}
+
+#[derive(Default)]
+struct Endpoint {
+    address: Option<String>,
+    port: Option<String>,
+    protocol: Option<Protocol>,
+    interface: Option<String>,
+    app: Option<String>,
+    v6: bool,
+}
+
+fn parse_endpoint(tokens: &[&str]) -> Endpoint {
+    let mut endpoint = Endpoint::default();
+    let mut app = Vec::new();
+    let mut tokens = tokens.iter();
+
+    while let Some(&token) = tokens.next() {
+        // Proto-only rules list their endpoints as `Anywhere/tcp` or `1.2.3.4/udp`.
+        let token = match token.rsplit_once('/') {
+            Some((rest, protocol @ ("tcp" | "udp")))
+                if rest == "Anywhere" || is_valid_address(rest) =>
+            {
+                endpoint.protocol = Protocol::parse(protocol);
+                rest
+            }
+            _ => token,
+        };
+        match token {
+            "on" => endpoint.interface = tokens.next().map(|i| i.to_string()),
+            "Anywhere" => {}
+            "(v6)" => endpoint.v6 = true,
+            // Annotations such as (out), (log) and (log-all).
+            _ if token.starts_with('(') => {}
+            _ if is_valid_address(token) => endpoint.address = Some(token.to_string()),
+            _ => {
+                let (port, protocol) = match token.split_once('/') {
+                    Some((port, protocol)) => (port, Protocol::parse(protocol)),
+                    None => (token, None),
+                };
+                if is_valid_port(port) {
+                    endpoint.port = Some(port.to_string());
+                    endpoint.protocol = protocol;
+                } else {
+                    app.push(token);
+                }
+            }
+        }
+    }
+
+    if !app.is_empty() {
+        endpoint.app = Some(app.join(" "));
+    }
+    endpoint
+}
+
+// Parses one line of `ufw status numbered`, e.g.
+// `[ 2] 80,443/tcp on eth0   ALLOW IN    10.0.0.0/8   # web`.
+fn parse_rule(line: &str) -> Option<FirewallRule> {
+    let rest = line.trim().strip_prefix('[')?;
+    let (number, rest) = rest.split_once(']')?;
+    let number = number.trim().parse().ok()?;
+    let (rest, comment) = match rest.split_once(" # ") {
+        Some((rest, comment)) => (rest, Some(comment.trim().to_string())),
+        None => (rest, None),
+    };
+
+    let tokens: Vec<&str> = rest.split_whitespace().collect();
+    let split = tokens
+        .windows(2)
+        .position(|w| RuleAction::parse(w[0]).is_some() && matches!(w[1], "IN" | "OUT" | "FWD"))?;
+    let action = RuleAction::parse(tokens[split])?;
+    let direction = match tokens[split + 1] {
+        "OUT" => Direction::Out,
+        "FWD" => Direction::Forward,
+        _ => Direction::In,
+    };
+
+    let to = parse_endpoint(&tokens[..split]);
+    let from = parse_endpoint(&tokens[split + 2..]);
+
+    Some(FirewallRule {
+        number: Some(number),
+        action,
+        direction,
+        protocol: to.protocol.or(from.protocol).unwrap_or_default(),
+        port: to.port,
+        source: from.address,
+        source_port: from.port,
+        destination: to.address,
+        interface: to.interface.or(from.interface),
+        app: to.app.or(from.app),
+        comment,
+        v6: to.v6 || from.v6,
+    })
+}
+
+// The ufw arguments that describe this rule, shared by add and delete.
+// The comment is left out since ufw does not match on it when deleting.
+fn rule_args(rule: &FirewallRule) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
+    if rule.direction == Direction::Forward {
+        args.push("route".to_string());
+    }
+    args.push(rule.action.as_str().to_string());
+    let direction = match rule.direction {
+        Direction::Out => "out",
+        _ => "in",
+    };
+    args.push(direction.to_string());
+    if let Some(interface) = &rule.interface {
+        args.extend(["on".to_string(), interface.clone()]);
+    }
+    if rule.protocol != Protocol::Any {
+        args.extend(["proto".to_string(), rule.protocol.as_str().to_string()]);
+    }
+
+    args.push("from".to_string());
+    args.push(rule.source.as_deref().unwrap_or("any").to_string());
+    if let Some(port) = &rule.source_port {
+        args.extend(["port".to_string(), port.clone()]);
+    }
+
+    args.push("to".to_string());
+    args.push(rule.destination.as_deref().unwrap_or("any").to_string());
+    if let Some(app) = &rule.app {
+        args.extend(["app".to_string(), app.clone()]);
+    } else if let Some(port) = &rule.port {
+        args.extend(["port".to_string(), port.clone()]);
+    }
+    args
+}
+
+fn add_args(rule: &FirewallRule) -> Vec<String> {
+    let mut args = rule_args(rule);
+    if let Some(comment) = rule.comment.as_ref().filter(|c| !c.is_empty()) {
+        args.extend(["comment".to_string(), comment.clone()]);
+    }
+    args
+}
+
+// Reads `KEY=value` and `KEY="value"` lines of ufw's shell style config.
+fn config_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (k, v) = line.trim().split_once('=')?;
+        (k == key).then(|| v.trim().trim_matches('"').to_string())
+    })
+}
+
+fn policy_action(value: &str) -> Option<RuleAction> {
+    match value {
+        "ACCEPT" => Some(RuleAction::Allow),
+        "DROP" => Some(RuleAction::Deny),
+        "REJECT" => Some(RuleAction::Reject),
+        _ => None,
+    }
+}
+
+// Application profiles are ini style: a `[Name]` section per profile with
+// `title`, `description` and `|` separated `ports`.
+fn parse_profiles(content: &str, custom: bool) -> Vec<AppProfile> {
+    let mut profiles: Vec<AppProfile> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            profiles.push(AppProfile {
+                name: name.to_string(),
+                title: String::new(),
+                description: String::new(),
+                ports: Vec::new(),
+                custom,
+            });
+            continue;
+        }
+        let (Some(profile), Some((key, value))) = (profiles.last_mut(), line.split_once('='))
+        else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "title" => profile.title = value,
+            "description" => profile.description = value,
+            "ports" => {
+                profile.ports = value
+                    .split('|')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    profiles
+}
+
+// ufw refuses profiles without a title or description.
+fn format_profiles(profiles: &[AppProfile]) -> String {
+    profiles
+        .iter()
+        .map(|p| {
+            let title = if p.title.is_empty() {
+                &p.name
+            } else {
+                &p.title
+            };
+            let description = if p.description.is_empty() {
+                title
+            } else {
+                &p.description
+            };
+            format!(
+                "[{}]\ntitle={}\ndescription={}\nports={}\n",
+                p.name,
+                title,
+                description,
+                p.ports.join("|")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn custom_profiles() -> Vec<AppProfile> {
+    fs::read_to_string(CUSTOM_PROFILES)
+        .map(|content| parse_profiles(&content, true))
+        .unwrap_or_default()
+}
+
+fn write_custom_profiles(profiles: &[AppProfile]) -> Result<(), String> {
+    if profiles.is_empty() {
+        return run("rm", &["-f".to_string(), CUSTOM_PROFILES.to_string()]).map(|_| ());
+    }
+    install_file(CUSTOM_PROFILES, &format_profiles(profiles), "0644")
+}
+
+// `--force` is used in place of piping `yes` into the confirmation prompt.
+fn delete_args(rule: &FirewallRule) -> Vec<String> {
+    let mut args = vec!["--force".to_string(), "delete".to_string()];
+    args.extend(rule_args(rule));
+    args
+}
+
+impl FirewallBackend for Ufw {
+    fn name(&self) -> &'static str {
+        "ufw"
+    }
+
+    fn is_active(&self) -> bool {
+        ufw(&["status".to_string()]).is_ok_and(|s| s.contains("Status: active"))
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let action = if enabled { "enable" } else { "disable" };
+        ufw(&["--force".to_string(), action.to_string()]).map(|_| ())
+    }
+
+    fn rules(&self) -> Result<Vec<FirewallRule>, String> {
+        let output = ufw(&["status".to_string(), "numbered".to_string()])?;
+        Ok(output.lines().filter_map(parse_rule).collect())
+    }
+
+    fn add_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        ufw(&add_args(rule)).map(|_| ())
+    }
+
+    // ufw numbers IPv4 rules before IPv6 ones and only inserts a rule among
+    // those of its own family.
+    fn insert_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        let v6 = [&rule.source, &rule.destination]
+            .into_iter()
+            .flatten()
+            .any(|a| a.contains(':'));
+        let position = self
+            .rules()?
+            .into_iter()
+            .find(|r| r.v6 == v6)
+            .and_then(|r| r.number);
+        match position {
+            Some(n) => {
+                let mut args = add_args(rule);
+                let at = usize::from(rule.direction == Direction::Forward);
+                args.splice(at..at, ["insert".to_string(), n.to_string()]);
+                ufw(&args).map(|_| ())
+            }
+            None => self.add_rule(rule),
+        }
+    }
+
+    fn delete_rule(&self, rule: &FirewallRule) -> Result<(), String> {
+        ufw(&delete_args(rule)).map(|_| ())
+    }
+
+    fn delete_rule_number(&self, number: u32) -> Result<(), String> {
+        ufw(&[
+            "--force".to_string(),
+            "delete".to_string(),
+            number.to_string(),
+        ])
+        .map(|_| ())
+    }
+
+    fn snapshot(&self) -> Result<Snapshot, String> {
+        let files = STATE_FILES
+            .iter()
+            .map(|(path, _)| run("cat", &[path.to_string()]))
+            .collect::<Result<Vec<String>, String>>()?;
+        Ok(Snapshot {
+            active: self.is_active(),
+            ruleset: serde_json::to_string(&files).map_err(|e| e.to_string())?,
+        })
+    }
+
+    fn restore(&self, snapshot: &Snapshot) -> Result<(), String> {
+        let files: Vec<String> =
+            serde_json::from_str(&snapshot.ruleset).map_err(|e| e.to_string())?;
+        for ((path, mode), content) in STATE_FILES.iter().zip(files) {
+            install_file(path, &format!("{}\n", content), mode)?;
+        }
+
+        if !snapshot.active {
+            self.set_enabled(false)
+        } else if self.is_active() {
+            ufw(&["reload".to_string()]).map(|_| ())
+        } else {
+            self.set_enabled(true)
+        }
+    }
+
+    fn policy(&self) -> Result<Policy, String> {
+        let defaults = fs::read_to_string(DEFAULTS_FILE)
+            .map_err(|e| format!("Failed to read {}: {}", DEFAULTS_FILE, e))?;
+        let config = fs::read_to_string(CONFIG_FILE).unwrap_or_default();
+        let action = |key| config_value(&defaults, key).and_then(|v| policy_action(&v));
+
+        Ok(Policy {
+            incoming: action("DEFAULT_INPUT_POLICY"),
+            outgoing: action("DEFAULT_OUTPUT_POLICY"),
+            routed: action("DEFAULT_FORWARD_POLICY"),
+            logging: config_value(&config, "LOGLEVEL"),
+            logging_levels: LOGGING_LEVELS.iter().map(|l| l.to_string()).collect(),
+        })
+    }
+
+    fn set_policy(&self, direction: Direction, action: RuleAction) -> Result<(), String> {
+        let direction = match direction {
+            Direction::In => "incoming",
+            Direction::Out => "outgoing",
+            Direction::Forward => "routed",
+        };
+        ufw(&[
+            "default".to_string(),
+            action.as_str().to_string(),
+            direction.to_string(),
+        ])
+        .map(|_| ())
+    }
+
+    fn logging_levels(&self) -> &'static [&'static str] {
+        &LOGGING_LEVELS
+    }
+
+    fn set_logging(&self, level: &str) -> Result<(), String> {
+        ufw(&["logging".to_string(), level.to_string()]).map(|_| ())
+    }
+
+    fn profiles(&self) -> Result<Vec<AppProfile>, String> {
+        let dir = fs::read_dir(PROFILE_DIR)
+            .map_err(|e| format!("Failed to read {}: {}", PROFILE_DIR, e))?;
+        let mut profiles = Vec::new();
+        for path in dir.flatten().map(|e| e.path()) {
+            if let Ok(content) = fs::read_to_string(&path) {
+                let custom = path.to_str() == Some(CUSTOM_PROFILES);
+                profiles.extend(parse_profiles(&content, custom));
+            }
+        }
+        Ok(profiles)
+    }
+
+    fn save_profile(&self, profile: &AppProfile) -> Result<(), String> {
+        let mut profiles = custom_profiles();
+        let profile = AppProfile {
+            custom: true,
+            ..profile.clone()
+        };
+        match profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => profiles.push(profile),
+        }
+        write_custom_profiles(&profiles)
+    }
+
+    fn delete_profile(&self, name: &str) -> Result<(), String> {
+        let mut profiles = custom_profiles();
+        profiles.retain(|p| p.name != name);
+        write_custom_profiles(&profiles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_passes_arguments_as_a_vector() {
+        let rule = FirewallRule {
+            number: None,
+            action: RuleAction::Allow,
+            direction: Direction::In,
+            protocol: Protocol::Tcp,
+            port: Some("22".to_string()),
+            source: None,
+            source_port: None,
+            destination: None,
+            interface: None,
+            app: None,
+            comment: Some("ssh; from the office".to_string()),
+            v6: false,
+        };
+        assert_eq!(
+            delete_args(&rule),
+            [
+                "--force", "delete", "allow", "in", "proto", "tcp", "from", "any", "to", "any",
+                "port", "22"
+            ]
+        );
+        assert_eq!(add_args(&rule).last().unwrap(), "ssh; from the office");
+    }
+
+    #[test]
+    fn status_lines_are_parsed_into_rules() {
+        let ssh = parse_rule("[ 1] 22/tcp                     LIMIT IN    Anywhere").unwrap();
+        assert_eq!(ssh.number, Some(1));
+        assert_eq!(ssh.action, RuleAction::Limit);
+        assert_eq!(ssh.protocol, Protocol::Tcp);
+        assert_eq!(ssh.port.as_deref(), Some("22"));
+        assert_eq!(ssh.source, None);
+        assert!(!ssh.v6);
+
+        let range = parse_rule(
+            "[ 2] 6000:6010/udp on eth0      DENY OUT    10.0.0.0/8                 # x11; legacy",
+        )
+        .unwrap();
+        assert_eq!(range.action, RuleAction::Deny);
+        assert_eq!(range.direction, Direction::Out);
+        assert_eq!(range.protocol, Protocol::Udp);
+        assert_eq!(range.port.as_deref(), Some("6000:6010"));
+        assert_eq!(range.interface.as_deref(), Some("eth0"));
+        assert_eq!(range.source.as_deref(), Some("10.0.0.0/8"));
+        assert_eq!(range.comment.as_deref(), Some("x11; legacy"));
+
+        let app = parse_rule("[ 3] Apache Full                ALLOW IN    Anywhere").unwrap();
+        assert_eq!(app.app.as_deref(), Some("Apache Full"));
+        assert_eq!(app.port, None);
+        assert_eq!(app.protocol, Protocol::Any);
+
+        let v6 = parse_rule(
+            "[12] 80,443/tcp (v6)            REJECT IN   2001:db8::/32 (v6)         # web",
+        )
+        .unwrap();
+        assert_eq!(v6.number, Some(12));
+        assert_eq!(v6.action, RuleAction::Reject);
+        assert_eq!(v6.port.as_deref(), Some("80,443"));
+        assert_eq!(v6.source.as_deref(), Some("2001:db8::/32"));
+        assert_eq!(v6.comment.as_deref(), Some("web"));
+        assert!(v6.v6);
+
+        let app_v6 =
+            parse_rule("[13] OpenSSH (v6)               ALLOW IN    Anywhere (v6)").unwrap();
+        assert_eq!(app_v6.app.as_deref(), Some("OpenSSH"));
+        assert!(app_v6.v6);
+
+        let proto_only =
+            parse_rule("[14] Anywhere/tcp               ALLOW IN    10.0.0.5/tcp").unwrap();
+        assert_eq!(proto_only.protocol, Protocol::Tcp);
+        assert_eq!(proto_only.source.as_deref(), Some("10.0.0.5"));
+        assert_eq!(proto_only.destination, None);
+        assert_eq!(proto_only.port, None);
+        assert_eq!(proto_only.app, None);
+
+        let proto_only_v6 =
+            parse_rule("[15] Anywhere/udp (v6)          DENY IN     2001:db8::5/udp (v6)").unwrap();
+        assert_eq!(proto_only_v6.protocol, Protocol::Udp);
+        assert_eq!(proto_only_v6.source.as_deref(), Some("2001:db8::5"));
+        assert_eq!(proto_only_v6.app, None);
+        assert!(proto_only_v6.v6);
+
+        assert!(parse_rule("Status: active").is_none());
+        assert!(parse_rule("     To                         Action      From").is_none());
+    }
+
+    #[test]
+    fn profiles_round_trip_through_the_ini_format() {
+        let profiles = vec![
+            AppProfile {
+                name: "Web Server".to_string(),
+                title: "Web".to_string(),
+                description: "HTTP and HTTPS".to_string(),
+                ports: vec!["80,443/tcp".to_string()],
+                custom: true,
+            },
+            AppProfile {
+                name: "game".to_string(),
+                title: String::new(),
+                description: String::new(),
+                ports: vec!["27015".to_string(), "27020:27030/udp".to_string()],
+                custom: true,
+            },
+        ];
+
+        let parsed = parse_profiles(&format_profiles(&profiles), true);
+        assert_eq!(parsed[0], profiles[0]);
+        assert_eq!(parsed[1].title, "game");
+        assert_eq!(parsed[1].ports, profiles[1].ports);
+    }
+}
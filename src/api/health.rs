@@ -0,0 +1,579 @@
+use actix_web::{web, HttpResponse, Responder};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+use crate::api::alerts::AlertLog;
+use crate::api::config::AppConfig;
+use crate::api::systemd;
+
+const HEALTH_CHECKS_FILE: &str = "wadm-health.json";
+const MAX_HISTORY: usize = 1440;
+const MIN_INTERVAL_SECS: u64 = 5;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Probe {
+    Http {
+        url: String,
+        #[serde(default)]
+        expect_status: Option<u16>,
+    },
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    Command {
+        command: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RemediationAction {
+    Restart,
+    Hook { command: Vec<String> },
+    Alert,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HealthCheck {
+    #[serde(default)]
+    pub service: String,
+    pub probe: Probe,
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_threshold")]
+    pub failure_threshold: u32,
+    #[serde(default)]
+    pub actions: Vec<RemediationAction>,
+}
+
+fn default_interval() -> u64 {
+    30
+}
+
+fn default_timeout() -> u64 {
+    5
+}
+
+fn default_threshold() -> u32 {
+    3
+}
+
+#[derive(Serialize, Clone)]
+pub struct ProbeResult {
+    pub timestamp: String,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ProbeStatus {
+    pub consecutive_failures: u32,
+    pub history: VecDeque<ProbeResult>,
+}
+
+#[derive(Serialize)]
+pub struct HealthSummary {
+    pub healthy: Option<bool>,
+    pub uptime_percent: Option<f64>,
+    pub consecutive_failures: u32,
+    pub last_check: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HealthCheckInfo {
+    #[serde(flatten)]
+    pub check: HealthCheck,
+    pub status: HealthSummary,
+}
+
+pub struct HealthMonitor {
+    checks: Mutex<BTreeMap<String, HealthCheck>>,
+    status: Mutex<HashMap<String, ProbeStatus>>,
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+    client: reqwest::Client,
+}
+
+impl ProbeStatus {
+    fn summary(&self) -> HealthSummary {
+        let last = self.history.back();
+        let uptime_percent = if self.history.is_empty() {
+            None
+        } else {
+            let ok = self.history.iter().filter(|r| r.healthy).count();
+            Some(ok as f64 * 100.0 / self.history.len() as f64)
+        };
+
+        HealthSummary {
+            healthy: last.map(|r| r.healthy),
+            uptime_percent,
+            consecutive_failures: self.consecutive_failures,
+            last_check: last.map(|r| r.timestamp.clone()),
+            last_error: last.and_then(|r| r.error.clone()),
+        }
+    }
+}
+
+// Checks are keyed by the full unit name, which is how `list_services`
+// looks them up.
+fn service_key(name: &str) -> String {
+    if name.ends_with(".service") {
+        name.to_string()
+    } else {
+        format!("{}.service", name)
+    }
+}
+
+// Command probes and hooks run as WADM's user, so like the terminal they
+// need Developer Mode.
+fn runs_commands(check: &HealthCheck) -> bool {
+    matches!(check.probe, Probe::Command { .. })
+        || check
+            .actions
+            .iter()
+            .any(|a| matches!(a, RemediationAction::Hook { .. }))
+}
+
+fn developer_mode(config: &web::Data<Mutex<AppConfig>>) -> bool {
+    config.lock().unwrap().developer_mode
+}
+
+impl HealthMonitor {
+    pub fn load() -> Self {
+        let checks: BTreeMap<String, HealthCheck> = match fs::read_to_string(HEALTH_CHECKS_FILE) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => BTreeMap::new(),
+        };
+        let checks = checks
+            .into_values()
+            .map(|mut check| {
+                check.service = service_key(&check.service);
+                (check.service.clone(), check)
+            })
+            .collect();
+
+        HealthMonitor {
+            checks: Mutex::new(checks),
+            status: Mutex::new(HashMap::new()),
+            tasks: Mutex::new(HashMap::new()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let checks = self.checks.lock().unwrap();
+        let content = serde_json::to_string_pretty(&*checks).map_err(|e| e.to_string())?;
+        fs::write(HEALTH_CHECKS_FILE, content)
+            .map_err(|e| format!("Failed to save health checks: {}", e))
+    }
+
+    pub fn summary(&self, service: &str) -> Option<HealthSummary> {
+        if !self.checks.lock().unwrap().contains_key(service) {
+            return None;
+        }
+        let status = self.status.lock().unwrap();
+        Some(
+            status
+                .get(service)
+                .map(|s| s.summary())
+                .unwrap_or_else(|| ProbeStatus::default().summary()),
+        )
+    }
+
+    fn record(&self, service: &str, result: ProbeResult) -> (u32, u32) {
+        let mut status = self.status.lock().unwrap();
+        let entry = status.entry(service.to_string()).or_default();
+        let previous = entry.consecutive_failures;
+
+        if result.healthy {
+            entry.consecutive_failures = 0;
+        } else {
+            entry.consecutive_failures += 1;
+        }
+        if entry.history.len() >= MAX_HISTORY {
+            entry.history.pop_front();
+        }
+        entry.history.push_back(result);
+
+        (previous, entry.consecutive_failures)
+    }
+}
+
+pub fn validate_check(check: &HealthCheck) -> Result<(), String> {
+    if !systemd::is_valid_unit_name(&check.service) {
+        return Err(format!("Invalid service name: {}", check.service));
+    }
+    if check.interval_secs < MIN_INTERVAL_SECS {
+        return Err(format!(
+            "Interval must be at least {} seconds",
+            MIN_INTERVAL_SECS
+        ));
+    }
+    if check.timeout_secs == 0 || check.timeout_secs >= check.interval_secs {
+        return Err("Timeout must be between 1 second and the interval".to_string());
+    }
+    if check.failure_threshold == 0 {
+        return Err("Failure threshold must be at least 1".to_string());
+    }
+
+    match &check.probe {
+        Probe::Http { url, .. } => {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err("HTTP probe URL must start with http:// or https://".to_string());
+            }
+        }
+        Probe::Tcp { host, port } => {
+            if host.is_empty() || *port == 0 {
+                return Err("TCP probe needs a host and port".to_string());
+            }
+        }
+        Probe::Command { command } => {
+            if command.is_empty() || command[0].is_empty() {
+                return Err("Command probe needs a command".to_string());
+            }
+        }
+    }
+
+    for action in &check.actions {
+        if let RemediationAction::Hook { command } = action {
+            if command.is_empty() || command[0].is_empty() {
+                return Err("Hook action needs a command".to_string());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_probe(
+    client: &reqwest::Client,
+    probe: &Probe,
+    timeout: Duration,
+) -> Result<(), String> {
+    match probe {
+        Probe::Http { url, expect_status } => {
+            let res = client
+                .get(url)
+                .timeout(timeout)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            let status = res.status();
+            match expect_status {
+                Some(code) if status.as_u16() != *code => {
+                    Err(format!("Expected HTTP {}, got {}", code, status))
+                }
+                None if !status.is_success() => Err(format!("HTTP {}", status)),
+                _ => Ok(()),
+            }
+        }
+        Probe::Tcp { host, port } => {
+            match tokio::time::timeout(
+                timeout,
+                tokio::net::TcpStream::connect((host.as_str(), *port)),
+            )
+            .await
+            {
+                Ok(Ok(_)) => Ok(()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("Connection timed out".to_string()),
+            }
+        }
+        Probe::Command { command } => {
+            let child = tokio::process::Command::new(&command[0])
+                .args(&command[1..])
+                .kill_on_drop(true)
+                .output();
+            match tokio::time::timeout(timeout, child).await {
+                Ok(Ok(o)) if o.status.success() => Ok(()),
+                Ok(Ok(o)) => Err(format!(
+                    "Exited with {}: {}",
+                    o.status,
+                    String::from_utf8_lossy(&o.stderr).trim()
+                )),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err("Command timed out".to_string()),
+            }
+        }
+    }
+}
+
+// Remediation re-arms every `failure_threshold` consecutive failures, so a
+// restart that did not help is retried and alerted on again.
+fn should_remediate(failures: u32, threshold: u32) -> bool {
+    failures > 0 && failures.is_multiple_of(threshold)
+}
+
+async fn remediate(
+    check: &HealthCheck,
+    failures: u32,
+    error: &str,
+    alerts: &AlertLog,
+    commands: bool,
+) {
+    for action in &check.actions {
+        match action {
+            RemediationAction::Restart => {
+                let result = tokio::process::Command::new("systemctl")
+                    .arg("restart")
+                    .arg(&check.service)
+                    .output()
+                    .await;
+                match result {
+                    Ok(o) if o.status.success() => {
+                        log::info!("Health check restarted {}", check.service)
+                    }
+                    Ok(o) => log::error!(
+                        "Health check failed to restart {}: {}",
+                        check.service,
+                        String::from_utf8_lossy(&o.stderr).trim()
+                    ),
+                    Err(e) => log::error!("Failed to execute systemctl: {}", e),
+                }
+            }
+            RemediationAction::Hook { .. } if !commands => {
+                log::warn!(
+                    "Skipped health check hook for {}: Developer Mode is disabled",
+                    check.service
+                );
+            }
+            RemediationAction::Hook { command } => {
+                let result = tokio::process::Command::new(&command[0])
+                    .args(&command[1..])
+                    .env("WADM_SERVICE", &check.service)
+                    .env("WADM_PROBE_ERROR", error)
+                    .output()
+                    .await;
+                if let Err(e) = result {
+                    log::error!("Health check hook for {} failed: {}", check.service, e);
+                }
+            }
+            RemediationAction::Alert => alerts.push(
+                "health",
+                &check.service,
+                "critical",
+                format!("Health check failed {} times in a row: {}", failures, error),
+            ),
+        }
+    }
+}
+
+async fn run_check_loop(
+    check: HealthCheck,
+    monitor: web::Data<HealthMonitor>,
+    alerts: web::Data<AlertLog>,
+    config: web::Data<Mutex<AppConfig>>,
+) {
+    let interval = Duration::from_secs(check.interval_secs);
+    let timeout = Duration::from_secs(check.timeout_secs);
+    let mut ticker = tokio::time::interval(interval);
+    let alerts_enabled = check
+        .actions
+        .iter()
+        .any(|a| matches!(a, RemediationAction::Alert));
+
+    loop {
+        ticker.tick().await;
+
+        let commands = developer_mode(&config);
+        if matches!(check.probe, Probe::Command { .. }) && !commands {
+            continue;
+        }
+
+        let started = Instant::now();
+        let outcome = run_probe(&monitor.client, &check.probe, timeout).await;
+        let (previous_failures, failures) = monitor.record(
+            &check.service,
+            ProbeResult {
+                timestamp: Utc::now().to_rfc3339(),
+                healthy: outcome.is_ok(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                error: outcome.as_ref().err().cloned(),
+            },
+        );
+
+        match outcome {
+            Err(e) if should_remediate(failures, check.failure_threshold) => {
+                remediate(&check, failures, &e, &alerts, commands).await
+            }
+            Ok(_) if previous_failures >= check.failure_threshold && alerts_enabled => {
+                alerts.push(
+                    "health",
+                    &check.service,
+                    "info",
+                    "Health check recovered".to_string(),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn schedule(
+    monitor: &web::Data<HealthMonitor>,
+    alerts: &web::Data<AlertLog>,
+    config: &web::Data<Mutex<AppConfig>>,
+    check: HealthCheck,
+) {
+    let service = check.service.clone();
+    let handle = tokio::spawn(run_check_loop(
+        check,
+        monitor.clone(),
+        alerts.clone(),
+        config.clone(),
+    ));
+    if let Some(old) = monitor.tasks.lock().unwrap().insert(service, handle) {
+        old.abort();
+    }
+}
+
+pub fn start_health_checks(
+    monitor: &web::Data<HealthMonitor>,
+    alerts: &web::Data<AlertLog>,
+    config: &web::Data<Mutex<AppConfig>>,
+) {
+    let checks: Vec<HealthCheck> = monitor.checks.lock().unwrap().values().cloned().collect();
+    for check in checks {
+        schedule(monitor, alerts, config, check);
+    }
+}
+
+pub async fn list_checks(monitor: web::Data<HealthMonitor>) -> impl Responder {
+    let checks: Vec<HealthCheck> = monitor.checks.lock().unwrap().values().cloned().collect();
+    let info: Vec<HealthCheckInfo> = checks
+        .into_iter()
+        .map(|check| HealthCheckInfo {
+            status: monitor
+                .summary(&check.service)
+                .unwrap_or_else(|| ProbeStatus::default().summary()),
+            check,
+        })
+        .collect();
+
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn set_check(
+    path: web::Path<String>,
+    body: web::Json<HealthCheck>,
+    monitor: web::Data<HealthMonitor>,
+    alerts: web::Data<AlertLog>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> impl Responder {
+    let mut check = body.into_inner();
+    check.service = path.into_inner();
+
+    if let Err(e) = validate_check(&check) {
+        return HttpResponse::BadRequest().json(e);
+    }
+    check.service = service_key(&check.service);
+    if runs_commands(&check) && !developer_mode(&config) {
+        log::warn!("Attempted to save a command health check without Developer Mode enabled.");
+        return HttpResponse::Forbidden().json("Command probes and hooks require Developer Mode");
+    }
+
+    monitor
+        .checks
+        .lock()
+        .unwrap()
+        .insert(check.service.clone(), check.clone());
+    monitor.status.lock().unwrap().remove(&check.service);
+    if let Err(e) = monitor.save() {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    let service = check.service.clone();
+    schedule(&monitor, &alerts, &config, check);
+
+    HttpResponse::Ok().json(format!("Health check for {} saved", service))
+}
+
+pub async fn delete_check(
+    path: web::Path<String>,
+    monitor: web::Data<HealthMonitor>,
+) -> impl Responder {
+    let service = service_key(&path);
+
+    if monitor.checks.lock().unwrap().remove(&service).is_none() {
+        return HttpResponse::NotFound().json(format!("No health check for {}", service));
+    }
+    if let Some(handle) = monitor.tasks.lock().unwrap().remove(&service) {
+        handle.abort();
+    }
+    monitor.status.lock().unwrap().remove(&service);
+    if let Err(e) = monitor.save() {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    HttpResponse::Ok().json(format!("Health check for {} removed", service))
+}
+
+pub async fn get_history(
+    path: web::Path<String>,
+    monitor: web::Data<HealthMonitor>,
+) -> impl Responder {
+    let service = service_key(&path);
+    let status = monitor.status.lock().unwrap();
+
+    match status.get(&service) {
+        Some(s) => HttpResponse::Ok().json(&s.history),
+        None => HttpResponse::Ok().json(Vec::<ProbeResult>::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_are_keyed_by_the_full_service_name() {
+        assert_eq!(service_key("nginx"), "nginx.service");
+        assert_eq!(service_key("nginx.service"), "nginx.service");
+        assert_eq!(service_key("getty@tty1"), "getty@tty1.service");
+    }
+
+    #[test]
+    fn command_probes_and_hooks_are_gated() {
+        let check: HealthCheck = serde_json::from_value(serde_json::json!({
+            "probe": { "type": "tcp", "host": "127.0.0.1", "port": 80 },
+            "actions": [{ "type": "restart" }],
+        }))
+        .unwrap();
+        assert!(!runs_commands(&check));
+
+        let hook = HealthCheck {
+            actions: vec![RemediationAction::Hook {
+                command: vec!["/usr/local/bin/notify".to_string()],
+            }],
+            ..check.clone()
+        };
+        assert!(runs_commands(&hook));
+
+        let probe = HealthCheck {
+            probe: Probe::Command {
+                command: vec!["pg_isready".to_string()],
+            },
+            ..check
+        };
+        assert!(runs_commands(&probe));
+    }
+
+    #[test]
+    fn remediation_repeats_while_the_service_keeps_failing() {
+        let fired: Vec<u32> = (0..=10).filter(|&f| should_remediate(f, 3)).collect();
+        assert_eq!(fired, [3, 6, 9]);
+        assert!(should_remediate(1, 1) && should_remediate(2, 1));
+    }
+}
@@ -0,0 +1,386 @@
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
+use actix_ws::AggregatedMessage;
+use chrono::{TimeZone, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::api::auth::verify_token;
+use crate::api::config::AppConfig;
+use crate::api::systemd;
+use crate::api::user_units::authorize_account;
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+const SEVERITIES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+#[derive(Deserialize, Default)]
+pub struct JournalQuery {
+    #[serde(default)]
+    pub token: String,
+    pub unit: Option<String>,
+    pub user: Option<String>,
+    pub priority: Option<u8>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub boot: Option<String>,
+    pub grep: Option<String>,
+    pub before: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct JournalEntry {
+    pub cursor: String,
+    pub timestamp: String,
+    pub timestamp_us: u64,
+    pub unit: String,
+    pub identifier: String,
+    pub pid: Option<u32>,
+    pub priority: u8,
+    pub severity: String,
+    pub boot_id: String,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct JournalPage {
+    pub entries: Vec<JournalEntry>,
+    pub before: Option<String>,
+}
+
+fn field_string(entry: &Value, key: &str) -> String {
+    match entry.get(key) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(bytes)) => {
+            let raw: Vec<u8> = bytes
+                .iter()
+                .filter_map(|b| b.as_u64().map(|b| b as u8))
+                .collect();
+            String::from_utf8_lossy(&raw).to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+pub fn parse_entry(line: &str) -> Option<JournalEntry> {
+    let entry: Value = serde_json::from_str(line).ok()?;
+
+    let timestamp_us = field_string(&entry, "__REALTIME_TIMESTAMP")
+        .parse::<u64>()
+        .unwrap_or(0);
+    let timestamp = Utc
+        .timestamp_micros(timestamp_us as i64)
+        .single()
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_default();
+
+    let priority = field_string(&entry, "PRIORITY").parse::<u8>().unwrap_or(6);
+    let mut unit = field_string(&entry, "_SYSTEMD_UNIT");
+    if unit.is_empty() {
+        unit = field_string(&entry, "_SYSTEMD_USER_UNIT");
+    }
+    let mut identifier = field_string(&entry, "SYSLOG_IDENTIFIER");
+    if identifier.is_empty() {
+        identifier = field_string(&entry, "_COMM");
+    }
+
+    Some(JournalEntry {
+        cursor: field_string(&entry, "__CURSOR"),
+        timestamp,
+        timestamp_us,
+        unit,
+        identifier,
+        pid: field_string(&entry, "_PID").parse().ok(),
+        priority,
+        severity: SEVERITIES
+            .get(priority as usize)
+            .unwrap_or(&"info")
+            .to_string(),
+        boot_id: field_string(&entry, "_BOOT_ID"),
+        message: field_string(&entry, "MESSAGE"),
+    })
+}
+
+fn is_valid_boot_id(boot: &str) -> bool {
+    let offset = boot.strip_prefix('-').unwrap_or(boot);
+    (!offset.is_empty() && offset.len() <= 4 && offset.chars().all(|c| c.is_ascii_digit()))
+        || (boot.len() == 32 && boot.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// Every value is passed as a `--flag=value` or `FIELD=value` pair so user input
+// can never be read as an additional journalctl option. With a uid the query is
+// scoped to that account's user manager.
+pub fn filter_args(query: &JournalQuery, uid: Option<u32>) -> Result<Vec<String>, String> {
+    let mut args = vec![
+        "--output=json".to_string(),
+        "--no-pager".to_string(),
+        "--quiet".to_string(),
+    ];
+
+    if let Some(unit) = &query.unit {
+        if !systemd::is_valid_unit_name(unit) {
+            return Err(format!("Invalid unit name: {}", unit));
+        }
+        match uid {
+            Some(_) => args.push(format!("_SYSTEMD_USER_UNIT={}", unit)),
+            None => args.push(format!("--unit={}", unit)),
+        }
+    }
+    if let Some(uid) = uid {
+        args.push(format!("_UID={}", uid));
+    }
+
+    if let Some(priority) = query.priority {
+        if priority > 7 {
+            return Err("Priority must be between 0 and 7".to_string());
+        }
+        args.push(format!("--priority={}", priority));
+    }
+
+    for (flag, value) in [("--since", &query.since), ("--until", &query.until)] {
+        if let Some(value) = value {
+            if systemd::has_control_chars(value) {
+                return Err(format!("Invalid time value: {}", value));
+            }
+            args.push(format!("{}={}", flag, value));
+        }
+    }
+
+    if let Some(boot) = &query.boot {
+        if !is_valid_boot_id(boot) {
+            return Err(format!("Invalid boot ID: {}", boot));
+        }
+        args.push(format!("--boot={}", boot));
+    }
+
+    if let Some(grep) = &query.grep {
+        if !grep.is_empty() {
+            args.push(format!("--grep={}", grep));
+            args.push("--case-sensitive=false".to_string());
+        }
+    }
+
+    Ok(args)
+}
+
+pub fn read_page(query: &JournalQuery, uid: Option<u32>) -> Result<JournalPage, String> {
+    let mut args = filter_args(query, uid)?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    args.push("--reverse".to_string());
+    match &query.before {
+        Some(cursor) => {
+            args.push(format!("--cursor={}", cursor));
+            args.push(format!("--lines={}", limit + 1));
+        }
+        None => args.push(format!("--lines={}", limit)),
+    }
+
+    let output = Command::new("journalctl")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to execute journalctl: {}", e))?;
+
+    // journalctl exits with 1 when --grep matches nothing.
+    if !output.status.success() && !output.stderr.is_empty() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut entries: Vec<JournalEntry> = stdout.lines().filter_map(parse_entry).collect();
+
+    if let Some(cursor) = &query.before {
+        if entries.first().map(|e| &e.cursor) == Some(cursor) {
+            entries.remove(0);
+        }
+    }
+    entries.truncate(limit);
+
+    let before = if entries.len() == limit {
+        entries.last().map(|e| e.cursor.clone())
+    } else {
+        None
+    };
+    entries.reverse();
+
+    Ok(JournalPage { entries, before })
+}
+
+fn resolve_user(
+    query: &JournalQuery,
+    config: &Mutex<AppConfig>,
+) -> Result<Option<u32>, HttpResponse> {
+    match &query.user {
+        Some(user) => authorize_account(config, user).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub async fn get_journal(
+    query: web::Query<JournalQuery>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> impl Responder {
+    let uid = match resolve_user(&query, &config) {
+        Ok(uid) => uid,
+        Err(res) => return res,
+    };
+
+    match read_page(&query, uid) {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => HttpResponse::BadRequest().json(e),
+    }
+}
+
+async fn follow_journal(
+    req: HttpRequest,
+    stream: web::Payload,
+    args: Vec<String>,
+) -> Result<HttpResponse, Error> {
+    let (res, mut session, stream) = actix_ws::handle(&req, stream)?;
+    let mut stream = stream.aggregate_continuations();
+
+    let child = tokio::process::Command::new("journalctl")
+        .args(&args)
+        .arg("--follow")
+        .arg("--lines=50")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn();
+
+    let mut child = match child {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = session
+                .close(Some(actix_ws::CloseReason {
+                    code: actix_ws::CloseCode::Error,
+                    description: Some(format!("Failed to execute journalctl: {}", e)),
+                }))
+                .await;
+            return Ok(res);
+        }
+    };
+
+    let stdout = child.stdout.take().expect("journalctl stdout is piped");
+
+    actix_web::rt::spawn(async move {
+        let mut lines = BufReader::new(stdout).lines();
+
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let line = match line {
+                        Ok(Some(line)) => line,
+                        _ => break,
+                    };
+                    if let Some(entry) = parse_entry(&line) {
+                        let json = serde_json::to_string(&entry).unwrap_or_default();
+                        if session.text(json).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+
+                Some(msg) = stream.next() => {
+                    match msg {
+                        Ok(AggregatedMessage::Ping(msg)) => {
+                            let _ = session.pong(&msg).await;
+                        }
+                        Ok(AggregatedMessage::Close(_)) | Err(_) => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let _ = child.kill().await;
+        let _ = session.close(None).await;
+    });
+
+    Ok(res)
+}
+
+pub async fn ws_journal(
+    req: HttpRequest,
+    stream: web::Payload,
+    query: web::Query<JournalQuery>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> Result<HttpResponse, Error> {
+    if !verify_token(&query.token) {
+        return Ok(HttpResponse::Unauthorized().body("Invalid token"));
+    }
+
+    let uid = match resolve_user(&query, &config) {
+        Ok(uid) => uid,
+        Err(res) => return Ok(res),
+    };
+
+    let args = match filter_args(&query, uid) {
+        Ok(args) => args,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(e)),
+    };
+
+    follow_journal(req, stream, args).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_parsed_from_journalctl_json() {
+        // (line, unit, identifier, pid, severity, message)
+        let cases = [
+            (
+                r#"{"__CURSOR":"s=1","__REALTIME_TIMESTAMP":"1760781600000000","_SYSTEMD_UNIT":"nginx.service","SYSLOG_IDENTIFIER":"nginx","_PID":"812","PRIORITY":"3","_BOOT_ID":"abc","MESSAGE":"bind() failed"}"#,
+                "nginx.service",
+                "nginx",
+                Some(812),
+                "err",
+                "bind() failed",
+            ),
+            (
+                r#"{"_SYSTEMD_USER_UNIT":"sync.service","_COMM":"rsync","MESSAGE":"done"}"#,
+                "sync.service",
+                "rsync",
+                None,
+                "info",
+                "done",
+            ),
+            (
+                r#"{"SYSLOG_IDENTIFIER":"kernel","PRIORITY":"9","MESSAGE":[104,105,255]}"#,
+                "",
+                "kernel",
+                None,
+                "info",
+                "hi\u{fffd}",
+            ),
+        ];
+
+        for (line, unit, identifier, pid, severity, message) in cases {
+            let entry = parse_entry(line).unwrap();
+            assert_eq!(entry.unit, unit, "{}", line);
+            assert_eq!(entry.identifier, identifier, "{}", line);
+            assert_eq!(entry.pid, pid, "{}", line);
+            assert_eq!(entry.severity, severity, "{}", line);
+            assert_eq!(entry.message, message, "{}", line);
+        }
+
+        let entry = parse_entry(cases[0].0).unwrap();
+        assert_eq!(entry.cursor, "s=1");
+        assert_eq!(entry.timestamp, "2025-10-18T10:00:00+00:00");
+        assert_eq!(entry.priority, 3);
+
+        assert!(parse_entry("-- No entries --").is_none());
+    }
+}
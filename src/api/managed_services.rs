@@ -0,0 +1,414 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::api::systemd;
+
+const MANAGED_SERVICES_FILE: &str = "wadm-services.json";
+
+const RESTART_POLICIES: &[&str] = &[
+    "no",
+    "on-success",
+    "on-failure",
+    "on-abnormal",
+    "on-watchdog",
+    "on-abort",
+    "always",
+];
+
+const SERVICE_TYPES: &[&str] = &["simple", "exec", "forking", "oneshot", "notify", "idle"];
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ResourceLimits {
+    #[serde(default)]
+    pub memory_max: Option<String>,
+    #[serde(default)]
+    pub cpu_quota: Option<u32>,
+    #[serde(default)]
+    pub tasks_max: Option<u32>,
+    #[serde(default)]
+    pub nofile: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ServiceSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub exec_start: String,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub working_directory: Option<String>,
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    #[serde(default = "default_restart")]
+    pub restart: String,
+    #[serde(default)]
+    pub restart_sec: Option<u32>,
+    #[serde(default = "default_service_type")]
+    pub service_type: String,
+    #[serde(default)]
+    pub limits: ResourceLimits,
+}
+
+fn default_restart() -> String {
+    "on-failure".to_string()
+}
+
+fn default_service_type() -> String {
+    "simple".to_string()
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ManagedServices {
+    pub services: BTreeMap<String, ServiceSpec>,
+}
+
+impl ManagedServices {
+    pub fn is_managed(&self, unit: &str) -> bool {
+        self.services.contains_key(unit)
+    }
+}
+
+pub fn load_managed_services() -> ManagedServices {
+    match fs::read_to_string(MANAGED_SERVICES_FILE) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => ManagedServices::default(),
+    }
+}
+
+fn save_managed_services(store: &ManagedServices) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(MANAGED_SERVICES_FILE, content)
+        .map_err(|e| format!("Failed to save managed services: {}", e))
+}
+
+pub fn unit_file_name(name: &str) -> String {
+    if name.ends_with(".service") {
+        name.to_string()
+    } else {
+        format!("{}.service", name)
+    }
+}
+
+fn user_exists(user: &str) -> bool {
+    Command::new("id")
+        .arg("-u")
+        .arg("--")
+        .arg(user)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+fn is_valid_memory_size(value: &str) -> bool {
+    if value == "infinity" {
+        return true;
+    }
+    let digits = value.trim_end_matches(['K', 'M', 'G', 'T']);
+    !digits.is_empty()
+        && digits.len() + 1 >= value.len()
+        && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+pub fn validate_spec(spec: &ServiceSpec) -> Result<(), String> {
+    let unit = unit_file_name(&spec.name);
+    if !systemd::is_valid_unit_name(&unit) || spec.name.contains('@') {
+        return Err(format!("Invalid service name: {}", spec.name));
+    }
+
+    let exec = spec.exec_start.trim();
+    if exec.is_empty() {
+        return Err("Exec command cannot be empty".to_string());
+    }
+    if systemd::has_control_chars(exec) || systemd::has_control_chars(&spec.description) {
+        return Err("Exec command and description must be a single line".to_string());
+    }
+    let program = exec.split_whitespace().next().unwrap_or_default();
+    if !program.starts_with('/') {
+        return Err("Exec command must start with an absolute path".to_string());
+    }
+
+    if let Some(user) = &spec.user {
+        if user.is_empty()
+            || user.starts_with('-')
+            || !user
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+        {
+            return Err(format!("Invalid user: {}", user));
+        }
+        if !user_exists(user) {
+            return Err(format!("User {} does not exist", user));
+        }
+    }
+
+    if let Some(dir) = &spec.working_directory {
+        if systemd::has_control_chars(dir) || !dir.starts_with('/') {
+            return Err("Working directory must be an absolute path".to_string());
+        }
+        if !Path::new(dir).is_dir() {
+            return Err(format!("Working directory {} does not exist", dir));
+        }
+    }
+
+    for (key, value) in &spec.environment {
+        if !is_valid_env_key(key) {
+            return Err(format!("Invalid environment variable name: {}", key));
+        }
+        if systemd::has_control_chars(value) {
+            return Err(format!(
+                "Environment variable {} must be a single line",
+                key
+            ));
+        }
+    }
+
+    if !RESTART_POLICIES.contains(&spec.restart.as_str()) {
+        return Err(format!("Invalid restart policy: {}", spec.restart));
+    }
+    if !SERVICE_TYPES.contains(&spec.service_type.as_str()) {
+        return Err(format!("Invalid service type: {}", spec.service_type));
+    }
+
+    if let Some(mem) = &spec.limits.memory_max {
+        if !is_valid_memory_size(mem) {
+            return Err(format!("Invalid memory limit: {}", mem));
+        }
+    }
+    if spec.limits.cpu_quota == Some(0) {
+        return Err("CPU quota must be greater than 0".to_string());
+    }
+
+    Ok(())
+}
+
+fn escape_value(value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    systemd::escape_specifiers(&value)
+}
+
+pub fn render_unit(spec: &ServiceSpec) -> String {
+    let mut unit = String::new();
+
+    unit.push_str("[Unit]\n");
+    let description = if spec.description.is_empty() {
+        spec.name.as_str()
+    } else {
+        spec.description.as_str()
+    };
+    unit.push_str(&format!(
+        "Description={}\n",
+        systemd::escape_specifiers(description)
+    ));
+    unit.push_str("After=network.target\n");
+    unit.push_str("X-ManagedBy=WADM\n\n");
+
+    unit.push_str("[Service]\n");
+    unit.push_str(&format!("Type={}\n", spec.service_type));
+    unit.push_str(&format!(
+        "ExecStart={}\n",
+        systemd::escape_specifiers(spec.exec_start.trim())
+    ));
+    if let Some(user) = &spec.user {
+        unit.push_str(&format!("User={}\n", user));
+    }
+    if let Some(dir) = &spec.working_directory {
+        unit.push_str(&format!(
+            "WorkingDirectory={}\n",
+            systemd::escape_specifiers(dir)
+        ));
+    }
+    for (key, value) in &spec.environment {
+        unit.push_str(&format!(
+            "Environment=\"{}={}\"\n",
+            key,
+            escape_value(value)
+        ));
+    }
+    unit.push_str(&format!("Restart={}\n", spec.restart));
+    if let Some(sec) = spec.restart_sec {
+        unit.push_str(&format!("RestartSec={}\n", sec));
+    }
+    if let Some(mem) = &spec.limits.memory_max {
+        unit.push_str(&format!("MemoryMax={}\n", mem));
+    }
+    if let Some(quota) = spec.limits.cpu_quota {
+        unit.push_str(&format!("CPUQuota={}%\n", quota));
+    }
+    if let Some(tasks) = spec.limits.tasks_max {
+        unit.push_str(&format!("TasksMax={}\n", tasks));
+    }
+    if let Some(nofile) = spec.limits.nofile {
+        unit.push_str(&format!("LimitNOFILE={}\n", nofile));
+    }
+
+    unit.push_str("\n[Install]\n");
+    unit.push_str("WantedBy=multi-user.target\n");
+
+    unit
+}
+
+fn deploy_unit(spec: &ServiceSpec) -> Result<String, String> {
+    validate_spec(spec)?;
+    let unit = unit_file_name(&spec.name);
+    let content = render_unit(spec);
+    systemd::verify_unit(&unit, &content)?;
+    systemd::install_unit(&unit, &content)?;
+    Ok(unit)
+}
+
+pub async fn list_managed_services(data: web::Data<Mutex<ManagedServices>>) -> impl Responder {
+    let store = data.lock().unwrap();
+    let specs: Vec<&ServiceSpec> = store.services.values().collect();
+    HttpResponse::Ok().json(specs)
+}
+
+pub async fn create_service(
+    body: web::Json<ServiceSpec>,
+    data: web::Data<Mutex<ManagedServices>>,
+) -> impl Responder {
+    let spec = body.into_inner();
+    if let Err(e) = validate_spec(&spec) {
+        return HttpResponse::BadRequest().json(e);
+    }
+    let unit = unit_file_name(&spec.name);
+
+    let mut store = data.lock().unwrap();
+    if store.is_managed(&unit) {
+        return HttpResponse::Conflict().json(format!("Service {} already exists", unit));
+    }
+    match systemd::find_unit(&unit) {
+        Ok(None) => {}
+        Ok(Some(path)) => {
+            return HttpResponse::Conflict()
+                .json(format!("Service {} already exists at {}", unit, path))
+        }
+        Err(e) => return HttpResponse::InternalServerError().json(e),
+    }
+
+    if let Err(e) = deploy_unit(&spec) {
+        return HttpResponse::BadRequest().json(e);
+    }
+
+    store.services.insert(unit.clone(), spec);
+    if let Err(e) = save_managed_services(&store) {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    match systemd::systemctl(&["enable", "--now", &unit]) {
+        Ok(_) => HttpResponse::Ok().json(format!("Service {} created", unit)),
+        Err(e) => HttpResponse::InternalServerError().json(format!(
+            "Service {} created but failed to start: {}",
+            unit, e
+        )),
+    }
+}
+
+pub async fn update_service(
+    path: web::Path<String>,
+    body: web::Json<ServiceSpec>,
+    data: web::Data<Mutex<ManagedServices>>,
+) -> impl Responder {
+    let unit = unit_file_name(&path.into_inner());
+    let mut spec = body.into_inner();
+    spec.name = unit.trim_end_matches(".service").to_string();
+
+    let mut store = data.lock().unwrap();
+    if !store.is_managed(&unit) {
+        return HttpResponse::NotFound().json(format!("Service {} is not managed by WADM", unit));
+    }
+
+    if let Err(e) = deploy_unit(&spec) {
+        return HttpResponse::BadRequest().json(e);
+    }
+
+    store.services.insert(unit.clone(), spec);
+    if let Err(e) = save_managed_services(&store) {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    match systemd::systemctl(&["try-restart", &unit]) {
+        Ok(_) => HttpResponse::Ok().json(format!("Service {} updated", unit)),
+        Err(e) => HttpResponse::InternalServerError().json(format!(
+            "Service {} updated but failed to restart: {}",
+            unit, e
+        )),
+    }
+}
+
+pub async fn delete_service(
+    path: web::Path<String>,
+    data: web::Data<Mutex<ManagedServices>>,
+) -> impl Responder {
+    let unit = unit_file_name(&path.into_inner());
+
+    let mut store = data.lock().unwrap();
+    if !store.is_managed(&unit) {
+        return HttpResponse::NotFound().json(format!("Service {} is not managed by WADM", unit));
+    }
+
+    if let Err(e) = systemd::systemctl(&["disable", "--now", &unit]) {
+        log::warn!("Failed to disable {}: {}", unit, e);
+    }
+    if let Err(e) = systemd::remove_unit(&unit) {
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    store.services.remove(&unit);
+    if let Err(e) = save_managed_services(&store) {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    HttpResponse::Ok().json(format!("Service {} removed", unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_signs_are_not_expanded_as_specifiers() {
+        let spec: ServiceSpec = serde_json::from_value(serde_json::json!({
+            "name": "report",
+            "description": "Disk at 90% full",
+            "exec_start": "/usr/bin/date +%Y-%m-%d",
+            "environment": { "FORMAT": "%s" },
+        }))
+        .unwrap();
+
+        let unit = render_unit(&spec);
+        assert!(unit.contains("Description=Disk at 90%% full\n"));
+        assert!(unit.contains("ExecStart=/usr/bin/date +%%Y-%%m-%%d\n"));
+        assert!(unit.contains("Environment=\"FORMAT=%%s\"\n"));
+    }
+
+    #[test]
+    fn users_cannot_be_read_as_options() {
+        let spec: ServiceSpec = serde_json::from_value(serde_json::json!({
+            "name": "report",
+            "exec_start": "/usr/bin/true",
+            "user": "--help",
+        }))
+        .unwrap();
+        assert_eq!(validate_spec(&spec).unwrap_err(), "Invalid user: --help");
+    }
+}
@@ -1,15 +1,23 @@
 use actix_web::web;
 
+pub mod alerts;
+pub mod analyze;
 pub mod auth;
 pub mod config;
 pub mod db;
 pub mod docker;
 pub mod firewall;
+pub mod health;
+pub mod journal;
+pub mod managed_services;
 pub mod monitor;
 pub mod pkgmgr;
 pub mod services;
 pub mod system;
+pub mod systemd;
 pub mod terminal;
+pub mod timers;
+pub mod user_units;
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     
@@ -20,6 +28,7 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 
     cfg.service(web::resource("/stats").route(web::get().to(monitor::get_system_stats)));
     cfg.service(web::resource("/system").route(web::get().to(system::get_detailed_info)));
+    cfg.service(web::resource("/system/boot").route(web::get().to(analyze::get_boot_analysis)));
     cfg.service(web::resource("/system/reboot").route(web::post().to(system::reboot_system)));
     cfg.service(web::resource("/processes").route(web::get().to(monitor::get_processes)));
     cfg.service(web::resource("/processes/kill").route(web::post().to(monitor::kill_process)));
@@ -27,26 +36,258 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(web::resource("/packages/upgrade").route(web::post().to(pkgmgr::upgrade_package)));
     cfg.service(web::resource("/packages/install").route(web::post().to(pkgmgr::install_package)));
     cfg.service(web::resource("/services").route(web::get().to(services::list_services)));
+    cfg.service(
+        web::resource("/services/managed")
+            .route(web::get().to(managed_services::list_managed_services))
+            .route(web::post().to(managed_services::create_service)),
+    );
+    cfg.service(
+        web::resource("/services/managed/{name}")
+            .route(web::put().to(managed_services::update_service))
+            .route(web::delete().to(managed_services::delete_service)),
+    );
     cfg.service(web::resource("/services/{name}").route(web::post().to(services::control_service)));
+    cfg.service(
+        web::resource("/services/{name}/dependencies")
+            .route(web::get().to(analyze::get_dependencies)),
+    );
     cfg.service(
         web::resource("/services/{name}/logs").route(web::get().to(services::get_service_logs)),
     );
+    cfg.service(web::resource("/healthchecks").route(web::get().to(health::list_checks)));
+    cfg.service(
+        web::resource("/healthchecks/{service}")
+            .route(web::put().to(health::set_check))
+            .route(web::delete().to(health::delete_check)),
+    );
+    cfg.service(
+        web::resource("/healthchecks/{service}/history").route(web::get().to(health::get_history)),
+    );
+    cfg.service(
+        web::resource("/alerts")
+            .route(web::get().to(alerts::list_alerts))
+            .route(web::delete().to(alerts::clear_alerts)),
+    );
+    cfg.service(
+        web::resource("/users/{user}/units").route(web::get().to(user_units::list_user_units)),
+    );
+    cfg.service(
+        web::resource("/users/{user}/units/{name}")
+            .route(web::post().to(user_units::control_user_unit)),
+    );
+    cfg.service(
+        web::resource("/users/{user}/linger")
+            .route(web::get().to(user_units::get_linger))
+            .route(web::post().to(user_units::set_linger)),
+    );
+    cfg.service(
+        web::resource("/timers")
+            .route(web::get().to(timers::list_timers))
+            .route(web::post().to(timers::create_timer)),
+    );
+    cfg.service(web::resource("/timers/managed").route(web::get().to(timers::list_managed_timers)));
+    cfg.service(
+        web::resource("/timers/{name}")
+            .route(web::put().to(timers::update_timer))
+            .route(web::delete().to(timers::delete_timer)),
+    );
+    cfg.service(
+        web::resource("/timers/{name}/action").route(web::post().to(timers::control_timer)),
+    );
+    cfg.service(web::resource("/journal").route(web::get().to(journal::get_journal)));
     cfg.service(web::resource("/docker").route(web::get().to(docker::list_containers)));
     cfg.service(web::resource("/docker/status").route(web::get().to(docker::get_status)));
     cfg.service(web::resource("/docker/start").route(web::post().to(docker::start_service)));
-    cfg.service(web::resource("/docker/{id}").route(web::post().to(docker::control_container)));
+    cfg.service(web::resource("/docker/stacks").route(web::get().to(docker::compose::list_stacks)));
+    cfg.service(
+        web::resource("/docker/stacks/{name}")
+            .route(web::get().to(docker::compose::get_stack))
+            .route(web::put().to(docker::compose::save_stack))
+            .route(web::delete().to(docker::compose::delete_stack)),
+    );
+    cfg.service(
+        web::resource("/docker/stacks/{name}/action")
+            .route(web::post().to(docker::compose::control_stack)),
+    );
+    cfg.service(
+        web::resource("/docker/stacks/{name}/history/{version}")
+            .route(web::get().to(docker::compose::get_stack_version)),
+    );
+    cfg.service(web::resource("/docker/stats").route(web::get().to(docker::stats::list_stats)));
+    cfg.service(web::resource("/docker/events").route(web::get().to(docker::events::list_events)));
+    cfg.service(
+        web::resource("/docker/volumes")
+            .route(web::get().to(docker::volumes::list_volumes))
+            .route(web::post().to(docker::volumes::create_volume)),
+    );
+    cfg.service(
+        web::resource("/docker/volumes/{name}")
+            .route(web::get().to(docker::volumes::inspect_volume))
+            .route(web::delete().to(docker::volumes::remove_volume)),
+    );
+    cfg.service(
+        web::resource("/docker/networks")
+            .route(web::get().to(docker::networks::list_networks))
+            .route(web::post().to(docker::networks::create_network)),
+    );
+    cfg.service(
+        web::resource("/docker/networks/{id}")
+            .route(web::get().to(docker::networks::inspect_network))
+            .route(web::delete().to(docker::networks::remove_network)),
+    );
+    cfg.service(
+        web::resource("/docker/networks/{id}/connect")
+            .route(web::post().to(docker::networks::connect_container)),
+    );
+    cfg.service(
+        web::resource("/docker/networks/{id}/disconnect")
+            .route(web::post().to(docker::networks::disconnect_container)),
+    );
+    cfg.service(
+        web::resource("/docker/containers")
+            .route(web::post().to(docker::containers::create_container)),
+    );
+    cfg.service(
+        web::resource("/docker/registries")
+            .route(web::get().to(docker::registries::list_registries))
+            .route(web::post().to(docker::registries::save_registry)),
+    );
+    cfg.service(
+        web::resource("/docker/registries/test")
+            .route(web::post().to(docker::registries::test_registry)),
+    );
+    cfg.service(
+        web::resource("/docker/registries/{registry}")
+            .route(web::delete().to(docker::registries::remove_registry)),
+    );
+    cfg.service(web::resource("/docker/images").route(web::get().to(docker::images::list_images)));
+    cfg.service(
+        web::resource("/docker/images/pull").route(web::post().to(docker::images::pull_image)),
+    );
+    cfg.service(
+        web::resource("/docker/images/prune").route(web::post().to(docker::images::prune_images)),
+    );
+    cfg.service(
+        web::resource("/docker/images/{id}").route(web::delete().to(docker::images::remove_image)),
+    );
     cfg.service(
-        web::resource("/docker/{id}/stats").route(web::get().to(docker::get_container_stats)),
+        web::resource("/docker/images/{id}/tag").route(web::post().to(docker::images::tag_image)),
+    );
+    cfg.service(
+        web::resource("/docker/images/{id}/history")
+            .route(web::get().to(docker::images::get_image_history)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}")
+            .route(web::post().to(docker::control_container))
+            .route(web::delete().to(docker::containers::remove_container)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/inspect")
+            .route(web::get().to(docker::containers::inspect_container)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/rename")
+            .route(web::post().to(docker::containers::rename_container)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/update")
+            .route(web::put().to(docker::containers::update_container)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/logs").route(web::get().to(docker::logs::get_container_logs)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/recreate")
+            .route(web::post().to(docker::containers::recreate_container)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/stats")
+            .route(web::get().to(docker::stats::get_container_stats)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/files").route(web::get().to(docker::files::list_files)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/files/download")
+            .route(web::get().to(docker::files::download_files)),
+    );
+    cfg.service(
+        web::resource("/docker/{id}/files/upload")
+            .app_data(web::PayloadConfig::new(docker::files::MAX_UPLOAD_SIZE))
+            .route(web::post().to(docker::files::upload_files)),
     );
 
     cfg.service(web::resource("/firewall").route(web::get().to(firewall::get_status)));
     cfg.service(web::resource("/firewall/action").route(web::post().to(firewall::set_status)));
-    cfg.service(web::resource("/firewall/install").route(web::post().to(firewall::install_ufw)));
+    cfg.service(
+        web::resource("/firewall/install").route(web::post().to(firewall::install_firewall)),
+    );
     cfg.service(
         web::resource("/firewall/rules")
             .route(web::post().to(firewall::add_rule))
             .route(web::delete().to(firewall::delete_rule)),
     );
+    cfg.service(
+        web::resource("/firewall/zones").route(web::get().to(firewall::firewalld::list_zones)),
+    );
+    cfg.service(
+        web::resource("/firewall/nftables").route(web::get().to(firewall::nftables::list_ruleset)),
+    );
+    cfg.service(
+        web::resource("/firewall/policy").route(web::post().to(firewall::policy::set_policy)),
+    );
+    cfg.service(
+        web::resource("/firewall/logging").route(web::post().to(firewall::policy::set_logging)),
+    );
+    cfg.service(
+        web::resource("/firewall/profiles")
+            .route(web::get().to(firewall::policy::list_profiles))
+            .route(web::post().to(firewall::policy::save_profile)),
+    );
+    cfg.service(
+        web::resource("/firewall/profiles/{name}")
+            .route(web::delete().to(firewall::policy::delete_profile)),
+    );
+    cfg.service(
+        web::resource("/firewall/export").route(web::get().to(firewall::ruleset::export_ruleset)),
+    );
+    cfg.service(
+        web::resource("/firewall/import").route(web::post().to(firewall::ruleset::import_ruleset)),
+    );
+    cfg.service(
+        web::resource("/firewall/import/preview")
+            .route(web::post().to(firewall::ruleset::preview_import)),
+    );
+    cfg.service(
+        web::resource("/firewall/templates")
+            .route(web::get().to(firewall::ruleset::list_templates)),
+    );
+    cfg.service(
+        web::resource("/firewall/templates/{name}")
+            .route(web::get().to(firewall::ruleset::get_template))
+            .route(web::post().to(firewall::ruleset::save_template))
+            .route(web::delete().to(firewall::ruleset::delete_template)),
+    );
+    cfg.service(
+        web::resource("/firewall/blocked").route(web::get().to(firewall::blocked::list_blocked)),
+    );
+    cfg.service(
+        web::resource("/firewall/blocked/deny")
+            .route(web::post().to(firewall::blocked::deny_source)),
+    );
+    cfg.service(
+        web::resource("/firewall/rules/{number}")
+            .route(web::delete().to(firewall::delete_rule_number)),
+    );
+    cfg.service(
+        web::resource("/firewall/confirm/{id}")
+            .route(web::post().to(firewall::rollback::confirm_change)),
+    );
+    cfg.service(
+        web::resource("/firewall/rollback/{id}")
+            .route(web::post().to(firewall::rollback::rollback_change)),
+    );
 
     cfg.service(web::resource("/db").route(web::get().to(db::list_dbs)));
 
@@ -58,4 +299,9 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     );
     
     cfg.service(web::resource("/terminal/ws").to(terminal::ws_terminal));
+    cfg.service(web::resource("/journal/ws").to(journal::ws_journal));
+    cfg.service(web::resource("/docker/events/ws").to(docker::events::ws_events));
+    cfg.service(web::resource("/docker/stats/ws").to(docker::stats::ws_stats));
+    cfg.service(web::resource("/docker/{id}/exec/ws").to(docker::exec::ws_container_exec));
+    cfg.service(web::resource("/docker/{id}/logs/ws").to(docker::logs::ws_container_logs));
 }
@@ -3,6 +3,8 @@ use serde::Serialize;
 use std::sync::Mutex;
 use sysinfo::{Disks, Networks, ProcessesToUpdate, System};
 
+use crate::api::docker::DockerClient;
+
 #[derive(Serialize)]
 pub struct SystemStats {
     pub cpu_usage: f32,
@@ -62,7 +64,7 @@ fn count_services(state: &str) -> u32 {
     use std::process::Command;
     
     let output = Command::new("systemctl")
-        .args(&[
+        .args([
             "list-units",
             "--type=service",
             &format!("--state={}", state),
@@ -80,21 +82,12 @@ fn count_services(state: &str) -> u32 {
     }
 }
 
-fn count_containers() -> u32 {
-    use std::process::Command;
-    
-    let output = Command::new("docker").args(&["ps", "-q"]).output();
+pub async fn get_system_stats(
+    data: web::Data<AppState>,
+    client: web::Data<DockerClient>,
+) -> impl Responder {
+    let active_containers = client.count_running().await;
 
-    match output {
-        Ok(out) => {
-            let stdout = String::from_utf8_lossy(&out.stdout);
-            stdout.lines().count() as u32
-        }
-        Err(_) => 0,
-    }
-}
-
-pub async fn get_system_stats(data: web::Data<AppState>) -> impl Responder {
     let mut sys = data.sys.lock().unwrap();
     sys.refresh_all();
 
@@ -131,8 +124,6 @@ pub async fn get_system_stats(data: web::Data<AppState>) -> impl Responder {
     let failed_services = count_services("failed");
 
     
-    let active_containers = count_containers();
-    
     let upgradable_packages = crate::api::pkgmgr::count_upgradable_packages();
 
     
@@ -78,7 +78,7 @@ fn list_packages_pacman() -> Result<Vec<Package>, String> {
         .filter_map(|line| {
             
             let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 1 {
+            if !parts.is_empty() {
                 let name = parts[0].to_string();
                 let version = if parts.len() >= 4 {
                     parts[3].to_string()
@@ -348,7 +348,7 @@ fn list_installed_packages_apt() -> Result<Vec<Package>, String> {
 fn list_installed_packages_dnf() -> Result<Vec<Package>, String> {
     
     let output = Command::new("dnf")
-        .args(&["list", "installed", "-q"]) 
+        .args(["list", "installed", "-q"]) 
         .output()
         .map_err(|e| e.to_string())?;
 
@@ -408,7 +408,7 @@ fn remove_package_dry_run_impl(manager: &ManagerType, name: &str) -> Result<Stri
         ManagerType::Pacman => {
             
             let output = Command::new("pacman")
-                .args(&["-Rns", name, "-p"])
+                .args(["-Rns", name, "-p"])
                 .output()
                 .map_err(|e| e.to_string())?;
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -416,7 +416,7 @@ fn remove_package_dry_run_impl(manager: &ManagerType, name: &str) -> Result<Stri
         ManagerType::Apt => {
             
             let output = Command::new("apt-get")
-                .args(&["remove", "-s", name])
+                .args(["remove", "-s", name])
                 .output()
                 .map_err(|e| e.to_string())?;
             Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -425,7 +425,7 @@ fn remove_package_dry_run_impl(manager: &ManagerType, name: &str) -> Result<Stri
             
             
             let output = Command::new("dnf")
-                .args(&["remove", name, "--assumeno"])
+                .args(["remove", name, "--assumeno"])
                 .output()
                 .map_err(|e| e.to_string())?;
             
@@ -1,12 +1,19 @@
 use actix_web::{web, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
 use std::process::Command;
+use std::sync::Mutex;
+
+use crate::api::health::{HealthMonitor, HealthSummary};
+use crate::api::managed_services::ManagedServices;
 
 #[derive(Serialize)]
-struct Service {
+pub struct Service {
     name: String,
     status: String, 
     description: String,
+    managed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<HealthSummary>,
 }
 
 #[derive(Deserialize)]
@@ -14,7 +21,7 @@ pub struct ServiceControl {
     action: String, 
 }
 
-pub async fn list_services() -> impl Responder {
+pub fn collect_services(scope: &[String], is_managed: impl Fn(&str) -> bool) -> Vec<Service> {
     use std::collections::HashSet;
 
     let mut services = Vec::new();
@@ -23,7 +30,8 @@ pub async fn list_services() -> impl Responder {
     
     
     if let Ok(output) = Command::new("systemctl")
-        .args(&[
+        .args(scope)
+        .args([
             "list-units",
             "--type=service",
             "--all",
@@ -55,6 +63,8 @@ pub async fn list_services() -> impl Responder {
                 };
 
                 services.push(Service {
+                    managed: is_managed(&name),
+                    health: None,
                     name,
                     status: nice_status.to_string(),
                     description: parts[4..].join(" "),
@@ -66,7 +76,8 @@ pub async fn list_services() -> impl Responder {
     
     
     if let Ok(output) = Command::new("systemctl")
-        .args(&[
+        .args(scope)
+        .args([
             "list-unit-files",
             "--type=service",
             "--no-pager",
@@ -92,6 +103,8 @@ pub async fn list_services() -> impl Responder {
                 
                 
                 services.push(Service {
+                    managed: is_managed(&name),
+                    health: None,
                     name,
                     status: "Stopped".to_string(),
                     description: "Available (Not Loaded)".to_string(),
@@ -103,6 +116,19 @@ pub async fn list_services() -> impl Responder {
     
     services.sort_by(|a, b| a.name.cmp(&b.name));
 
+    services
+}
+
+pub async fn list_services(
+    managed: web::Data<Mutex<ManagedServices>>,
+    health: web::Data<HealthMonitor>,
+) -> impl Responder {
+    let managed = managed.lock().unwrap();
+    let mut services = collect_services(&[], |name| managed.is_managed(name));
+    for service in &mut services {
+        service.health = health.summary(&service.name);
+    }
+
     HttpResponse::Ok().json(services)
 }
 
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+pub const UNIT_DIR: &str = "/etc/systemd/system";
+
+pub fn systemctl(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("systemctl")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute systemctl: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+pub fn is_valid_unit_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 255
+        && !name.starts_with('.')
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.@\\".contains(c))
+}
+
+pub fn has_control_chars(value: &str) -> bool {
+    value.chars().any(|c| c.is_control())
+}
+
+// systemd expands `%` specifiers in most unit settings, so user supplied text
+// has them doubled to stay literal.
+pub fn escape_specifiers(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+// Splits `systemctl show` output for several units into one property map per unit.
+pub fn parse_show_output(output: &str) -> Vec<HashMap<String, String>> {
+    let mut units = Vec::new();
+    let mut current = HashMap::new();
+
+    for line in output.lines() {
+        if line.is_empty() {
+            if !current.is_empty() {
+                units.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            current.insert(key.to_string(), value.to_string());
+        }
+    }
+    if !current.is_empty() {
+        units.push(current);
+    }
+
+    units
+}
+
+pub fn show_units(
+    units: &[String],
+    props: &[&str],
+) -> Result<Vec<HashMap<String, String>>, String> {
+    if units.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let args = show_args(units, props);
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    systemctl(&args).map(|out| parse_show_output(&out))
+}
+
+// Dependencies reach units such as `-.mount`, so the names follow `--` to keep
+// systemctl from reading them as options.
+fn show_args(units: &[String], props: &[&str]) -> Vec<String> {
+    let mut args = vec![
+        "show".to_string(),
+        format!("--property={}", props.join(",")),
+        "--no-pager".to_string(),
+        "--".to_string(),
+    ];
+    args.extend(units.iter().cloned());
+    args
+}
+
+// Runs `systemd-analyze verify` against a scratch copy of the unit so a broken
+// unit never lands in the live unit directory.
+pub fn verify_unit(file_name: &str, content: &str) -> Result<(), String> {
+    let available = Command::new("which")
+        .arg("systemd-analyze")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if !available {
+        return Ok(());
+    }
+
+    let dir = std::env::temp_dir().join(format!("wadm-verify-{:016x}", rand::random::<u64>()));
+    fs::create_dir(&dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let path = dir.join(file_name);
+    fs::write(&path, content).map_err(|e| format!("Failed to write temp unit: {}", e))?;
+
+    let output = Command::new("systemd-analyze")
+        .arg("verify")
+        .arg(&path)
+        .output();
+    let _ = fs::remove_dir_all(&dir);
+
+    match output {
+        Ok(o) if o.status.success() => Ok(()),
+        Ok(o) => Err(format!(
+            "Unit verification failed: {}",
+            String::from_utf8_lossy(&o.stderr).trim()
+        )),
+        Err(e) => Err(format!("Failed to execute systemd-analyze: {}", e)),
+    }
+}
+
+pub fn unit_path(file_name: &str) -> std::path::PathBuf {
+    Path::new(UNIT_DIR).join(file_name)
+}
+
+// Looks a unit up the way systemd does, across every unit directory, and
+// returns where it is defined. Checking UNIT_DIR alone would let a new unit
+// shadow a vendor unit from /lib/systemd/system.
+pub fn find_unit(file_name: &str) -> Result<Option<String>, String> {
+    let path = unit_path(file_name);
+    if path.exists() {
+        return Ok(Some(path.display().to_string()));
+    }
+
+    let units = show_units(&[file_name.to_string()], &["LoadState", "FragmentPath"])?;
+    Ok(units
+        .first()
+        .and_then(|props| loaded_from(file_name, props)))
+}
+
+fn loaded_from(file_name: &str, props: &HashMap<String, String>) -> Option<String> {
+    match props.get("LoadState").map(|s| s.as_str()) {
+        Some("not-found") => None,
+        _ => Some(
+            props
+                .get("FragmentPath")
+                .filter(|p| !p.is_empty())
+                .cloned()
+                .unwrap_or_else(|| file_name.to_string()),
+        ),
+    }
+}
+
+pub fn install_unit(file_name: &str, content: &str) -> Result<(), String> {
+    fs::write(unit_path(file_name), content)
+        .map_err(|e| format!("Failed to write unit file: {}", e))?;
+    systemctl(&["daemon-reload"]).map(|_| ())
+}
+
+pub fn remove_unit(file_name: &str) -> Result<(), String> {
+    let path = unit_path(file_name);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove unit file: {}", e))?;
+    }
+    systemctl(&["daemon-reload"]).map(|_| ())
+}
+
+// Checks a calendar expression or time span with `systemd-analyze calendar|timespan`.
+pub fn check_time_expression(kind: &str, value: &str) -> Result<(), String> {
+    if value.trim().is_empty() || has_control_chars(value) {
+        return Err(format!("Invalid {} expression: {:?}", kind, value));
+    }
+
+    match Command::new("systemd-analyze")
+        .arg(kind)
+        .arg("--")
+        .arg(value)
+        .output()
+    {
+        Ok(o) if o.status.success() => Ok(()),
+        Ok(o) => Err(format!(
+            "Invalid {} expression {:?}: {}",
+            kind,
+            value,
+            String::from_utf8_lossy(&o.stderr).trim()
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to execute systemd-analyze: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_names_cannot_be_read_as_options() {
+        let units = vec!["-.mount".to_string(), "nginx.service".to_string()];
+        assert_eq!(
+            show_args(&units, &["Id", "Wants"]),
+            [
+                "show",
+                "--property=Id,Wants",
+                "--no-pager",
+                "--",
+                "-.mount",
+                "nginx.service"
+            ]
+        );
+
+        let output = "Id=-.mount\nWants=\n\nId=nginx.service\nWants=network-online.target\n";
+        let parsed = parse_show_output(output);
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0]["Id"], "-.mount");
+        assert_eq!(parsed[1]["Wants"], "network-online.target");
+    }
+
+    #[test]
+    fn units_are_found_wherever_systemd_loads_them() {
+        let show = |output: &str| parse_show_output(output).remove(0);
+
+        let vendor = show("LoadState=loaded\nFragmentPath=/lib/systemd/system/ssh.service\n");
+        assert_eq!(
+            loaded_from("ssh.service", &vendor).as_deref(),
+            Some("/lib/systemd/system/ssh.service")
+        );
+
+        let masked = show("LoadState=masked\nFragmentPath=\n");
+        assert_eq!(
+            loaded_from("apt-daily.timer", &masked).as_deref(),
+            Some("apt-daily.timer")
+        );
+
+        let missing = show("LoadState=not-found\nFragmentPath=\n");
+        assert_eq!(loaded_from("report.service", &missing), None);
+    }
+}
@@ -105,9 +105,10 @@ pub async fn ws_terminal(
                 Some(msg) = stream.next() => {
                     match msg {
                         Ok(AggregatedMessage::Binary(bin)) => {
-                             if writer.write_all(&bin).is_err() {
-                                 break;
-                             }
+                            if let Err(e) = writer.write_all(&bin) {
+                                log::warn!("Failed to write to terminal: {}", e);
+                                break;
+                            }
                         }
                         Ok(AggregatedMessage::Text(text)) => {
                             if text.starts_with("RESIZE:") {
@@ -0,0 +1,434 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::sync::Mutex;
+
+use crate::api::systemd;
+
+const MANAGED_TIMERS_FILE: &str = "wadm-timers.json";
+
+const TIMER_PROPERTIES: &[&str] = &[
+    "Id",
+    "Description",
+    "Triggers",
+    "NextElapseUSecRealtime",
+    "LastTriggerUSec",
+    "ActiveState",
+    "UnitFileState",
+];
+
+const TRIGGERED_PROPERTIES: &[&str] = &["Id", "Result", "ActiveState", "ExecMainStatus"];
+
+#[derive(Serialize)]
+pub struct TimerInfo {
+    pub name: String,
+    pub description: String,
+    pub unit: String,
+    pub next_trigger: Option<String>,
+    pub last_trigger: Option<String>,
+    pub last_result: Option<String>,
+    pub last_exit_status: Option<i32>,
+    pub active: bool,
+    pub enabled: String,
+    pub managed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimerSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub on_calendar: Vec<String>,
+    #[serde(default)]
+    pub on_boot_sec: Option<String>,
+    #[serde(default)]
+    pub on_unit_active_sec: Option<String>,
+    #[serde(default)]
+    pub randomized_delay_sec: Option<String>,
+    #[serde(default)]
+    pub persistent: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ManagedTimers {
+    pub timers: BTreeMap<String, TimerSpec>,
+}
+
+#[derive(Deserialize)]
+pub struct TimerAction {
+    pub action: String,
+}
+
+pub fn load_managed_timers() -> ManagedTimers {
+    match fs::read_to_string(MANAGED_TIMERS_FILE) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => ManagedTimers::default(),
+    }
+}
+
+fn save_managed_timers(store: &ManagedTimers) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(MANAGED_TIMERS_FILE, content)
+        .map_err(|e| format!("Failed to save managed timers: {}", e))
+}
+
+fn timer_file_name(name: &str) -> String {
+    if name.ends_with(".timer") {
+        name.to_string()
+    } else {
+        format!("{}.timer", name)
+    }
+}
+
+fn target_unit(spec: &TimerSpec) -> String {
+    match &spec.unit {
+        Some(unit) if !unit.is_empty() => unit.clone(),
+        _ => format!("{}.service", spec.name.trim_end_matches(".timer")),
+    }
+}
+
+fn non_empty(value: Option<&String>) -> Option<String> {
+    value
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && *v != "n/a" && *v != "0")
+        .map(|v| v.to_string())
+}
+
+fn list_timer_names() -> Vec<String> {
+    let mut names = BTreeSet::new();
+
+    let listings = [
+        vec![
+            "list-units",
+            "--type=timer",
+            "--all",
+            "--no-pager",
+            "--no-legend",
+            "--plain",
+            "--full",
+        ],
+        vec![
+            "list-unit-files",
+            "--type=timer",
+            "--no-pager",
+            "--no-legend",
+            "--plain",
+        ],
+    ];
+
+    for args in listings {
+        if let Ok(out) = systemd::systemctl(&args) {
+            for line in out.lines() {
+                if let Some(name) = line.split_whitespace().next() {
+                    if name.ends_with(".timer") {
+                        names.insert(name.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    names.into_iter().collect()
+}
+
+pub fn validate_timer(spec: &TimerSpec) -> Result<(), String> {
+    let timer = timer_file_name(&spec.name);
+    if !systemd::is_valid_unit_name(&timer) || spec.name.contains('@') {
+        return Err(format!("Invalid timer name: {}", spec.name));
+    }
+
+    let unit = target_unit(spec);
+    if !systemd::is_valid_unit_name(&unit) || !unit.contains('.') {
+        return Err(format!("Invalid target unit: {}", unit));
+    }
+    if systemd::has_control_chars(&spec.description) {
+        return Err("Description must be a single line".to_string());
+    }
+
+    if spec.on_calendar.is_empty()
+        && spec.on_boot_sec.is_none()
+        && spec.on_unit_active_sec.is_none()
+    {
+        return Err("Timer needs at least one schedule".to_string());
+    }
+    for expr in &spec.on_calendar {
+        systemd::check_time_expression("calendar", expr)?;
+    }
+    for span in [
+        &spec.on_boot_sec,
+        &spec.on_unit_active_sec,
+        &spec.randomized_delay_sec,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        systemd::check_time_expression("timespan", span)?;
+    }
+
+    Ok(())
+}
+
+pub fn render_timer(spec: &TimerSpec) -> String {
+    let mut unit = String::new();
+
+    unit.push_str("[Unit]\n");
+    let description = if spec.description.is_empty() {
+        spec.name.as_str()
+    } else {
+        spec.description.as_str()
+    };
+    unit.push_str(&format!(
+        "Description={}\n",
+        systemd::escape_specifiers(description)
+    ));
+    unit.push_str("X-ManagedBy=WADM\n\n");
+
+    unit.push_str("[Timer]\n");
+    unit.push_str(&format!("Unit={}\n", target_unit(spec)));
+    for expr in &spec.on_calendar {
+        unit.push_str(&format!("OnCalendar={}\n", expr.trim()));
+    }
+    if let Some(span) = &spec.on_boot_sec {
+        unit.push_str(&format!("OnBootSec={}\n", span.trim()));
+    }
+    if let Some(span) = &spec.on_unit_active_sec {
+        unit.push_str(&format!("OnUnitActiveSec={}\n", span.trim()));
+    }
+    if let Some(span) = &spec.randomized_delay_sec {
+        unit.push_str(&format!("RandomizedDelaySec={}\n", span.trim()));
+    }
+    if spec.persistent {
+        unit.push_str("Persistent=true\n");
+    }
+
+    unit.push_str("\n[Install]\n");
+    unit.push_str("WantedBy=timers.target\n");
+
+    unit
+}
+
+// The timer only starts its target, so the target has to be a unit systemd
+// can already load.
+fn check_target(spec: &TimerSpec) -> Result<(), String> {
+    let unit = target_unit(spec);
+    match systemd::find_unit(&unit)? {
+        Some(_) => Ok(()),
+        None => Err(format!("Target unit {} does not exist", unit)),
+    }
+}
+
+fn deploy_timer(spec: &TimerSpec) -> Result<String, String> {
+    validate_timer(spec)?;
+    check_target(spec)?;
+    let timer = timer_file_name(&spec.name);
+    let content = render_timer(spec);
+    systemd::verify_unit(&timer, &content)?;
+    systemd::install_unit(&timer, &content)?;
+    Ok(timer)
+}
+
+pub async fn list_timers(data: web::Data<Mutex<ManagedTimers>>) -> impl Responder {
+    let names = list_timer_names();
+    let timers = match systemd::show_units(&names, TIMER_PROPERTIES) {
+        Ok(t) => t,
+        Err(e) => return HttpResponse::InternalServerError().json(e),
+    };
+
+    let triggered: Vec<String> = timers
+        .iter()
+        .filter_map(|t| t.get("Triggers"))
+        .flat_map(|t| t.split_whitespace().map(|u| u.to_string()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let results: HashMap<String, HashMap<String, String>> =
+        systemd::show_units(&triggered, TRIGGERED_PROPERTIES)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|props| props.get("Id").cloned().map(|id| (id, props)))
+            .collect();
+
+    let store = data.lock().unwrap();
+    let info: Vec<TimerInfo> = timers
+        .into_iter()
+        .map(|props| {
+            let name = props.get("Id").cloned().unwrap_or_default();
+            let unit = props
+                .get("Triggers")
+                .and_then(|t| t.split_whitespace().next())
+                .unwrap_or_default()
+                .to_string();
+            let target = results.get(&unit);
+
+            TimerInfo {
+                managed: store.timers.contains_key(&name),
+                description: props.get("Description").cloned().unwrap_or_default(),
+                next_trigger: non_empty(props.get("NextElapseUSecRealtime")),
+                last_trigger: non_empty(props.get("LastTriggerUSec")),
+                last_result: target.and_then(|t| non_empty(t.get("Result"))),
+                last_exit_status: target
+                    .and_then(|t| t.get("ExecMainStatus"))
+                    .and_then(|s| s.parse().ok()),
+                active: props.get("ActiveState").map(|s| s == "active") == Some(true),
+                enabled: props.get("UnitFileState").cloned().unwrap_or_default(),
+                name,
+                unit,
+            }
+        })
+        .collect();
+
+    HttpResponse::Ok().json(info)
+}
+
+pub async fn list_managed_timers(data: web::Data<Mutex<ManagedTimers>>) -> impl Responder {
+    let store = data.lock().unwrap();
+    let specs: Vec<&TimerSpec> = store.timers.values().collect();
+    HttpResponse::Ok().json(specs)
+}
+
+pub async fn create_timer(
+    body: web::Json<TimerSpec>,
+    data: web::Data<Mutex<ManagedTimers>>,
+) -> impl Responder {
+    let spec = body.into_inner();
+    if let Err(e) = validate_timer(&spec) {
+        return HttpResponse::BadRequest().json(e);
+    }
+    let timer = timer_file_name(&spec.name);
+
+    let mut store = data.lock().unwrap();
+    if store.timers.contains_key(&timer) {
+        return HttpResponse::Conflict().json(format!("Timer {} already exists", timer));
+    }
+    match systemd::find_unit(&timer) {
+        Ok(None) => {}
+        Ok(Some(path)) => {
+            return HttpResponse::Conflict()
+                .json(format!("Timer {} already exists at {}", timer, path))
+        }
+        Err(e) => return HttpResponse::InternalServerError().json(e),
+    }
+
+    if let Err(e) = deploy_timer(&spec) {
+        return HttpResponse::BadRequest().json(e);
+    }
+
+    store.timers.insert(timer.clone(), spec);
+    if let Err(e) = save_managed_timers(&store) {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    match systemd::systemctl(&["enable", "--now", &timer]) {
+        Ok(_) => HttpResponse::Ok().json(format!("Timer {} created", timer)),
+        Err(e) => HttpResponse::InternalServerError().json(format!(
+            "Timer {} created but failed to start: {}",
+            timer, e
+        )),
+    }
+}
+
+pub async fn update_timer(
+    path: web::Path<String>,
+    body: web::Json<TimerSpec>,
+    data: web::Data<Mutex<ManagedTimers>>,
+) -> impl Responder {
+    let timer = timer_file_name(&path.into_inner());
+    let mut spec = body.into_inner();
+    spec.name = timer.trim_end_matches(".timer").to_string();
+
+    let mut store = data.lock().unwrap();
+    if !store.timers.contains_key(&timer) {
+        return HttpResponse::NotFound().json(format!("Timer {} is not managed by WADM", timer));
+    }
+
+    if let Err(e) = deploy_timer(&spec) {
+        return HttpResponse::BadRequest().json(e);
+    }
+
+    store.timers.insert(timer.clone(), spec);
+    if let Err(e) = save_managed_timers(&store) {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    match systemd::systemctl(&["try-restart", &timer]) {
+        Ok(_) => HttpResponse::Ok().json(format!("Timer {} updated", timer)),
+        Err(e) => HttpResponse::InternalServerError().json(format!(
+            "Timer {} updated but failed to restart: {}",
+            timer, e
+        )),
+    }
+}
+
+pub async fn delete_timer(
+    path: web::Path<String>,
+    data: web::Data<Mutex<ManagedTimers>>,
+) -> impl Responder {
+    let timer = timer_file_name(&path.into_inner());
+
+    let mut store = data.lock().unwrap();
+    if !store.timers.contains_key(&timer) {
+        return HttpResponse::NotFound().json(format!("Timer {} is not managed by WADM", timer));
+    }
+
+    if let Err(e) = systemd::systemctl(&["disable", "--now", &timer]) {
+        log::warn!("Failed to disable {}: {}", timer, e);
+    }
+    if let Err(e) = systemd::remove_unit(&timer) {
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    store.timers.remove(&timer);
+    if let Err(e) = save_managed_timers(&store) {
+        log::error!("{}", e);
+        return HttpResponse::InternalServerError().json(e);
+    }
+
+    HttpResponse::Ok().json(format!("Timer {} removed", timer))
+}
+
+pub async fn control_timer(
+    path: web::Path<String>,
+    body: web::Json<TimerAction>,
+) -> impl Responder {
+    let timer = timer_file_name(&path.into_inner());
+    if !systemd::is_valid_unit_name(&timer) {
+        return HttpResponse::BadRequest().json("Invalid timer name");
+    }
+
+    let (result, verb) = match body.action.as_str() {
+        "enable" => (systemd::systemctl(&["enable", "--now", &timer]), "enabled"),
+        "disable" => (
+            systemd::systemctl(&["disable", "--now", &timer]),
+            "disabled",
+        ),
+        "run" => {
+            let unit = systemd::show_units(std::slice::from_ref(&timer), &["Triggers"])
+                .ok()
+                .and_then(|units| units.into_iter().next())
+                .and_then(|props| props.get("Triggers").cloned())
+                .and_then(|t| t.split_whitespace().next().map(|u| u.to_string()));
+            match unit {
+                Some(unit) => (
+                    systemd::systemctl(&["start", "--no-block", &unit]),
+                    "triggered",
+                ),
+                None => {
+                    return HttpResponse::NotFound()
+                        .json(format!("Timer {} has no linked unit", timer))
+                }
+            }
+        }
+        _ => return HttpResponse::BadRequest().json("Invalid action"),
+    };
+
+    match result {
+        Ok(_) => HttpResponse::Ok().json(format!("Timer {} {}", timer, verb)),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
@@ -0,0 +1,152 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::api::config::AppConfig;
+use crate::api::services::collect_services;
+use crate::api::systemd;
+
+const LINGER_DIR: &str = "/var/lib/systemd/linger";
+const MIN_USER_UID: u32 = 1000;
+
+#[derive(Deserialize)]
+pub struct UserUnitControl {
+    pub action: String,
+}
+
+#[derive(Deserialize)]
+pub struct LingerRequest {
+    pub enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct LingerStatus {
+    pub user: String,
+    pub uid: u32,
+    pub linger: bool,
+}
+
+fn lookup_uid(user: &str) -> Option<u32> {
+    let output = Command::new("id").arg("-u").arg(user).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+// Operators may only touch accounts listed in `user_unit_accounts`, and never
+// root or system accounts even when listed.
+pub fn authorize_account(config: &Mutex<AppConfig>, user: &str) -> Result<u32, HttpResponse> {
+    let allowed = config
+        .lock()
+        .unwrap()
+        .user_unit_accounts
+        .iter()
+        .any(|a| a == user);
+    if !allowed {
+        log::warn!("Rejected user unit access for account {}", user);
+        return Err(HttpResponse::Forbidden().json(format!(
+            "Account {} is not allowed for user unit management",
+            user
+        )));
+    }
+
+    match lookup_uid(user) {
+        Some(uid) if uid >= MIN_USER_UID => Ok(uid),
+        Some(_) => Err(HttpResponse::Forbidden().json("System accounts cannot be managed")),
+        None => Err(HttpResponse::NotFound().json(format!("User {} does not exist", user))),
+    }
+}
+
+fn user_scope(user: &str) -> Vec<String> {
+    vec!["--user".to_string(), format!("--machine={}@.host", user)]
+}
+
+pub async fn list_user_units(
+    path: web::Path<String>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> impl Responder {
+    let user = path.into_inner();
+    if let Err(res) = authorize_account(&config, &user) {
+        return res;
+    }
+
+    let services = collect_services(&user_scope(&user), |_| false);
+    HttpResponse::Ok().json(services)
+}
+
+pub async fn control_user_unit(
+    path: web::Path<(String, String)>,
+    body: web::Json<UserUnitControl>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> impl Responder {
+    let (user, unit) = path.into_inner();
+    if let Err(res) = authorize_account(&config, &user) {
+        return res;
+    }
+
+    let action = body.action.as_str();
+    if !["start", "stop", "restart", "enable", "disable"].contains(&action) {
+        return HttpResponse::BadRequest().json("Invalid action");
+    }
+    if !systemd::is_valid_unit_name(&unit) {
+        return HttpResponse::BadRequest().json("Invalid unit name");
+    }
+
+    let scope = user_scope(&user);
+    let mut args: Vec<&str> = scope.iter().map(|s| s.as_str()).collect();
+    args.push(action);
+    args.push(&unit);
+
+    match systemd::systemctl(&args) {
+        Ok(_) => HttpResponse::Ok().json(format!("{} {} for {}", action, unit, user)),
+        Err(e) => HttpResponse::InternalServerError().json(e),
+    }
+}
+
+pub async fn get_linger(
+    path: web::Path<String>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> impl Responder {
+    let user = path.into_inner();
+    let uid = match authorize_account(&config, &user) {
+        Ok(uid) => uid,
+        Err(res) => return res,
+    };
+
+    HttpResponse::Ok().json(LingerStatus {
+        linger: Path::new(LINGER_DIR).join(&user).exists(),
+        user,
+        uid,
+    })
+}
+
+pub async fn set_linger(
+    path: web::Path<String>,
+    body: web::Json<LingerRequest>,
+    config: web::Data<Mutex<AppConfig>>,
+) -> impl Responder {
+    let user = path.into_inner();
+    if let Err(res) = authorize_account(&config, &user) {
+        return res;
+    }
+
+    let (action, verb) = if body.enabled {
+        ("enable-linger", "enabled")
+    } else {
+        ("disable-linger", "disabled")
+    };
+
+    match Command::new("loginctl").arg(action).arg(&user).output() {
+        Ok(o) if o.status.success() => {
+            HttpResponse::Ok().json(format!("Lingering {} for {}", verb, user))
+        }
+        Ok(o) => HttpResponse::InternalServerError()
+            .json(String::from_utf8_lossy(&o.stderr).trim().to_string()),
+        Err(e) => {
+            HttpResponse::InternalServerError().json(format!("Failed to execute loginctl: {}", e))
+        }
+    }
+}
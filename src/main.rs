@@ -10,6 +10,8 @@ use middleware::Auth;
 use api::auth::load_auth_store;
 use api::monitor::AppState;
 
+pub const PORT: u16 = 8168;
+
 async fn health_check() -> impl Responder {
     HttpResponse::Ok().json(serde_json::json!({"status": "ok"}))
 }
@@ -18,7 +20,7 @@ async fn health_check() -> impl Responder {
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
-    let port = 8168;
+    let port = PORT;
     log::info!("Starting WADM server on port {}", port);
 
     
@@ -46,6 +48,24 @@ async fn main() -> std::io::Result<()> {
     
     let app_config = web::Data::new(Mutex::new(api::config::load_config()));
 
+    let managed_services =
+        web::Data::new(Mutex::new(api::managed_services::load_managed_services()));
+
+    let managed_timers = web::Data::new(Mutex::new(api::timers::load_managed_timers()));
+
+    let alert_log = web::Data::new(api::alerts::AlertLog::default());
+    let health_monitor = web::Data::new(api::health::HealthMonitor::load());
+    api::health::start_health_checks(&health_monitor, &alert_log, &app_config);
+
+    let docker_client = web::Data::new(api::docker::DockerClient::connect());
+    let docker_registries = web::Data::new(api::docker::registries::RegistryStore::load());
+    let container_stats = web::Data::new(api::docker::stats::StatsCollector::default());
+    api::docker::stats::start_stats_collector(&docker_client, &container_stats);
+    let docker_events = web::Data::new(api::docker::events::EventLog::default());
+    let firewall_changes = web::Data::new(api::firewall::rollback::SafeApply::default());
+    let firewall_templates = web::Data::new(Mutex::new(api::firewall::ruleset::load_templates()));
+    api::docker::events::start_event_watcher(&docker_client, &docker_events, &alert_log);
+
     HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
@@ -56,6 +76,16 @@ async fn main() -> std::io::Result<()> {
             .app_data(app_state.clone())
             .app_data(auth_store.clone())
             .app_data(app_config.clone())
+            .app_data(managed_services.clone())
+            .app_data(managed_timers.clone())
+            .app_data(alert_log.clone())
+            .app_data(health_monitor.clone())
+            .app_data(docker_client.clone())
+            .app_data(container_stats.clone())
+            .app_data(docker_events.clone())
+            .app_data(docker_registries.clone())
+            .app_data(firewall_changes.clone())
+            .app_data(firewall_templates.clone())
             .wrap(cors)
             .wrap(actix_web::middleware::Logger::default())
             .route("/api/health", web::get().to(health_check))
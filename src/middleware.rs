@@ -8,6 +8,28 @@ use std::task::{Context, Poll};
 
 pub struct Auth;
 
+// WebSocket handlers check the token from the query string themselves, since
+// browsers cannot set headers on the upgrade request. Only the exact routes
+// are let through so no other endpoint can be reached without a token.
+fn is_websocket_route(path: &str) -> bool {
+    match path {
+        "/api/terminal/ws"
+        | "/api/journal/ws"
+        | "/api/docker/events/ws"
+        | "/api/docker/stats/ws" => true,
+        _ => {
+            let segments: Vec<&str> = path
+                .strip_prefix("/api/")
+                .map(|p| p.split('/').collect())
+                .unwrap_or_default();
+            matches!(
+                segments.as_slice(),
+                ["docker", id, "exec" | "logs", "ws"] if !id.is_empty()
+            )
+        }
+    }
+}
+
 impl<S, B> Transform<S, ServiceRequest> for Auth
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
@@ -55,7 +77,7 @@ where
             || path == "/api/auth/setup/init"
             || path == "/api/auth/setup/confirm"
             || path == "/api/health"
-            || path == "/api/terminal/ws"
+            || is_websocket_route(path)
         {
             let fut = self.service.call(req);
             return Box::pin(async move {
@@ -98,17 +120,50 @@ where
                 
                 
                 let fut = self.service.call(req);
-                return Box::pin(async move {
+                Box::pin(async move {
                     let res = fut.await?;
                     Ok(res.map_into_left_body())
-                });
+                })
             }
             Err(_) => {
-                return Box::pin(async move {
+                Box::pin(async move {
                     let res = HttpResponse::Unauthorized().body("Invalid token");
                     Ok(ServiceResponse::new(req.into_parts().0, res).map_into_right_body())
-                });
+                })
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_websocket_routes_skip_the_header_check() {
+        for path in [
+            "/api/terminal/ws",
+            "/api/journal/ws",
+            "/api/docker/events/ws",
+            "/api/docker/stats/ws",
+            "/api/docker/3f2a/exec/ws",
+            "/api/docker/web/logs/ws",
+        ] {
+            assert!(is_websocket_route(path), "{}", path);
+        }
+
+        for path in [
+            "/api/docker/ws",
+            "/api/docker/volumes/ws",
+            "/api/docker/stacks/ws",
+            "/api/docker/images/ws",
+            "/api/docker//exec/ws",
+            "/api/docker/web/exec/extra/ws",
+            "/api/docker/web/stats/ws",
+            "/api/docker/3f2a/exec/ws/",
+            "/api/terminal/ws/x",
+        ] {
+            assert!(!is_websocket_route(path), "{}", path);
+        }
+    }
+}
@@ -13,6 +13,7 @@ interface DockerStatus {
     installed: boolean;
     running: boolean;
     version: string;
+    runtime: string;
 }
 
 export default function Docker() {
@@ -64,28 +65,19 @@ export default function Docker() {
         let interval: number;
         if (containers.length > 0) {
             const fetchStats = async () => {
-                const runningContainers = containers.filter(c => c.state === 'running');
-                if (runningContainers.length === 0) return;
-
-                
-                const newStats: Record<string, { cpu: number, memory: number }> = {};
-
-                await Promise.all(runningContainers.map(async (c) => {
-                    try {
-                        const res = await fetch(`/api/docker/${c.id}/stats`);
-                        if (res.ok) {
-                            const data = await res.json();
-                            newStats[c.id] = {
-                                cpu: data.cpu_usage,
-                                memory: data.memory_usage
-                            };
-                        }
-                    } catch {
-                        
+                try {
+                    const res = await fetch('/api/docker/stats');
+                    if (res.ok) {
+                        const data: { id: string, cpu_usage: number, memory_usage: number }[] = await res.json();
+                        const newStats: Record<string, { cpu: number, memory: number }> = {};
+                        data.forEach(s => {
+                            newStats[s.id] = { cpu: s.cpu_usage, memory: s.memory_usage };
+                        });
+                        setStatsMap(newStats);
                     }
-                }));
-
-                setStatsMap(prev => ({ ...prev, ...newStats }));
+                } catch {
+                    
+                }
             };
 
             fetchStats();
@@ -149,27 +141,29 @@ export default function Docker() {
                 <div style={{ fontSize: '3rem', marginBottom: '1rem' }}>🐳</div>
                 <h3>Docker Not Found</h3>
                 <p style={{ color: 'var(--text-secondary)' }}>
-                    Docker does not appear to be installed on this system.
-                    Please install Docker to use this feature.
+                    Neither Docker nor Podman appears to be installed on this system.
+                    Please install one of them to use this feature.
                 </p>
             </div>
         );
     }
 
-    if (!status?.running) {
+    const runtimeLabel = status.runtime === 'podman' ? 'Podman' : 'Docker';
+
+    if (!status.running) {
         return (
             <div className="glass-panel" style={{ padding: '3rem', textAlign: 'center' }}>
                 <div style={{ fontSize: '3rem', marginBottom: '1rem', filter: 'grayscale(1)' }}>🐳</div>
-                <h3>Docker Service Stopped</h3>
+                <h3>{runtimeLabel} Service Stopped</h3>
                 <p style={{ color: 'var(--text-secondary)', marginBottom: '2rem' }}>
-                    The Docker service is installed ({status.version}) but not currently running.
+                    The {runtimeLabel} service is installed ({status.version}) but not currently running.
                 </p>
                 <button
                     className="btn-primary"
                     onClick={startService}
                     disabled={actionLoading === 'service'}
                 >
-                    {actionLoading === 'service' ? 'Starting...' : 'Start Docker Service'}
+                    {actionLoading === 'service' ? 'Starting...' : `Start ${runtimeLabel} Service`}
                 </button>
             </div>
         );
@@ -1,17 +1,147 @@
 import { useState, useEffect, useCallback } from 'react';
 import { useToast } from '../context/ToastContext';
 
+type RuleAction = 'allow' | 'deny' | 'reject' | 'limit';
+type Direction = 'in' | 'out' | 'forward';
+type Protocol = 'any' | 'tcp' | 'udp';
+
+interface FirewallRule {
+    number?: number | null;
+    action: RuleAction;
+    direction: Direction;
+    protocol: Protocol;
+    port?: string | null;
+    source?: string | null;
+    source_port?: string | null;
+    destination?: string | null;
+    interface?: string | null;
+    app?: string | null;
+    comment?: string | null;
+    v6?: boolean;
+}
+
+interface PendingChange {
+    id: string;
+    description: string;
+    reason?: string | null;
+    timeout: number;
+    expires_at: string;
+}
+
+interface Policy {
+    incoming?: RuleAction | null;
+    outgoing?: RuleAction | null;
+    routed?: RuleAction | null;
+    logging?: string | null;
+    logging_levels: string[];
+}
+
+interface AppProfile {
+    name: string;
+    title: string;
+    description: string;
+    ports: string[];
+    custom?: boolean;
+}
+
+interface PolicyUpdate {
+    direction: Direction;
+    from?: RuleAction | null;
+    to: RuleAction;
+}
+
+interface RulesetDiff {
+    enable?: boolean | null;
+    policy: PolicyUpdate[];
+    logging?: string | null;
+    add: FirewallRule[];
+    remove: FirewallRule[];
+    profiles: AppProfile[];
+    skipped: string[];
+}
+
+interface TemplateInfo {
+    name: string;
+    description?: string | null;
+    builtin: boolean;
+}
+
+interface BlockedEvent {
+    time: string;
+    source: string;
+    destination?: string | null;
+    port?: number | null;
+    protocol?: string | null;
+    interface?: string | null;
+}
+
+interface BlockedCount {
+    value: string;
+    count: number;
+}
+
+interface BlockedReport {
+    origin: string;
+    total: number;
+    events: BlockedEvent[];
+    top_sources: BlockedCount[];
+    top_ports: BlockedCount[];
+}
+
 interface FirewallStatus {
+    backend: string;
     active: boolean;
-    rules: string[];
+    rules: FirewallRule[];
     installed?: boolean;
+    policy?: Policy | null;
+    pending?: PendingChange | null;
 }
 
+const policyDirections: { key: 'incoming' | 'outgoing' | 'routed'; direction: Direction; label: string }[] = [
+    { key: 'incoming', direction: 'in', label: 'Incoming' },
+    { key: 'outgoing', direction: 'out', label: 'Outgoing' },
+    { key: 'routed', direction: 'forward', label: 'Routed' },
+];
+
+const emptyProfile: AppProfile = { name: '', title: '', description: '', ports: [] };
+
+const SAFE_APPLY_TIMEOUT = 60;
+
+const emptyRule: FirewallRule = {
+    action: 'allow',
+    direction: 'in',
+    protocol: 'tcp',
+    port: '',
+    source: '',
+    interface: '',
+    comment: '',
+};
+
+const orNull = (value?: string | null) => (value && value.trim() ? value.trim() : null);
+
+const describeTarget = (rule: FirewallRule) => {
+    if (rule.app) return rule.app;
+    const port = rule.port ? `${rule.port}${rule.protocol !== 'any' ? `/${rule.protocol}` : ''}` : rule.protocol !== 'any' ? rule.protocol : 'any port';
+    return rule.destination ? `${rule.destination} ${port}` : port;
+};
+
 function Firewall() {
     const [status, setStatus] = useState<FirewallStatus | null>(null);
     const [loading, setLoading] = useState(false);
-    const [newRule, setNewRule] = useState('');
+    const [newRule, setNewRule] = useState<FirewallRule>(emptyRule);
     const [showReboot, setShowReboot] = useState(false);
+    const [safeApply, setSafeApply] = useState(false);
+    const [pending, setPending] = useState<PendingChange | null>(null);
+    const [now, setNow] = useState(Date.now());
+    const [profiles, setProfiles] = useState<AppProfile[]>([]);
+    const [newProfile, setNewProfile] = useState<AppProfile>(emptyProfile);
+    const [profilePorts, setProfilePorts] = useState('');
+    const [templates, setTemplates] = useState<TemplateInfo[]>([]);
+    const [ruleset, setRuleset] = useState('');
+    const [replaceRules, setReplaceRules] = useState(false);
+    const [preview, setPreview] = useState<RulesetDiff | null>(null);
+    const [templateName, setTemplateName] = useState('');
+    const [blocked, setBlocked] = useState<BlockedReport | null>(null);
     const { addToast } = useToast();
 
     const fetchStatus = useCallback(() => {
@@ -20,6 +150,7 @@ function Firewall() {
             .then(res => res.json())
             .then(data => {
                 setStatus(data);
+                setPending(data.pending ?? null);
                 setLoading(false);
             })
             .catch(err => {
@@ -29,9 +160,89 @@ function Firewall() {
             });
     }, [addToast]);
 
+    const fetchProfiles = useCallback(() => {
+        fetch('/api/firewall/profiles')
+            .then(res => (res.ok ? res.json() : []))
+            .then(setProfiles)
+            .catch(console.error);
+    }, []);
+
+    const fetchTemplates = useCallback(() => {
+        fetch('/api/firewall/templates')
+            .then(res => (res.ok ? res.json() : []))
+            .then(setTemplates)
+            .catch(console.error);
+    }, []);
+
+    const fetchBlocked = useCallback(() => {
+        fetch('/api/firewall/blocked')
+            .then(res => (res.ok ? res.json() : null))
+            .then(setBlocked)
+            .catch(console.error);
+    }, []);
+
     useEffect(() => {
         fetchStatus();
-    }, [fetchStatus]);
+        fetchProfiles();
+        fetchTemplates();
+        fetchBlocked();
+    }, [fetchStatus, fetchProfiles, fetchTemplates, fetchBlocked]);
+
+    const remaining = pending ? Math.max(0, Math.ceil((Date.parse(pending.expires_at) - now) / 1000)) : 0;
+
+    useEffect(() => {
+        if (!pending) return;
+        const timer = setInterval(() => setNow(Date.now()), 1000);
+        return () => clearInterval(timer);
+    }, [pending]);
+
+    // Once the deadline passes the server has rolled the change back.
+    useEffect(() => {
+        if (pending && remaining === 0) {
+            const timer = setTimeout(fetchStatus, 1500);
+            return () => clearTimeout(timer);
+        }
+    }, [pending, remaining, fetchStatus]);
+
+    const changeUrl = (path: string) => (safeApply ? `${path}?timeout=${SAFE_APPLY_TIMEOUT}` : path);
+
+    // Changes that may cut off access come back as 202 with a pending change
+    // that is rolled back unless confirmed in time.
+    const finishChange = async (res: Response, done: string) => {
+        if (res.status === 202) {
+            const change: PendingChange = await res.json();
+            setNow(Date.now());
+            setPending(change);
+            addToast(`${change.description}. Confirm within ${change.timeout}s or it will be rolled back.`, 'warning');
+        } else {
+            addToast(done, 'success');
+        }
+        await fetchStatus();
+    };
+
+    const confirmChange = async () => {
+        if (!pending) return;
+        try {
+            const res = await fetch(`/api/firewall/confirm/${pending.id}`, { method: 'POST' });
+            if (!res.ok) throw new Error(await res.json());
+            addToast('Firewall change confirmed', 'success');
+        } catch (err) {
+            addToast(`Failed to confirm change: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+        fetchStatus();
+    };
+
+    const rollbackChange = async () => {
+        if (!pending) return;
+        try {
+            const res = await fetch(`/api/firewall/rollback/${pending.id}`, { method: 'POST' });
+            if (!res.ok) throw new Error(await res.json());
+            addToast('Firewall change rolled back', 'success');
+        } catch (err) {
+            addToast(`Failed to roll back change: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+        fetchStatus();
+    };
 
     const installFirewall = async () => {
         setLoading(true);
@@ -55,25 +266,226 @@ function Firewall() {
         if (!status) return;
         const action = status.active ? 'disable' : 'enable';
 
-        if (action === 'enable' && !confirm("Are you sure you want to enable the firewall? Ensure you have SSH allowed if remote.")) {
+        if (action === 'enable' && !confirm("Enable the firewall? It will be disabled again automatically unless you confirm that WADM and SSH are still reachable.")) {
             return;
         }
 
         try {
-            const res = await fetch('/api/firewall/action', {
+            const res = await fetch(changeUrl('/api/firewall/action'), {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify({ action })
             });
-            if (!res.ok) throw new Error('Failed to toggle firewall');
+            if (!res.ok) throw new Error(await res.json());
 
-            addToast(`Firewall ${action}d`, 'success');
             if (action === 'enable') {
                 setShowReboot(true);
             }
+            await finishChange(res, `Firewall ${action}d`);
+        } catch (err) {
+            addToast(`Failed to ${action} firewall: ${err instanceof Error ? err.message : err}`, "error");
+        }
+    };
+
+    const setPolicy = async (direction: Direction, action: RuleAction) => {
+        try {
+            const res = await fetch(changeUrl('/api/firewall/policy'), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ direction, action })
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            await finishChange(res, 'Default policy updated');
+        } catch (err) {
+            addToast(`Failed to set policy: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const setLogging = async (level: string) => {
+        try {
+            const res = await fetch('/api/firewall/logging', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ level })
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            addToast(`Logging set to ${level}`, 'success');
             fetchStatus();
-        } catch {
-            addToast("Failed to fetch firewall status", "error");
+        } catch (err) {
+            addToast(`Failed to set logging: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const allowProfile = async (profile: AppProfile) => {
+        try {
+            const res = await fetch(changeUrl('/api/firewall/rules'), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ action: 'allow', direction: 'in', protocol: 'any', app: profile.name })
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            await finishChange(res, `Allowed ${profile.name}`);
+        } catch (err) {
+            addToast(`Failed to allow ${profile.name}: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const saveProfile = async (e: React.FormEvent) => {
+        e.preventDefault();
+
+        const profile: AppProfile = {
+            ...newProfile,
+            name: newProfile.name.trim(),
+            ports: profilePorts.split('|').map(p => p.trim()).filter(Boolean),
+        };
+
+        try {
+            const res = await fetch('/api/firewall/profiles', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify(profile)
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            addToast(`Profile ${profile.name} saved`, 'success');
+            setNewProfile(emptyProfile);
+            setProfilePorts('');
+            fetchProfiles();
+        } catch (err) {
+            addToast(`Failed to save profile: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const deleteProfile = async (profile: AppProfile) => {
+        if (!confirm(`Remove profile ${profile.name}?`)) return;
+
+        try {
+            const res = await fetch(`/api/firewall/profiles/${encodeURIComponent(profile.name)}`, { method: 'DELETE' });
+            if (!res.ok) throw new Error(await res.json());
+
+            addToast(`Profile ${profile.name} removed`, 'success');
+            fetchProfiles();
+        } catch (err) {
+            addToast(`Failed to remove profile: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const exportRuleset = async (format: 'json' | 'yaml') => {
+        try {
+            const res = await fetch(`/api/firewall/export?format=${format}`);
+            if (!res.ok) throw new Error(await res.json());
+
+            const url = URL.createObjectURL(await res.blob());
+            const link = document.createElement('a');
+            link.href = url;
+            link.download = `firewall.${format}`;
+            link.click();
+            URL.revokeObjectURL(url);
+        } catch (err) {
+            addToast(`Failed to export firewall: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const loadRulesetFile = async (e: React.ChangeEvent<HTMLInputElement>) => {
+        const file = e.target.files?.[0];
+        e.target.value = '';
+        if (!file) return;
+        setRuleset(await file.text());
+        setPreview(null);
+    };
+
+    const loadTemplate = async (name: string) => {
+        try {
+            const res = await fetch(`/api/firewall/templates/${encodeURIComponent(name)}?format=yaml`);
+            if (!res.ok) throw new Error(await res.json());
+
+            setRuleset(await res.text());
+            setTemplateName(name);
+            setPreview(null);
+        } catch (err) {
+            addToast(`Failed to load template: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const previewImport = async () => {
+        try {
+            const res = await fetch(`/api/firewall/import/preview?replace=${replaceRules}`, {
+                method: 'POST',
+                body: ruleset
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            setPreview(await res.json());
+        } catch (err) {
+            setPreview(null);
+            addToast(`Failed to preview import: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const applyImport = async () => {
+        const url = changeUrl('/api/firewall/import');
+        try {
+            const res = await fetch(`${url}${url.includes('?') ? '&' : '?'}replace=${replaceRules}`, {
+                method: 'POST',
+                body: ruleset
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            setPreview(null);
+            await finishChange(res, 'Ruleset imported');
+            fetchProfiles();
+        } catch (err) {
+            addToast(`Failed to import ruleset: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const saveTemplate = async () => {
+        const name = templateName.trim();
+        try {
+            const res = await fetch(`/api/firewall/templates/${encodeURIComponent(name)}`, {
+                method: 'POST',
+                body: ruleset
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            addToast(`Template ${name} saved`, 'success');
+            fetchTemplates();
+        } catch (err) {
+            addToast(`Failed to save template: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const deleteTemplate = async (template: TemplateInfo) => {
+        if (!confirm(`Remove template ${template.name}?`)) return;
+
+        try {
+            const res = await fetch(`/api/firewall/templates/${encodeURIComponent(template.name)}`, { method: 'DELETE' });
+            if (!res.ok) throw new Error(await res.json());
+
+            addToast(`Template ${template.name} removed`, 'success');
+            fetchTemplates();
+        } catch (err) {
+            addToast(`Failed to remove template: ${err instanceof Error ? err.message : err}`, 'error');
+        }
+    };
+
+    const denySource = async (source: string) => {
+        if (!confirm(`Deny all incoming traffic from ${source}?`)) return;
+
+        try {
+            const res = await fetch(changeUrl('/api/firewall/blocked/deny'), {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ source })
+            });
+            if (!res.ok) throw new Error(await res.json());
+
+            await finishChange(res, `Denied traffic from ${source}`);
+        } catch (err) {
+            addToast(`Failed to deny ${source}: ${err instanceof Error ? err.message : err}`, 'error');
         }
     };
 
@@ -89,42 +501,46 @@ function Firewall() {
 
     const addRule = async (e: React.FormEvent) => {
         e.preventDefault();
-        if (!newRule.trim()) return;
+
+        const rule: FirewallRule = {
+            ...newRule,
+            port: orNull(newRule.port),
+            source: orNull(newRule.source),
+            interface: orNull(newRule.interface),
+            comment: orNull(newRule.comment),
+        };
 
         try {
-            const res = await fetch('/api/firewall/rules', {
+            const res = await fetch(changeUrl('/api/firewall/rules'), {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
-                body: JSON.stringify({ rule: newRule })
+                body: JSON.stringify(rule)
             });
-            if (!res.ok) throw new Error('Failed to add rule');
+            if (!res.ok) throw new Error(await res.json());
 
-            addToast("Rule added successfully", "success");
-            await fetchStatus();
-            setNewRule('');
-        } catch {
-            addToast("Failed to add rule", "error");
+            await finishChange(res, "Rule added successfully");
+            setNewRule(emptyRule);
+        } catch (err) {
+            addToast(`Failed to add rule: ${err instanceof Error ? err.message : err}`, "error");
         }
     };
 
-    const deleteRule = async (rule: string) => {
-        if (!confirm(`Delete rule: ${rule}?`)) return;
-
-        
-        
+    const deleteRule = async (rule: FirewallRule) => {
+        if (!confirm(`Delete rule: ${rule.action} ${describeTarget(rule)}?`)) return;
 
         try {
-            const res = await fetch('/api/firewall/rules', {
-                method: 'DELETE',
-                headers: { 'Content-Type': 'application/json' },
-                body: JSON.stringify({ rule: rule }) 
-            });
-            if (!res.ok) throw new Error('Failed to delete rule');
+            const res = rule.number
+                ? await fetch(changeUrl(`/api/firewall/rules/${rule.number}`), { method: 'DELETE' })
+                : await fetch(changeUrl('/api/firewall/rules'), {
+                    method: 'DELETE',
+                    headers: { 'Content-Type': 'application/json' },
+                    body: JSON.stringify(rule)
+                });
+            if (!res.ok) throw new Error(await res.json());
 
-            addToast("Rule deleted successfully", "success");
-            await fetchStatus();
-        } catch {
-            addToast("Failed to delete rule", "error");
+            await finishChange(res, "Rule deleted successfully");
+        } catch (err) {
+            addToast(`Failed to delete rule: ${err instanceof Error ? err.message : err}`, "error");
         }
     };
 
@@ -134,7 +550,7 @@ function Firewall() {
                 <div className="glass-panel" style={{ padding: '2rem', textAlign: 'center', display: 'flex', flexDirection: 'column', alignItems: 'center', gap: '1rem' }}>
                     <h2 style={{ color: 'var(--warning)' }}>Firewall Not Installed</h2>
                     <p style={{ color: 'var(--text-secondary)' }}>
-                        UFW (Uncomplicated Firewall) was not found on this system.
+                        No supported firewall (ufw, firewalld or nftables) was found on this system.
                         Would you like to install it now?
                     </p>
                     <button
@@ -142,7 +558,7 @@ function Firewall() {
                         onClick={installFirewall}
                         disabled={loading}
                     >
-                        {loading ? 'Installing...' : 'Install Firewall (UFW)'}
+                        {loading ? 'Installing...' : 'Install Firewall'}
                     </button>
                 </div>
             </div>
@@ -161,7 +577,7 @@ function Firewall() {
                 gap: '1rem'
             }}>
                 <div>
-                    <h2 style={{ margin: 0 }}>Firewall Status (UFW)</h2>
+                    <h2 style={{ margin: 0 }}>Firewall Status ({status?.backend ?? 'unknown'})</h2>
                     <div style={{
                         marginTop: '0.5rem',
                         color: status?.active ? '#4caf50' : '#ff5252',
@@ -170,28 +586,157 @@ function Firewall() {
                         {status ? (status.active ? 'ACTIVE' : 'INACTIVE') : 'Loading...'}
                     </div>
                 </div>
-                <button
-                    className={`btn ${status?.active ? 'btn-danger' : 'btn-primary'}`}
-                    onClick={toggleFirewall}
-                    disabled={loading || !status}
-                >
-                    {status?.active ? 'Disable Firewall' : 'Enable Firewall'}
-                </button>
+                <div style={{ display: 'flex', alignItems: 'center', gap: '1rem' }}>
+                    <label style={{ display: 'flex', alignItems: 'center', gap: '0.5rem', color: 'var(--text-secondary)' }}>
+                        <input
+                            type="checkbox"
+                            checked={safeApply}
+                            onChange={e => setSafeApply(e.target.checked)}
+                        />
+                        Safe apply ({SAFE_APPLY_TIMEOUT}s rollback)
+                    </label>
+                    <button
+                        className={`btn ${status?.active ? 'btn-danger' : 'btn-primary'}`}
+                        onClick={toggleFirewall}
+                        disabled={loading || !status || !!pending}
+                    >
+                        {status?.active ? 'Disable Firewall' : 'Enable Firewall'}
+                    </button>
+                </div>
             </div>
 
+            {pending && (
+                <div className="glass-panel" style={{
+                    marginBottom: '2rem',
+                    padding: '1.5rem',
+                    border: '1px solid var(--warning)',
+                    display: 'flex',
+                    justifyContent: 'space-between',
+                    alignItems: 'center',
+                    flexWrap: 'wrap',
+                    gap: '1rem'
+                }}>
+                    <div>
+                        <h3 style={{ margin: 0, color: 'var(--warning)' }}>
+                            {remaining > 0 ? `Rolling back in ${remaining}s` : 'Rolling back...'}
+                        </h3>
+                        <p style={{ margin: '0.5rem 0 0', color: 'var(--text-secondary)' }}>
+                            {pending.description}. {pending.reason ? `${pending.reason}. ` : ''}
+                            Check that you can still reach WADM and SSH, then keep the change.
+                        </p>
+                    </div>
+                    <div style={{ display: 'flex', gap: '0.5rem' }}>
+                        <button className="btn btn-primary" onClick={confirmChange} disabled={remaining === 0}>
+                            Keep Change
+                        </button>
+                        <button className="btn btn-danger" onClick={rollbackChange} disabled={remaining === 0}>
+                            Roll Back Now
+                        </button>
+                    </div>
+                </div>
+            )}
+
+            {status?.policy && (
+                <div className="glass-panel" style={{ marginBottom: '2rem' }}>
+                    <h3>Default Policy</h3>
+                    <div style={{ display: 'flex', gap: '1.5rem', flexWrap: 'wrap', alignItems: 'center' }}>
+                        {policyDirections.filter(d => status.policy?.[d.key]).map(d => (
+                            <label key={d.key} style={{ display: 'flex', alignItems: 'center', gap: '0.5rem' }}>
+                                {d.label}
+                                <select
+                                    className="input-field"
+                                    value={status.policy?.[d.key] ?? ''}
+                                    onChange={e => setPolicy(d.direction, e.target.value as RuleAction)}
+                                    disabled={!!pending}
+                                >
+                                    <option value="allow">Allow</option>
+                                    <option value="deny">Deny</option>
+                                    <option value="reject">Reject</option>
+                                </select>
+                            </label>
+                        ))}
+                        {status.policy.logging_levels.length > 0 && (
+                            <label style={{ display: 'flex', alignItems: 'center', gap: '0.5rem' }}>
+                                Logging
+                                <select
+                                    className="input-field"
+                                    value={status.policy.logging ?? ''}
+                                    onChange={e => setLogging(e.target.value)}
+                                >
+                                    {status.policy.logging_levels.map(level => (
+                                        <option key={level} value={level}>{level}</option>
+                                    ))}
+                                </select>
+                            </label>
+                        )}
+                    </div>
+                </div>
+            )}
+
             <div className="glass-panel">
                 <h3>Rules</h3>
 
-                <form onSubmit={addRule} style={{ display: 'flex', gap: '1rem', marginBottom: '2rem' }}>
+                <form onSubmit={addRule} style={{ display: 'flex', gap: '1rem', marginBottom: '2rem', flexWrap: 'wrap' }}>
+                    <select
+                        className="input-field"
+                        value={newRule.action}
+                        onChange={e => setNewRule({ ...newRule, action: e.target.value as RuleAction })}
+                    >
+                        <option value="allow">Allow</option>
+                        <option value="deny">Deny</option>
+                        <option value="reject">Reject</option>
+                        <option value="limit">Limit</option>
+                    </select>
+                    <select
+                        className="input-field"
+                        value={newRule.direction}
+                        onChange={e => setNewRule({ ...newRule, direction: e.target.value as Direction })}
+                    >
+                        <option value="in">In</option>
+                        <option value="out">Out</option>
+                    </select>
+                    <select
+                        className="input-field"
+                        value={newRule.protocol}
+                        onChange={e => setNewRule({ ...newRule, protocol: e.target.value as Protocol })}
+                    >
+                        <option value="tcp">TCP</option>
+                        <option value="udp">UDP</option>
+                        <option value="any">Any</option>
+                    </select>
                     <input
                         type="text"
                         className="input-field"
-                        placeholder="e.g. allow 80/tcp"
-                        value={newRule}
-                        onChange={e => setNewRule(e.target.value)}
+                        placeholder="Port (22, 80,443, 6000:6010)"
+                        value={newRule.port ?? ''}
+                        onChange={e => setNewRule({ ...newRule, port: e.target.value })}
                         style={{ flex: 1 }}
                     />
-                    <button type="submit" className="btn btn-primary" disabled={loading}>Add Rule</button>
+                    <input
+                        type="text"
+                        className="input-field"
+                        placeholder="Source (any, 10.0.0.0/8)"
+                        value={newRule.source ?? ''}
+                        onChange={e => setNewRule({ ...newRule, source: e.target.value })}
+                        style={{ flex: 1 }}
+                    />
+                    <input
+                        type="text"
+                        className="input-field"
+                        placeholder="Interface"
+                        value={newRule.interface ?? ''}
+                        onChange={e => setNewRule({ ...newRule, interface: e.target.value })}
+                        style={{ width: '8rem' }}
+                    />
+                    <input
+                        type="text"
+                        className="input-field"
+                        placeholder="Comment"
+                        value={newRule.comment ?? ''}
+                        onChange={e => setNewRule({ ...newRule, comment: e.target.value })}
+                        style={{ flex: 1 }}
+                    />
+                    <button type="submit" className="btn btn-primary" disabled={loading || !!pending}>Add Rule</button>
                 </form>
 
                 <div className="rules-list">
@@ -199,14 +744,32 @@ function Firewall() {
                         <table style={{ width: '100%', borderCollapse: 'collapse' }}>
                             <thead>
                                 <tr style={{ textAlign: 'left', borderBottom: '1px solid var(--glass-border)' }}>
-                                    <th style={{ padding: '1rem' }}>Rule</th>
-                                    <th style={{ padding: '1rem', textAlign: 'right' }}>Action</th>
+                                    <th style={{ padding: '1rem' }}>#</th>
+                                    <th style={{ padding: '1rem' }}>Action</th>
+                                    <th style={{ padding: '1rem' }}>To</th>
+                                    <th style={{ padding: '1rem' }}>From</th>
+                                    <th style={{ padding: '1rem' }}>Interface</th>
+                                    <th style={{ padding: '1rem' }}>Comment</th>
+                                    <th style={{ padding: '1rem', textAlign: 'right' }}></th>
                                 </tr>
                             </thead>
                             <tbody>
                                 {status.rules.map((rule, idx) => (
                                     <tr key={idx} style={{ borderBottom: '1px solid rgba(255,255,255,0.05)' }}>
-                                        <td style={{ padding: '1rem', fontFamily: 'monospace' }}>{rule}</td>
+                                        <td style={{ padding: '1rem', color: 'var(--text-secondary)' }}>{rule.number}</td>
+                                        <td style={{ padding: '1rem' }}>
+                                            <span className={`badge ${rule.action === 'allow' ? 'success' : rule.action === 'limit' ? 'neutral' : 'warning'}`}>
+                                                {rule.action.toUpperCase()} {rule.direction.toUpperCase()}
+                                            </span>
+                                        </td>
+                                        <td style={{ padding: '1rem', fontFamily: 'monospace' }}>
+                                            {describeTarget(rule)}{rule.v6 ? ' (v6)' : ''}
+                                        </td>
+                                        <td style={{ padding: '1rem', fontFamily: 'monospace' }}>
+                                            {rule.source ?? 'Anywhere'}{rule.source_port ? ` port ${rule.source_port}` : ''}
+                                        </td>
+                                        <td style={{ padding: '1rem' }}>{rule.interface ?? '-'}</td>
+                                        <td style={{ padding: '1rem', color: 'var(--text-secondary)' }}>{rule.comment ?? ''}</td>
                                         <td style={{ padding: '1rem', textAlign: 'right' }}>
                                             <button
                                                 onClick={() => deleteRule(rule)}
@@ -228,6 +791,287 @@ function Firewall() {
                 </div>
             </div>
 
+            {status?.backend !== 'nftables' && (
+                <div className="glass-panel" style={{ marginTop: '2rem' }}>
+                    <h3>Application Profiles</h3>
+
+                    <form onSubmit={saveProfile} style={{ display: 'flex', gap: '1rem', marginBottom: '2rem', flexWrap: 'wrap' }}>
+                        <input
+                            type="text"
+                            className="input-field"
+                            placeholder="Name"
+                            value={newProfile.name}
+                            onChange={e => setNewProfile({ ...newProfile, name: e.target.value })}
+                            style={{ width: '10rem' }}
+                        />
+                        <input
+                            type="text"
+                            className="input-field"
+                            placeholder="Title"
+                            value={newProfile.title}
+                            onChange={e => setNewProfile({ ...newProfile, title: e.target.value })}
+                            style={{ flex: 1 }}
+                        />
+                        <input
+                            type="text"
+                            className="input-field"
+                            placeholder="Ports (22/tcp | 80,443/tcp)"
+                            value={profilePorts}
+                            onChange={e => setProfilePorts(e.target.value)}
+                            style={{ flex: 1 }}
+                        />
+                        <button type="submit" className="btn btn-primary">Save Profile</button>
+                    </form>
+
+                    {profiles.length > 0 ? (
+                        <table style={{ width: '100%', borderCollapse: 'collapse' }}>
+                            <thead>
+                                <tr style={{ textAlign: 'left', borderBottom: '1px solid var(--glass-border)' }}>
+                                    <th style={{ padding: '1rem' }}>Name</th>
+                                    <th style={{ padding: '1rem' }}>Title</th>
+                                    <th style={{ padding: '1rem' }}>Ports</th>
+                                    <th style={{ padding: '1rem', textAlign: 'right' }}></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {profiles.map(profile => (
+                                    <tr key={profile.name} style={{ borderBottom: '1px solid rgba(255,255,255,0.05)' }}>
+                                        <td style={{ padding: '1rem' }}>
+                                            {profile.name}
+                                            {profile.custom && <span className="badge neutral" style={{ marginLeft: '0.5rem' }}>custom</span>}
+                                        </td>
+                                        <td style={{ padding: '1rem', color: 'var(--text-secondary)' }} title={profile.description}>{profile.title}</td>
+                                        <td style={{ padding: '1rem', fontFamily: 'monospace' }}>{profile.ports.join(' | ')}</td>
+                                        <td style={{ padding: '1rem', textAlign: 'right', whiteSpace: 'nowrap' }}>
+                                            <button
+                                                onClick={() => allowProfile(profile)}
+                                                className="btn-text item-action"
+                                                disabled={!!pending}
+                                                title="Allow incoming traffic for this profile"
+                                            >
+                                                Allow
+                                            </button>
+                                            {profile.custom && (
+                                                <button
+                                                    onClick={() => deleteProfile(profile)}
+                                                    className="btn-text item-action danger"
+                                                    title="Remove Profile"
+                                                >
+                                                    🗑️
+                                                </button>
+                                            )}
+                                        </td>
+                                    </tr>
+                                ))}
+                            </tbody>
+                        </table>
+                    ) : (
+                        <div style={{ padding: '2rem', textAlign: 'center', color: 'var(--text-secondary)' }}>
+                            No application profiles found.
+                        </div>
+                    )}
+                </div>
+            )}
+
+            <div className="glass-panel" style={{ marginTop: '2rem' }}>
+                <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'center', flexWrap: 'wrap', gap: '1rem' }}>
+                    <h3 style={{ margin: 0 }}>Import / Export</h3>
+                    <div style={{ display: 'flex', gap: '0.5rem' }}>
+                        <button className="btn" onClick={() => exportRuleset('json')}>Export JSON</button>
+                        <button className="btn" onClick={() => exportRuleset('yaml')}>Export YAML</button>
+                    </div>
+                </div>
+
+                {templates.length > 0 && (
+                    <div style={{ display: 'flex', gap: '0.5rem', flexWrap: 'wrap', margin: '1rem 0' }}>
+                        {templates.map(template => (
+                            <span key={template.name} className="badge neutral" title={template.description ?? ''}>
+                                <button className="btn-text" onClick={() => loadTemplate(template.name)}>
+                                    {template.name}
+                                </button>
+                                {!template.builtin && (
+                                    <button
+                                        onClick={() => deleteTemplate(template)}
+                                        className="btn-text item-action danger"
+                                        title="Remove Template"
+                                    >
+                                        ✕
+                                    </button>
+                                )}
+                            </span>
+                        ))}
+                    </div>
+                )}
+
+                <textarea
+                    className="input-field"
+                    placeholder="Paste an exported ruleset (JSON or YAML), load a file or pick a template"
+                    value={ruleset}
+                    onChange={e => { setRuleset(e.target.value); setPreview(null); }}
+                    rows={10}
+                    style={{ width: '100%', fontFamily: 'monospace', marginTop: '1rem' }}
+                />
+
+                <div style={{ display: 'flex', gap: '1rem', alignItems: 'center', flexWrap: 'wrap', marginTop: '1rem' }}>
+                    <input type="file" accept=".json,.yaml,.yml" onChange={loadRulesetFile} />
+                    <label style={{ display: 'flex', alignItems: 'center', gap: '0.5rem', color: 'var(--text-secondary)' }}>
+                        <input
+                            type="checkbox"
+                            checked={replaceRules}
+                            onChange={e => { setReplaceRules(e.target.checked); setPreview(null); }}
+                        />
+                        Remove rules not in the import
+                    </label>
+                    <button className="btn" onClick={previewImport} disabled={!ruleset.trim()}>Preview</button>
+                    <button className="btn btn-primary" onClick={applyImport} disabled={!preview || !!pending}>Apply</button>
+                    <input
+                        type="text"
+                        className="input-field"
+                        placeholder="Template name"
+                        value={templateName}
+                        onChange={e => setTemplateName(e.target.value)}
+                        style={{ width: '10rem' }}
+                    />
+                    <button className="btn" onClick={saveTemplate} disabled={!ruleset.trim() || !templateName.trim()}>
+                        Save as Template
+                    </button>
+                </div>
+
+                {preview && (
+                    <div style={{ marginTop: '1.5rem', fontFamily: 'monospace', lineHeight: '1.8' }}>
+                        {preview.enable != null && <div>{preview.enable ? 'Enable' : 'Disable'} the firewall</div>}
+                        {preview.policy.map(p => (
+                            <div key={p.direction}>Default {p.direction}: {p.from ?? 'unset'} → {p.to}</div>
+                        ))}
+                        {preview.logging && <div>Logging: {preview.logging}</div>}
+                        {preview.profiles.map(p => (
+                            <div key={p.name}>Profile {p.name}: {p.ports.join(' | ')}</div>
+                        ))}
+                        {preview.add.map((rule, idx) => (
+                            <div key={`add-${idx}`} style={{ color: '#4caf50' }}>
+                                + {rule.action} {rule.direction} {describeTarget(rule)} from {rule.source ?? 'anywhere'}
+                            </div>
+                        ))}
+                        {preview.remove.map((rule, idx) => (
+                            <div key={`remove-${idx}`} style={{ color: '#ff5252' }}>
+                                - {rule.action} {rule.direction} {describeTarget(rule)} from {rule.source ?? 'anywhere'}
+                            </div>
+                        ))}
+                        {preview.skipped.map((reason, idx) => (
+                            <div key={`skipped-${idx}`} style={{ color: 'var(--warning)' }}>Skipped: {reason}</div>
+                        ))}
+                        {preview.enable == null && preview.policy.length === 0 && !preview.logging && preview.profiles.length === 0
+                            && preview.add.length === 0 && preview.remove.length === 0 && (
+                            <div style={{ color: 'var(--text-secondary)' }}>The firewall already matches this ruleset.</div>
+                        )}
+                    </div>
+                )}
+            </div>
+
+            <div className="glass-panel" style={{ marginTop: '2rem' }}>
+                <div style={{ display: 'flex', justifyContent: 'space-between', alignItems: 'center', flexWrap: 'wrap', gap: '1rem' }}>
+                    <h3 style={{ margin: 0 }}>Blocked Traffic</h3>
+                    <div style={{ display: 'flex', alignItems: 'center', gap: '1rem' }}>
+                        {blocked && (
+                            <span style={{ color: 'var(--text-secondary)' }}>
+                                {blocked.total} blocked from {blocked.origin}
+                            </span>
+                        )}
+                        <button className="btn" onClick={fetchBlocked}>Refresh</button>
+                    </div>
+                </div>
+
+                {blocked && blocked.total > 0 ? (
+                    <>
+                        <div style={{ display: 'flex', gap: '2rem', flexWrap: 'wrap', marginTop: '1rem' }}>
+                            <table style={{ flex: 1, minWidth: '18rem', borderCollapse: 'collapse' }}>
+                                <thead>
+                                    <tr style={{ textAlign: 'left', borderBottom: '1px solid var(--glass-border)' }}>
+                                        <th style={{ padding: '0.75rem' }}>Top Sources</th>
+                                        <th style={{ padding: '0.75rem' }}>Blocks</th>
+                                        <th style={{ padding: '0.75rem', textAlign: 'right' }}></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {blocked.top_sources.map(source => (
+                                        <tr key={source.value} style={{ borderBottom: '1px solid rgba(255,255,255,0.05)' }}>
+                                            <td style={{ padding: '0.75rem', fontFamily: 'monospace' }}>{source.value}</td>
+                                            <td style={{ padding: '0.75rem' }}>{source.count}</td>
+                                            <td style={{ padding: '0.75rem', textAlign: 'right' }}>
+                                                <button
+                                                    onClick={() => denySource(source.value)}
+                                                    className="btn-text item-action danger"
+                                                    disabled={!!pending}
+                                                    title="Deny all traffic from this source"
+                                                >
+                                                    Deny
+                                                </button>
+                                            </td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                            <table style={{ flex: 1, minWidth: '12rem', borderCollapse: 'collapse' }}>
+                                <thead>
+                                    <tr style={{ textAlign: 'left', borderBottom: '1px solid var(--glass-border)' }}>
+                                        <th style={{ padding: '0.75rem' }}>Top Ports</th>
+                                        <th style={{ padding: '0.75rem' }}>Blocks</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {blocked.top_ports.map(port => (
+                                        <tr key={port.value} style={{ borderBottom: '1px solid rgba(255,255,255,0.05)' }}>
+                                            <td style={{ padding: '0.75rem', fontFamily: 'monospace' }}>{port.value}</td>
+                                            <td style={{ padding: '0.75rem' }}>{port.count}</td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                        </div>
+
+                        <div style={{ maxHeight: '24rem', overflowY: 'auto', marginTop: '1.5rem' }}>
+                            <table style={{ width: '100%', borderCollapse: 'collapse' }}>
+                                <thead>
+                                    <tr style={{ textAlign: 'left', borderBottom: '1px solid var(--glass-border)' }}>
+                                        <th style={{ padding: '0.75rem' }}>Time</th>
+                                        <th style={{ padding: '0.75rem' }}>Source</th>
+                                        <th style={{ padding: '0.75rem' }}>Port</th>
+                                        <th style={{ padding: '0.75rem' }}>Protocol</th>
+                                        <th style={{ padding: '0.75rem' }}>Interface</th>
+                                        <th style={{ padding: '0.75rem', textAlign: 'right' }}></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    {blocked.events.map((event, idx) => (
+                                        <tr key={idx} style={{ borderBottom: '1px solid rgba(255,255,255,0.05)' }}>
+                                            <td style={{ padding: '0.75rem', color: 'var(--text-secondary)', whiteSpace: 'nowrap' }}>{event.time}</td>
+                                            <td style={{ padding: '0.75rem', fontFamily: 'monospace' }}>{event.source}</td>
+                                            <td style={{ padding: '0.75rem', fontFamily: 'monospace' }}>{event.port ?? '-'}</td>
+                                            <td style={{ padding: '0.75rem' }}>{event.protocol ?? '-'}</td>
+                                            <td style={{ padding: '0.75rem' }}>{event.interface ?? '-'}</td>
+                                            <td style={{ padding: '0.75rem', textAlign: 'right' }}>
+                                                <button
+                                                    onClick={() => denySource(event.source)}
+                                                    className="btn-text item-action danger"
+                                                    disabled={!!pending}
+                                                    title="Deny all traffic from this source"
+                                                >
+                                                    Deny
+                                                </button>
+                                            </td>
+                                        </tr>
+                                    ))}
+                                </tbody>
+                            </table>
+                        </div>
+                    </>
+                ) : (
+                    <div style={{ padding: '2rem', textAlign: 'center', color: 'var(--text-secondary)' }}>
+                        No blocked traffic has been logged. Blocks are only recorded while firewall logging is on.
+                    </div>
+                )}
+            </div>
+
 
             {
                 showReboot && (